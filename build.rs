@@ -0,0 +1,42 @@
+fn main() {
+    #[cfg(feature = "grpc")]
+    grpc::generate();
+}
+
+/// The gRPC service stubs are generated from a Rust description rather than
+/// from `proto/sentra.proto`, so building needs no `protoc`. Keep the two in
+/// sync; the messages live in `src/grpc.rs`.
+#[cfg(feature = "grpc")]
+mod grpc {
+    use tonic_build::manual::{Builder, Method, Service};
+
+    fn method(name: &str, route: &str, input: &str, output: &str) -> Method {
+        Method::builder()
+            .name(name)
+            .route_name(route)
+            .input_type(input)
+            .output_type(output)
+            .codec_path("tonic::codec::ProstCodec")
+            .build()
+    }
+
+    pub fn generate() {
+        let service = Service::builder()
+            .name("Sentra")
+            .package("sentra.v1")
+            .method(method(
+                "validate",
+                "Validate",
+                "crate::grpc::ValidateRequest",
+                "crate::grpc::ValidateResponse",
+            ))
+            .method(method(
+                "analyze_tool_execution",
+                "AnalyzeToolExecution",
+                "crate::grpc::AnalyzeRequest",
+                "crate::grpc::AnalyzeResponse",
+            ))
+            .build();
+        Builder::new().build_client(true).compile(&[service]);
+    }
+}
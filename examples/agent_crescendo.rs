@@ -24,6 +24,7 @@
 //! The simulator stops early if a phase that is expected to allow blocks (unexpected),
 //! or if an expected block does not occur. Summary printed at end.
 
+use sentra::AnalyzeRequest;
 use serde_json::json;
 use std::time::Duration;
 use tokio::time::sleep;
@@ -45,6 +46,7 @@ struct Phase<'a> {
     expect_reason_min: Option<i32>,
 }
 
+#[allow(dead_code)]
 #[derive(serde::Deserialize)]
 struct DecisionResp {
     #[allow(dead_code)]
@@ -63,11 +65,13 @@ async fn send(
     phase: &Phase<'_>,
 ) -> anyhow::Result<(bool, Option<i32>, Option<String>)> {
     let client = reqwest::Client::new();
-    let body = json!({
-        "plannerContext": {"userMessage": phase.user_msg},
-        "toolDefinition": {"name": phase.tool},
-        "inputValues": phase.input,
-    });
+    let mut builder = AnalyzeRequest::builder()
+        .user_message(phase.user_msg)
+        .tool(phase.tool);
+    for (key, value) in phase.input.as_object().into_iter().flatten() {
+        builder = builder.input(key.as_str(), value.clone());
+    }
+    let body = builder.build();
     let url = format!(
         "{}/analyze-tool-execution?api-version=2025-05-01",
         config.base_url
@@ -135,7 +139,7 @@ async fn main() -> anyhow::Result<()> {
         delay_ms,
     };
 
-    let phases = vec![
+    let phases = [
         Phase {
             name: "benign",
             user_msg: "Summarize quarterly revenue trends",
@@ -22,7 +22,7 @@
 use std::time::{Duration, Instant};
 
 use reqwest::Client;
-use serde_json::json;
+use sentra::AnalyzeRequest;
 use tokio::sync::Semaphore;
 
 #[derive(Default, Debug)]
@@ -32,7 +32,7 @@ struct Stats {
     blocked: usize,
     allowed: usize,
     errors: usize,
-    reason_counts: HashMap<i64, usize>,
+    reason_counts: HashMap<i32, usize>,
 }
 
 #[tokio::main]
@@ -103,32 +103,29 @@ async fn main() {
             }; // semaphore closed
             let scenario = i % 5; // rotate across 5 payload types
             let body = match scenario {
-                0 => json!({
-                    "plannerContext": {"userMessage": "Generate summary"},
-                    "toolDefinition": {"name": "SendEmail"},
-                    "inputValues": {"to": "alice@yourcompany.com"}
-                }),
-                1 => json!({
-                    "plannerContext": {"userMessage": "Here is key AKIAZZZZZZZZZZ123456"},
-                    "toolDefinition": {"name": "SendEmail"},
-                    "inputValues": {"to": "dev@yourcompany.com"}
-                }),
-                2 => json!({
-                    "plannerContext": {"userMessage": "Export all data right now"},
-                    "toolDefinition": {"name": "DataExport"},
-                    "inputValues": {"table": "users"}
-                }),
-                3 => json!({
-                    "plannerContext": {"userMessage": "Contact me at bob.external@gmail.com"},
-                    "toolDefinition": {"name": "SendEmail"},
-                    "inputValues": {"to": "team@yourcompany.com"}
-                }),
-                _ => json!({
-                    "plannerContext": {"userMessage": "Check this"},
-                    "toolDefinition": {"name": "SendEmail"},
-                    "inputValues": {"to": "team@yourcompany.com", "url": "http://mailinator.com/inbox"}
-                }),
-            };
+                0 => AnalyzeRequest::builder()
+                    .user_message("Generate summary")
+                    .tool("SendEmail")
+                    .input("to", "alice@yourcompany.com"),
+                1 => AnalyzeRequest::builder()
+                    .user_message("Here is key AKIAZZZZZZZZZZ123456")
+                    .tool("SendEmail")
+                    .input("to", "dev@yourcompany.com"),
+                2 => AnalyzeRequest::builder()
+                    .user_message("Export all data right now")
+                    .tool("DataExport")
+                    .input("table", "users"),
+                3 => AnalyzeRequest::builder()
+                    .user_message("Contact me at bob.external@gmail.com")
+                    .tool("SendEmail")
+                    .input("to", "team@yourcompany.com"),
+                _ => AnalyzeRequest::builder()
+                    .user_message("Check this")
+                    .tool("SendEmail")
+                    .input("to", "team@yourcompany.com")
+                    .input("url", "http://mailinator.com/inbox"),
+            }
+            .build();
             let t0 = Instant::now();
             let resp = client
                 .post(&endpoint)
@@ -141,7 +138,7 @@ async fn main() {
             // Collect metrics outside lock
             let mut status_code: Option<u16> = None;
             let mut blocked: Option<bool> = None;
-            let mut reason_code: Option<i64> = None;
+            let mut reason_code: Option<i32> = None;
             let mut parse_error = false;
             match resp {
                 Ok(r) => {
@@ -149,7 +146,10 @@ async fn main() {
                     match r.json::<serde_json::Value>().await {
                         Ok(v) => {
                             blocked = v.get("blockAction").and_then(|b| b.as_bool());
-                            reason_code = v.get("reasonCode").and_then(|rc| rc.as_i64());
+                            reason_code = v
+                                .get("reasonCode")
+                                .and_then(|rc| rc.as_i64())
+                                .and_then(|rc| i32::try_from(rc).ok());
                         }
                         Err(_) => parse_error = true,
                     }
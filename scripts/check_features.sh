@@ -0,0 +1,28 @@
+#!/usr/bin/env bash
+# Compile-check the feature combinations embedders are likely to use, so an
+# optional dependency leaking into the core pipeline fails CI.
+set -euo pipefail
+
+combos=(
+  "--no-default-features"
+  "--no-default-features --features external-http"
+  "--no-default-features --features compression"
+  "--no-default-features --features metrics"
+  "--no-default-features --features tls"
+  "--no-default-features --features strict-schema"
+  "--no-default-features --features unicode-normalization"
+  "--no-default-features --features grpc"
+  ""
+  # Everything but `wasm`, which replaces the server rather than adding to it.
+  "--features unicode-normalization,grpc"
+)
+
+for flags in "${combos[@]}"; do
+  echo "==> cargo check --all-targets ${flags:-(default features)}"
+  # shellcheck disable=SC2086
+  cargo check --all-targets $flags
+done
+
+# The runtime-free core; server-only tests compile out under wasm.
+echo "==> cargo check --all-targets --no-default-features --features wasm"
+cargo check --all-targets --no-default-features --features wasm
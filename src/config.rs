@@ -4,7 +4,17 @@
 
 use anyhow::{anyhow, Context, Result};
 
-use crate::plugins::{parse_plugin_order, PluginConfig};
+use crate::plugins::external_http::total_timeout_ms;
+use crate::plugins::policy_pack::PolicyRule;
+use crate::plugins::{
+    default_plugin_order, parse_plugin_order, DiagnosticsLevel, PluginConfig,
+    DEFAULT_COMPANY_DOMAIN,
+};
+
+const DEFAULT_PLUGIN_BUDGET_MS: u64 = 900;
+const DEFAULT_PLUGIN_WARN_MS: u64 = 120;
+const DEFAULT_GZIP_MIN_BYTES: usize = 1024;
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 16 * 1024;
 
 #[derive(Debug, Clone)]
 pub struct RotationConfig {
@@ -13,6 +23,97 @@ pub struct RotationConfig {
     pub compress: bool,
 }
 
+impl Default for RotationConfig {
+    fn default() -> Self {
+        Self {
+            max_bytes: None,
+            keep: 1,
+            compress: false,
+        }
+    }
+}
+
+/// What happens to a `userMessage` longer than `SENTRA_MAX_USER_MESSAGE_CHARS`
+/// (`SENTRA_USER_MESSAGE_OVERFLOW`).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum UserMessageOverflow {
+    /// Scan only the first `limit` characters.
+    #[default]
+    Truncate,
+    /// Reject the request with `errorCode` 4005.
+    Reject,
+}
+
+impl UserMessageOverflow {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "truncate" => Some(UserMessageOverflow::Truncate),
+            "reject" => Some(UserMessageOverflow::Reject),
+            _ => None,
+        }
+    }
+}
+
+/// Connection tuning for the server binary (`sentra::server`). Defaults
+/// match hyper's, with HTTP/2 off.
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    /// Accept HTTP/2: prior-knowledge h2c on the plain listener, ALPN `h2`
+    /// with TLS (`SENTRA_HTTP2`).
+    pub http2: bool,
+    /// Reuse HTTP/1.1 connections (`SENTRA_HTTP_KEEPALIVE`, default on).
+    pub keep_alive: bool,
+    /// Time a client has to send request headers (`SENTRA_HTTP_HEADER_TIMEOUT_MS`).
+    pub header_read_timeout_ms: u64,
+    /// HTTP/2 PING interval; `None` sends no PINGs
+    /// (`SENTRA_HTTP2_KEEPALIVE_INTERVAL_MS`).
+    pub http2_keep_alive_interval_ms: Option<u64>,
+    /// Close an HTTP/2 connection whose PING is not acknowledged within this
+    /// (`SENTRA_HTTP2_KEEPALIVE_TIMEOUT_MS`).
+    pub http2_keep_alive_timeout_ms: u64,
+    /// Concurrent streams per HTTP/2 connection (`SENTRA_HTTP2_MAX_STREAMS`;
+    /// `None` keeps hyper's default).
+    pub http2_max_concurrent_streams: Option<u32>,
+    /// How long shutdown waits for open connections before dropping them
+    /// (`SENTRA_SHUTDOWN_TIMEOUT_MS`); `None` waits indefinitely.
+    pub shutdown_timeout_ms: Option<u64>,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            http2: false,
+            keep_alive: true,
+            header_read_timeout_ms: 30_000,
+            http2_keep_alive_interval_ms: None,
+            http2_keep_alive_timeout_ms: 20_000,
+            http2_max_concurrent_streams: None,
+            shutdown_timeout_ms: None,
+        }
+    }
+}
+
+impl ServerOptions {
+    pub fn from_env() -> Result<Self> {
+        let defaults = Self::default();
+        let max_streams = parse_optional_u64("SENTRA_HTTP2_MAX_STREAMS")?
+            .map(u32::try_from)
+            .transpose()
+            .map_err(|_| anyhow!("SENTRA_HTTP2_MAX_STREAMS is too large"))?;
+        Ok(Self {
+            http2: parse_bool_env("SENTRA_HTTP2")?.unwrap_or(defaults.http2),
+            keep_alive: parse_bool_env("SENTRA_HTTP_KEEPALIVE")?.unwrap_or(defaults.keep_alive),
+            header_read_timeout_ms: parse_optional_u64("SENTRA_HTTP_HEADER_TIMEOUT_MS")?
+                .unwrap_or(defaults.header_read_timeout_ms),
+            http2_keep_alive_interval_ms: parse_optional_u64("SENTRA_HTTP2_KEEPALIVE_INTERVAL_MS")?,
+            http2_keep_alive_timeout_ms: parse_optional_u64("SENTRA_HTTP2_KEEPALIVE_TIMEOUT_MS")?
+                .unwrap_or(defaults.http2_keep_alive_timeout_ms),
+            http2_max_concurrent_streams: max_streams,
+            shutdown_timeout_ms: parse_optional_u64("SENTRA_SHUTDOWN_TIMEOUT_MS")?,
+        })
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct AppConfig {
     pub plugin_config: PluginConfig,
@@ -23,33 +124,141 @@ pub struct AppConfig {
     pub rotation: RotationConfig,
     pub log_stdout: bool,
     pub max_request_bytes: Option<usize>,
+    /// gzip responses at least this large when the client accepts it
+    /// (`SENTRA_GZIP_MIN_BYTES`, feature `compression`).
+    pub gzip_min_bytes: usize,
+    /// Largest serialized decision returned; bigger ones have their
+    /// diagnostics summarized (`SENTRA_MAX_RESPONSE_BYTES`).
+    pub max_response_bytes: usize,
     pub plugin_budget_ms: u64,
     pub plugin_warn_ms: u64,
     pub audit_only: bool,
+    pub audit_full_request: bool,
     pub log_sample_n: Option<u64>,
+    /// Secret for signing telemetry/audit lines (`SENTRA_TELEMETRY_HMAC_KEY`).
+    pub telemetry_hmac_key: Option<String>,
+    /// Capacity of the background telemetry write queue
+    /// (`SENTRA_TELEMETRY_QUEUE`); `None` writes on the request path.
+    pub telemetry_queue: Option<usize>,
+    /// Bearer token `/metrics` requires when set (`SENTRA_METRICS_TOKEN`).
+    pub metrics_token: Option<String>,
+    pub require_metadata: bool,
+    /// Longest `userMessage` scanned, in characters (`SENTRA_MAX_USER_MESSAGE_CHARS`).
+    pub max_user_message_chars: Option<usize>,
+    pub user_message_overflow: UserMessageOverflow,
+    /// Validate analyze bodies against the request schema (`SENTRA_STRICT_SCHEMA`).
+    pub strict_schema: bool,
+    pub verbose_allow: bool,
+    /// Log redacted request/decision pairs at debug level (`SENTRA_DEBUG_CAPTURE`).
+    pub debug_capture: bool,
+    pub evaluate_all: bool,
+    pub telemetry_plugin_diagnostics: bool,
+}
+
+/// The configuration `from_env` produces with no environment variables set.
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            plugin_config: PluginConfig::default(),
+            plugin_order: default_plugin_order(),
+            log_file: None,
+            audit_log_file: None,
+            allowed_tokens: None,
+            rotation: RotationConfig::default(),
+            log_stdout: false,
+            max_request_bytes: None,
+            gzip_min_bytes: DEFAULT_GZIP_MIN_BYTES,
+            max_response_bytes: DEFAULT_MAX_RESPONSE_BYTES,
+            plugin_budget_ms: DEFAULT_PLUGIN_BUDGET_MS,
+            plugin_warn_ms: DEFAULT_PLUGIN_WARN_MS,
+            audit_only: false,
+            audit_full_request: false,
+            log_sample_n: None,
+            telemetry_hmac_key: None,
+            telemetry_queue: None,
+            metrics_token: None,
+            require_metadata: false,
+            max_user_message_chars: None,
+            user_message_overflow: UserMessageOverflow::default(),
+            strict_schema: false,
+            verbose_allow: false,
+            debug_capture: false,
+            evaluate_all: false,
+            telemetry_plugin_diagnostics: false,
+        }
+    }
 }
 
 impl AppConfig {
     pub fn from_env() -> Result<Self> {
-        let plugin_config = if let Ok(path) = env::var("SENTRA_PLUGIN_CONFIG") {
-            let content = fs::read_to_string(&path).with_context(|| {
-                format!(
-                    "Failed to read SENTRA_PLUGIN_CONFIG '{}': file unreadable",
-                    path
-                )
-            })?;
-            serde_json::from_str::<PluginConfig>(&content).with_context(|| {
-                format!(
-                    "Failed to parse SENTRA_PLUGIN_CONFIG '{}': invalid JSON configuration",
-                    path
-                )
-            })?
+        let mut plugin_config = if let Ok(paths) = env::var("SENTRA_PLUGIN_CONFIG") {
+            load_plugin_config(&paths)?
         } else {
             PluginConfig::default()
         };
 
+        // Policy rules maintained separately are appended after inline rules.
+        if let Ok(path) = env::var("SENTRA_POLICIES_FILE") {
+            let rules = load_json_file::<Vec<PolicyRule>>("SENTRA_POLICIES_FILE", &path)?;
+            plugin_config.policies.extend(rules);
+        }
+
+        if let Some(strict) = parse_bool_env("SENTRA_SECRETS_AWS_STRICT")? {
+            plugin_config.secrets_aws_strict = strict;
+        }
+        // `SENTRA_DIAGNOSTICS_MODE` is the older compact/verbose switch;
+        // `SENTRA_DIAGNOSTICS_LEVEL` wins when both are set.
+        match env::var("SENTRA_DIAGNOSTICS_MODE") {
+            Ok(value) if !value.trim().is_empty() => {
+                plugin_config.diagnostics_level = DiagnosticsLevel::parse_mode(&value)
+                    .ok_or_else(|| anyhow!("SENTRA_DIAGNOSTICS_MODE must be compact or verbose"))?;
+            }
+            _ => {}
+        }
+        match env::var("SENTRA_DIAGNOSTICS_LEVEL") {
+            Ok(value) if !value.trim().is_empty() => {
+                plugin_config.diagnostics_level =
+                    DiagnosticsLevel::parse(&value).ok_or_else(|| {
+                        anyhow!("SENTRA_DIAGNOSTICS_LEVEL must be minimal, standard or verbose")
+                    })?;
+            }
+            _ => {}
+        }
+
+        // Env-only deployments set the company domain(s) here; both
+        // override the plugin config file.
+        if let Ok(domain) = env::var("SENTRA_COMPANY_DOMAIN") {
+            if !domain.trim().is_empty() {
+                plugin_config.company_domain = domain.trim().to_string();
+            }
+        }
+        if let Ok(raw) = env::var("SENTRA_COMPANY_DOMAINS") {
+            let domains: Vec<String> = raw
+                .split(',')
+                .map(str::trim)
+                .filter(|d| !d.is_empty())
+                .map(str::to_string)
+                .collect();
+            if !domains.is_empty() {
+                plugin_config.company_domains = domains;
+            }
+        }
+
         let plugin_order = parse_plugin_order();
 
+        if uses_placeholder_company_domain(&plugin_order, &plugin_config) {
+            if parse_bool_env("SENTRA_REQUIRE_COMPANY_DOMAIN")?.unwrap_or(false) {
+                return Err(anyhow!(
+                    "company_domain is unset (placeholder '{}') while pii/email_bcc are enabled; set company_domain in SENTRA_PLUGIN_CONFIG or SENTRA_COMPANY_DOMAIN",
+                    DEFAULT_COMPANY_DOMAIN
+                ));
+            }
+            tracing::warn!(
+                company_domain = DEFAULT_COMPANY_DOMAIN,
+                "company_domain is the placeholder default; pii/email_bcc checks will treat your real domain as external"
+            );
+        }
+
         let log_file = env::var("LOG_FILE").ok();
         let audit_log_file = env::var("AUDIT_LOG_FILE").ok();
 
@@ -66,16 +275,68 @@ pub fn from_env() -> Result<Self> {
 
         let rotation = RotationConfig {
             max_bytes: parse_optional_u64("LOG_MAX_BYTES")?,
-            keep: parse_optional_u64("LOG_ROTATE_KEEP")?.unwrap_or(1) as usize,
+            keep: parse_optional_u64("LOG_ROTATE_KEEP")?
+                .map_or(RotationConfig::default().keep, |n| n as usize),
             compress: parse_bool_env("LOG_ROTATE_COMPRESS")?.unwrap_or(false),
         };
 
         let log_stdout = parse_bool_env("SENTRA_LOG_STDOUT")?.unwrap_or(false);
         let audit_only = parse_bool_env("SENTRA_AUDIT_ONLY")?.unwrap_or(false);
+        let audit_full_request = parse_bool_env("SENTRA_AUDIT_FULL_REQUEST")?.unwrap_or(false);
+        let require_metadata = parse_bool_env("SENTRA_REQUIRE_METADATA")?.unwrap_or(false);
+        let strict_schema = parse_bool_env("SENTRA_STRICT_SCHEMA")?.unwrap_or(false);
+        let verbose_allow = parse_bool_env("SENTRA_VERBOSE_ALLOW")?.unwrap_or(false);
+        let debug_capture = parse_bool_env("SENTRA_DEBUG_CAPTURE")?.unwrap_or(false);
+        let evaluate_all = parse_bool_env("SENTRA_EVALUATE_ALL")?.unwrap_or(false);
+        let telemetry_plugin_diagnostics =
+            parse_bool_env("SENTRA_TELEMETRY_PLUGIN_DIAGNOSTICS")?.unwrap_or(false);
         let max_request_bytes = parse_optional_u64("SENTRA_MAX_REQUEST_BYTES")?.map(|v| v as usize);
-        let plugin_budget_ms = parse_optional_u64("SENTRA_PLUGIN_BUDGET_MS")?.unwrap_or(900);
-        let plugin_warn_ms = parse_optional_u64("SENTRA_PLUGIN_WARN_MS")?.unwrap_or(120);
+        let max_user_message_chars =
+            parse_optional_u64("SENTRA_MAX_USER_MESSAGE_CHARS")?.map(|v| v as usize);
+        let user_message_overflow = match env::var("SENTRA_USER_MESSAGE_OVERFLOW") {
+            Ok(value) if !value.trim().is_empty() => UserMessageOverflow::parse(&value)
+                .ok_or_else(|| {
+                    anyhow!("SENTRA_USER_MESSAGE_OVERFLOW must be truncate or reject")
+                })?,
+            _ => UserMessageOverflow::default(),
+        };
+        let gzip_min_bytes = parse_optional_u64("SENTRA_GZIP_MIN_BYTES")?
+            .map_or(DEFAULT_GZIP_MIN_BYTES, |v| v as usize);
+        let max_response_bytes = parse_optional_u64("SENTRA_MAX_RESPONSE_BYTES")?
+            .map_or(DEFAULT_MAX_RESPONSE_BYTES, |v| v as usize);
+        let plugin_budget_ms =
+            parse_optional_u64("SENTRA_PLUGIN_BUDGET_MS")?.unwrap_or(DEFAULT_PLUGIN_BUDGET_MS);
+        let plugin_warn_ms =
+            parse_optional_u64("SENTRA_PLUGIN_WARN_MS")?.unwrap_or(DEFAULT_PLUGIN_WARN_MS);
+
+        // Externals run one after another, so their timeouts add up. Past the
+        // budget, later plugins are skipped when services hang: a silent fail-open.
+        let external_timeout_ms = total_timeout_ms(&plugin_order, &plugin_config.external_http);
+        if external_timeout_ms > plugin_budget_ms {
+            if parse_bool_env("SENTRA_STRICT_EXTERNAL_BUDGET")?.unwrap_or(false) {
+                return Err(anyhow!(
+                    "worst-case external_http latency {}ms exceeds SENTRA_PLUGIN_BUDGET_MS {}ms",
+                    external_timeout_ms,
+                    plugin_budget_ms
+                ));
+            }
+            tracing::warn!(
+                external_timeout_ms,
+                plugin_budget_ms,
+                "sum of external_http timeouts exceeds plugin budget; later plugins may be skipped"
+            );
+        }
         let log_sample_n = parse_optional_u64("SENTRA_LOG_SAMPLE_N")?.filter(|n| *n > 1);
+        let telemetry_hmac_key = env::var("SENTRA_TELEMETRY_HMAC_KEY")
+            .ok()
+            .filter(|k| !k.is_empty());
+        let telemetry_queue = parse_optional_u64("SENTRA_TELEMETRY_QUEUE")?
+            .filter(|n| *n > 0)
+            .map(|n| n as usize);
+        let metrics_token = env::var("SENTRA_METRICS_TOKEN")
+            .ok()
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty());
 
         Ok(Self {
             plugin_config,
@@ -86,14 +347,281 @@ pub fn from_env() -> Result<Self> {
             rotation,
             log_stdout,
             max_request_bytes,
+            gzip_min_bytes,
+            max_response_bytes,
             plugin_budget_ms,
             plugin_warn_ms,
             audit_only,
+            audit_full_request,
             log_sample_n,
+            telemetry_hmac_key,
+            telemetry_queue,
+            metrics_token,
+            require_metadata,
+            max_user_message_chars,
+            user_message_overflow,
+            strict_schema,
+            verbose_allow,
+            debug_capture,
+            evaluate_all,
+            telemetry_plugin_diagnostics,
         })
     }
 }
 
+/// Read, env-interpolate and deserialize a JSON file named by `var`.
+fn load_json_file<T: serde::de::DeserializeOwned>(var: &str, path: &str) -> Result<T> {
+    let raw = load_json_value(var, path)?;
+    serde_json::from_value::<T>(raw).with_context(|| {
+        format!(
+            "Failed to parse {} '{}': invalid JSON configuration",
+            var, path
+        )
+    })
+}
+
+/// Read and env-interpolate a JSON file named by `var`.
+fn load_json_value(var: &str, path: &str) -> Result<serde_json::Value> {
+    let content = fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {} '{}': file unreadable", var, path))?;
+    let mut raw: serde_json::Value = serde_json::from_str(&content).with_context(|| {
+        format!(
+            "Failed to parse {} '{}': invalid JSON configuration",
+            var, path
+        )
+    })?;
+    interpolate_env(&mut raw).with_context(|| {
+        format!(
+            "Failed to resolve {} '{}': environment interpolation failed",
+            var, path
+        )
+    })?;
+    Ok(raw)
+}
+
+/// Load `SENTRA_PLUGIN_CONFIG`, which may list several comma-separated files
+/// (e.g. a base config followed by an environment overlay). Several files are
+/// key-normalized and merged in order with [`merge_config`] before
+/// deserializing; a single file is deserialized as written.
+fn load_plugin_config(paths: &str) -> Result<PluginConfig> {
+    const VAR: &str = "SENTRA_PLUGIN_CONFIG";
+    let paths: Vec<&str> = paths
+        .split(',')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    if let [path] = paths.as_slice() {
+        return load_json_file(VAR, path);
+    }
+    let mut merged = serde_json::Value::Object(serde_json::Map::new());
+    for path in &paths {
+        let mut layer = load_json_value(VAR, path)?;
+        let serde_json::Value::Object(map) = &mut layer else {
+            return Err(anyhow!(
+                "Failed to parse {} '{}': expected a JSON object",
+                VAR,
+                path
+            ));
+        };
+        normalize_keys(map);
+        merge_config(&mut merged, layer);
+    }
+    serde_json::from_value(merged).with_context(|| {
+        format!(
+            "Failed to parse {} '{}': invalid JSON configuration",
+            VAR,
+            paths.join(",")
+        )
+    })
+}
+
+/// Entries of these lists deserialize from camelCase keys; everything else
+/// in a plugin config file uses snake_case field names.
+const CAMEL_CASE_LISTS: [&str; 3] = ["external_http", "exfil_patterns", "policies"];
+
+/// Rewrite a layer's keys to the spelling the config structs expect, so that
+/// `domainBlocklist` in one file and `domain_blocklist` in another (or
+/// `timeoutMs` and `timeout_ms` inside an `external_http` entry) merge into
+/// the same field. Keys the operator chooses, such as plugin names in
+/// `plugin_timeouts` or tokens in `bypass_tenants`, are kept as written.
+fn normalize_keys(map: &mut serde_json::Map<String, serde_json::Value>) {
+    use serde_json::Value;
+    rename_keys(map, snake_case);
+    for (key, value) in map.iter_mut() {
+        match (key.as_str(), value) {
+            (list, Value::Array(entries)) if CAMEL_CASE_LISTS.contains(&list) => {
+                for entry in entries {
+                    if let Value::Object(entry) = entry {
+                        rename_keys(entry, camel_case);
+                    }
+                }
+            }
+            ("trust" | "monitor", Value::Object(inner)) => rename_keys(inner, snake_case),
+            ("plugin_timeouts", Value::Object(timeouts)) => {
+                for timeout in timeouts.values_mut() {
+                    if let Value::Object(timeout) = timeout {
+                        rename_keys(timeout, snake_case);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn rename_keys(map: &mut serde_json::Map<String, serde_json::Value>, rename: fn(&str) -> String) {
+    *map = std::mem::take(map)
+        .into_iter()
+        .map(|(k, v)| (rename(&k), v))
+        .collect();
+}
+
+/// Merge `overlay` into `base`: objects merge key by key, lists (such as
+/// `policies` or `domain_blocklist`) are appended, and any other value
+/// (scalars, null, or a type change) is replaced by the overlay.
+/// `external_http` entries are matched by `name`, so an overlay can adjust
+/// one service (say its `url`) without registering it twice.
+fn merge_config(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    use serde_json::Value;
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (k, v) in overlay {
+                match base.get_mut(&k) {
+                    Some(existing) if k == "external_http" => merge_by_name(existing, v),
+                    Some(existing) => merge_config(existing, v),
+                    None => {
+                        base.insert(k, v);
+                    }
+                }
+            }
+        }
+        (Value::Array(base), Value::Array(overlay)) => base.extend(overlay),
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Merge each overlay entry into the base entry with the same `name`,
+/// appending entries that are new (or unnamed).
+fn merge_by_name(base: &mut serde_json::Value, overlay: serde_json::Value) {
+    use serde_json::Value;
+    match (base, overlay) {
+        (Value::Array(base), Value::Array(overlay)) => {
+            for entry in overlay {
+                let same_name = |existing: &&mut Value| {
+                    entry.get("name").is_some() && existing.get("name") == entry.get("name")
+                };
+                match base.iter_mut().find(same_name) {
+                    Some(existing) => merge_config(existing, entry),
+                    None => base.push(entry),
+                }
+            }
+        }
+        (base, overlay) => merge_config(base, overlay),
+    }
+}
+
+fn camel_case(key: &str) -> String {
+    let mut out = String::with_capacity(key.len());
+    let mut upper = false;
+    for c in key.chars() {
+        match c {
+            '_' => upper = !out.is_empty(),
+            c if upper => {
+                out.push(c.to_ascii_uppercase());
+                upper = false;
+            }
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// `timeoutMs` -> `timeout_ms`. Only a lone capital after the first
+/// character starts a new word; runs of capitals (`URL`, `maxURL`) are kept
+/// as written.
+fn snake_case(key: &str) -> String {
+    let chars: Vec<char> = key.chars().collect();
+    let upper = |i: usize| chars.get(i).is_some_and(char::is_ascii_uppercase);
+    let mut out = String::with_capacity(key.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if i > 0 && upper(i) && !upper(i - 1) && !upper(i + 1) {
+            out.push('_');
+            out.push(c.to_ascii_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+/// True when a plugin relying on `company_domain` is enabled but no company
+/// domain was ever configured.
+fn uses_placeholder_company_domain(order: &[String], cfg: &PluginConfig) -> bool {
+    cfg.company_domain == DEFAULT_COMPANY_DOMAIN
+        && cfg.company_domains.is_empty()
+        && order.iter().any(|p| p == "pii" || p == "email_bcc")
+}
+
+/// Config keys whose string values may reference `${VAR}`. Other strings are
+/// left alone: external_http `requestTemplate` uses the same syntax for its
+/// own placeholders (`${userMessage}`, `${inputJson}`), and policy regexes may
+/// contain `${` literally.
+const INTERPOLATED_KEYS: [&str; 3] = ["url", "bearerToken", "bearer_token"];
+
+/// Resolve `${VAR}` references in the `INTERPOLATED_KEYS` fields of the
+/// parsed configuration using the process environment. Interpolation happens
+/// on the JSON tree (not the raw text) so substituted values never need
+/// escaping. A reference to an unset variable is an error.
+fn interpolate_env(value: &mut serde_json::Value) -> Result<()> {
+    match value {
+        serde_json::Value::Array(items) => {
+            for item in items {
+                interpolate_env(item)?;
+            }
+        }
+        serde_json::Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                match v {
+                    serde_json::Value::String(s)
+                        if INTERPOLATED_KEYS.contains(&k.as_str()) && s.contains("${") =>
+                    {
+                        *s = interpolate_str(s)?;
+                    }
+                    _ => interpolate_env(v)?,
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+fn interpolate_str(input: &str) -> Result<String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| anyhow!("unterminated ${{...}} reference in '{}'", input))?;
+        let name = &after[..end];
+        if name.is_empty() {
+            return Err(anyhow!("empty ${{}} reference in '{}'", input));
+        }
+        let resolved = env::var(name).map_err(|_| {
+            anyhow!(
+                "environment variable '{}' referenced in config is not set",
+                name
+            )
+        })?;
+        out.push_str(&resolved);
+        rest = &after[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
+
 fn parse_optional_u64(var: &str) -> Result<Option<u64>> {
     match env::var(var) {
         Ok(value) if !value.trim().is_empty() => value
@@ -139,6 +667,7 @@ mod tests {
     fn parses_environment_defaults() {
         let _guard = ENV_MUTEX.lock().unwrap();
         std::env::remove_var("SENTRA_PLUGIN_CONFIG");
+        std::env::remove_var("SENTRA_POLICIES_FILE");
         std::env::remove_var("SENTRA_PLUGINS");
         std::env::remove_var("STRICT_AUTH_ALLOWED_TOKENS");
         std::env::remove_var("LOG_FILE");
@@ -148,13 +677,35 @@ fn parses_environment_defaults() {
         std::env::remove_var("LOG_ROTATE_COMPRESS");
         std::env::remove_var("SENTRA_LOG_STDOUT");
         std::env::remove_var("SENTRA_MAX_REQUEST_BYTES");
+        std::env::remove_var("SENTRA_GZIP_MIN_BYTES");
+        std::env::remove_var("SENTRA_MAX_RESPONSE_BYTES");
         std::env::remove_var("SENTRA_PLUGIN_BUDGET_MS");
         std::env::remove_var("SENTRA_PLUGIN_WARN_MS");
         std::env::remove_var("SENTRA_AUDIT_ONLY");
+        std::env::remove_var("SENTRA_AUDIT_FULL_REQUEST");
         std::env::remove_var("SENTRA_LOG_SAMPLE_N");
+        std::env::remove_var("SENTRA_REQUIRE_COMPANY_DOMAIN");
+        std::env::remove_var("SENTRA_SECRETS_AWS_STRICT");
+        std::env::remove_var("SENTRA_TELEMETRY_HMAC_KEY");
+        std::env::remove_var("SENTRA_TELEMETRY_QUEUE");
+        std::env::remove_var("SENTRA_METRICS_TOKEN");
+        std::env::remove_var("SENTRA_REQUIRE_METADATA");
+        std::env::remove_var("SENTRA_STRICT_SCHEMA");
+        std::env::remove_var("SENTRA_MAX_USER_MESSAGE_CHARS");
+        std::env::remove_var("SENTRA_USER_MESSAGE_OVERFLOW");
+        std::env::remove_var("SENTRA_VERBOSE_ALLOW");
+        std::env::remove_var("SENTRA_DEBUG_CAPTURE");
+        std::env::remove_var("SENTRA_EVALUATE_ALL");
+        std::env::remove_var("SENTRA_TELEMETRY_PLUGIN_DIAGNOSTICS");
+        std::env::remove_var("SENTRA_STRICT_EXTERNAL_BUDGET");
+        std::env::remove_var("SENTRA_DIAGNOSTICS_MODE");
+        std::env::remove_var("SENTRA_DIAGNOSTICS_LEVEL");
+        std::env::remove_var("SENTRA_COMPANY_DOMAIN");
+        std::env::remove_var("SENTRA_COMPANY_DOMAINS");
 
         let cfg = AppConfig::from_env().unwrap();
         assert!(cfg.log_file.is_none());
+        assert!(!cfg.audit_full_request);
         assert_eq!(cfg.rotation.keep, 1);
         assert!(!cfg.log_stdout);
         assert_eq!(cfg.plugin_budget_ms, 900);
@@ -223,4 +774,231 @@ fn parses_full_configuration() {
         std::env::remove_var("SENTRA_AUDIT_ONLY");
         std::env::remove_var("SENTRA_LOG_SAMPLE_N");
     }
+
+    /// `AppConfig::from_env` plus everything it logged.
+    fn from_env_logged() -> (Result<AppConfig>, String) {
+        #[derive(Clone, Default)]
+        struct Capture(std::sync::Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for Capture {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let capture = Capture::default();
+        let writer = capture.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .finish();
+        let cfg = tracing::subscriber::with_default(subscriber, AppConfig::from_env);
+        let logs = String::from_utf8(capture.0.lock().unwrap().clone()).unwrap();
+        (cfg, logs)
+    }
+
+    #[test]
+    fn placeholder_company_domain_warns_or_fails_when_required() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+        std::env::remove_var("SENTRA_PLUGIN_CONFIG");
+        std::env::set_var("SENTRA_PLUGINS", "secrets,email_bcc");
+
+        let (cfg, logs) = from_env_logged();
+        let cfg = cfg.unwrap();
+        assert!(uses_placeholder_company_domain(
+            &cfg.plugin_order,
+            &cfg.plugin_config
+        ));
+        assert!(logs.contains("WARN"), "{}", logs);
+        assert!(
+            logs.contains("company_domain is the placeholder default"),
+            "{}",
+            logs
+        );
+
+        std::env::set_var("SENTRA_COMPANY_DOMAIN", "corp.example");
+        let (cfg, logs) = from_env_logged();
+        assert!(cfg.is_ok());
+        assert!(!logs.contains("placeholder"), "{}", logs);
+        std::env::remove_var("SENTRA_COMPANY_DOMAIN");
+
+        std::env::set_var("SENTRA_REQUIRE_COMPANY_DOMAIN", "true");
+        let err = AppConfig::from_env().unwrap_err();
+        assert!(err.to_string().contains("company_domain"));
+
+        // Irrelevant when no domain-sensitive plugin is enabled.
+        std::env::set_var("SENTRA_PLUGINS", "secrets,exfil");
+        assert!(AppConfig::from_env().is_ok());
+
+        std::env::remove_var("SENTRA_REQUIRE_COMPANY_DOMAIN");
+        std::env::remove_var("SENTRA_PLUGINS");
+    }
+
+    #[test]
+    fn external_timeouts_over_budget_warn_or_fail_when_strict() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+        let mut temp = NamedTempFile::new().unwrap();
+        let config = serde_json::json!({
+            "externalHttp": [
+                {"name": "external_a", "url": "http://a", "timeoutMs": 400},
+                {"name": "external_b", "url": "http://b", "timeoutMs": 400},
+                {"name": "external_c", "url": "http://c", "timeoutMs": 400}
+            ]
+        });
+        use std::io::Write;
+        write!(temp, "{}", config).unwrap();
+        std::env::set_var("SENTRA_PLUGIN_CONFIG", temp.path());
+        std::env::set_var("SENTRA_PLUGINS", "external_a,external_b,external_c");
+        std::env::set_var("SENTRA_PLUGIN_BUDGET_MS", "900");
+
+        let cfg = AppConfig::from_env().unwrap();
+        assert_eq!(
+            total_timeout_ms(&cfg.plugin_order, &cfg.plugin_config.external_http),
+            1200
+        );
+
+        std::env::set_var("SENTRA_STRICT_EXTERNAL_BUDGET", "true");
+        let err = AppConfig::from_env().unwrap_err().to_string();
+        assert!(err.contains("1200ms"), "{}", err);
+
+        // Within budget the strict flag is irrelevant.
+        std::env::set_var("SENTRA_PLUGIN_BUDGET_MS", "1500");
+        assert!(AppConfig::from_env().is_ok());
+
+        std::env::remove_var("SENTRA_STRICT_EXTERNAL_BUDGET");
+        std::env::remove_var("SENTRA_PLUGIN_BUDGET_MS");
+        std::env::remove_var("SENTRA_PLUGINS");
+        std::env::remove_var("SENTRA_PLUGIN_CONFIG");
+    }
+
+    #[test]
+    fn interpolates_env_references_in_config() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+
+        let mut temp = NamedTempFile::new().unwrap();
+        let config = serde_json::json!({
+            "externalHttp": [{
+                "name": "external_env",
+                "url": "http://${EXT_HOST}/check",
+                "bearerToken": "${EXT_TOKEN}"
+            }]
+        });
+        use std::io::Write;
+        write!(temp, "{}", config).unwrap();
+
+        std::env::set_var("SENTRA_PLUGIN_CONFIG", temp.path());
+        std::env::set_var("EXT_HOST", "policy.internal:9000");
+        std::env::set_var("EXT_TOKEN", "s3cr\"et");
+
+        let cfg = AppConfig::from_env().unwrap();
+        let def = &cfg.plugin_config.external_http[0];
+        assert_eq!(def.url, "http://policy.internal:9000/check");
+        assert_eq!(def.bearer_token.as_deref(), Some("s3cr\"et"));
+
+        std::env::remove_var("EXT_TOKEN");
+        let err = AppConfig::from_env().unwrap_err();
+        assert!(format!("{:#}", err).contains("EXT_TOKEN"));
+
+        std::env::remove_var("EXT_HOST");
+        std::env::remove_var("SENTRA_PLUGIN_CONFIG");
+    }
+
+    #[test]
+    fn request_template_placeholders_are_not_interpolated() {
+        let _guard = ENV_MUTEX.lock().unwrap();
+        let example = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/examples/external_http_example.json"
+        );
+        std::env::set_var("SENTRA_PLUGIN_CONFIG", example);
+
+        let cfg = AppConfig::from_env().unwrap();
+        let template = cfg.plugin_config.external_http[0]
+            .request_template
+            .as_deref()
+            .unwrap();
+        assert!(template.contains("${userMessage}"), "{}", template);
+        assert!(template.contains("${inputJson}"), "{}", template);
+
+        std::env::remove_var("SENTRA_PLUGIN_CONFIG");
+    }
+
+    #[test]
+    fn merge_appends_lists_and_overrides_scalars() {
+        let mut base = serde_json::json!({
+            "company_domain": "base.example",
+            "domain_blocklist": ["evil.com"],
+            "external_max_concurrency": 4
+        });
+        merge_config(
+            &mut base,
+            serde_json::json!({
+                "company_domain": "overlay.example",
+                "domain_blocklist": ["staging-leak.example"],
+                "external_max_concurrency": null
+            }),
+        );
+        assert_eq!(
+            base,
+            serde_json::json!({
+                "company_domain": "overlay.example",
+                "domain_blocklist": ["evil.com", "staging-leak.example"],
+                "external_max_concurrency": null
+            })
+        );
+        assert_eq!(snake_case("domainBlocklist"), "domain_blocklist");
+        assert_eq!(snake_case("URL"), "URL");
+        assert_eq!(snake_case("maxURL"), "maxURL");
+        assert_eq!(snake_case("Authorization"), "Authorization");
+        assert_eq!(camel_case("bearer_token"), "bearerToken");
+    }
+
+    #[test]
+    fn merge_normalizes_nested_keys_and_matches_services_by_name() {
+        let mut merged = serde_json::json!({});
+        for layer in [
+            serde_json::json!({
+                "externalHttp": [
+                    { "name": "scanner", "url": "https://scanner.base", "timeout_ms": 500 },
+                    { "name": "audit", "url": "https://audit.base" }
+                ],
+                "pluginTimeouts": { "piiScan": { "failClosed": true, "ms": 50 } },
+                "trust": { "skipPlugins": ["pii"] }
+            }),
+            serde_json::json!({
+                "external_http": [
+                    { "name": "scanner", "url": "https://scanner.prod", "timeoutMs": 900 },
+                    { "name": "extra", "url": "https://extra.prod" }
+                ],
+                "plugin_timeouts": { "piiScan": { "ms": 80 } },
+                "trust": { "skip_plugins": ["secrets"] }
+            }),
+        ] {
+            let serde_json::Value::Object(mut map) = layer else {
+                unreachable!()
+            };
+            normalize_keys(&mut map);
+            merge_config(&mut merged, serde_json::Value::Object(map));
+        }
+        assert_eq!(
+            merged,
+            serde_json::json!({
+                "external_http": [
+                    { "name": "scanner", "url": "https://scanner.prod", "timeoutMs": 900 },
+                    { "name": "audit", "url": "https://audit.base" },
+                    { "name": "extra", "url": "https://extra.prod" }
+                ],
+                "plugin_timeouts": { "piiScan": { "fail_closed": true, "ms": 80 } },
+                "trust": { "skip_plugins": ["pii", "secrets"] }
+            })
+        );
+        let cfg: PluginConfig = serde_json::from_value(merged).unwrap();
+        assert_eq!(cfg.external_http.len(), 3);
+        assert_eq!(cfg.external_http[0].timeout_ms, 900);
+    }
 }
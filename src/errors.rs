@@ -0,0 +1,206 @@
+//! Error taxonomy for HTTP-level failures.
+//!
+//! Every `ErrorResponse` returned by the handlers is built here so the
+//! domain `errorCode`, HTTP status and message wording stay consistent.
+//! Plugin `reasonCode` values are a separate namespace and are not listed.
+
+use crate::ErrorResponse;
+
+/// Domain error codes surfaced in `ErrorResponse.errorCode`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// Missing, malformed or non-allowlisted bearer token.
+    Unauthorized,
+    /// `api-version` query parameter absent.
+    MissingApiVersion,
+    /// Request body exceeds `SENTRA_MAX_REQUEST_BYTES`.
+    PayloadTooLarge,
+    /// Required payload field missing or empty, or (`SENTRA_STRICT_SCHEMA`)
+    /// a payload that does not match the request schema.
+    MissingRequiredFields,
+    /// `Content-Type` is absent or not JSON.
+    UnsupportedMediaType,
+    /// `conversationMetadata.agent.tenantId` absent while
+    /// `SENTRA_REQUIRE_METADATA` is enabled.
+    MissingConversationMetadata,
+    /// `userMessage` longer than `SENTRA_MAX_USER_MESSAGE_CHARS` while
+    /// `SENTRA_USER_MESSAGE_OVERFLOW=reject`.
+    UserMessageTooLong,
+    /// `Content-Encoding: gzip` body that does not inflate.
+    InvalidContentEncoding,
+}
+
+impl ErrorCode {
+    /// All registered codes, in ascending numeric order.
+    pub const ALL: [ErrorCode; 8] = [
+        ErrorCode::Unauthorized,
+        ErrorCode::MissingApiVersion,
+        ErrorCode::PayloadTooLarge,
+        ErrorCode::MissingRequiredFields,
+        ErrorCode::UnsupportedMediaType,
+        ErrorCode::MissingConversationMetadata,
+        ErrorCode::UserMessageTooLong,
+        ErrorCode::InvalidContentEncoding,
+    ];
+
+    /// Numeric wire value.
+    pub fn code(self) -> i32 {
+        match self {
+            ErrorCode::Unauthorized => 2001,
+            ErrorCode::MissingApiVersion => 4000,
+            ErrorCode::PayloadTooLarge => 4001,
+            ErrorCode::MissingRequiredFields => 4002,
+            ErrorCode::UnsupportedMediaType => 4003,
+            ErrorCode::MissingConversationMetadata => 4004,
+            ErrorCode::UserMessageTooLong => 4005,
+            ErrorCode::InvalidContentEncoding => 4006,
+        }
+    }
+
+    /// HTTP status paired with the code.
+    pub fn http_status(self) -> u16 {
+        match self {
+            ErrorCode::Unauthorized => 401,
+            ErrorCode::MissingApiVersion => 400,
+            ErrorCode::PayloadTooLarge => 413,
+            ErrorCode::MissingRequiredFields => 400,
+            ErrorCode::UnsupportedMediaType => 415,
+            ErrorCode::MissingConversationMetadata => 400,
+            ErrorCode::UserMessageTooLong => 400,
+            ErrorCode::InvalidContentEncoding => 400,
+        }
+    }
+
+    fn response(self, message: String) -> ErrorResponse {
+        ErrorResponse {
+            error_code: self.code(),
+            message,
+            http_status: self.http_status(),
+            diagnostics: None,
+            request_id: None,
+        }
+    }
+}
+
+pub fn unauthorized() -> ErrorResponse {
+    ErrorCode::Unauthorized.response("Unauthorized".into())
+}
+
+pub fn missing_api_version(expected: &str) -> ErrorResponse {
+    ErrorCode::MissingApiVersion.response(format!("Missing api-version (expected {})", expected))
+}
+
+/// Rejection based on the declared `Content-Length` header.
+pub fn content_length_too_large(content_length: usize, limit: usize) -> ErrorResponse {
+    let mut err = ErrorCode::PayloadTooLarge.response(format!(
+        "Request too large ({} bytes > limit {} bytes)",
+        content_length, limit
+    ));
+    err.diagnostics = Some(serde_json::json!({
+        "contentLength": content_length,
+        "limit": limit,
+    }));
+    err
+}
+
+/// Rejection raised while buffering a body (chunked or undeclared length).
+/// The actual size is unknown here because buffering stops at the limit.
+pub fn body_too_large(limit: Option<usize>) -> ErrorResponse {
+    match limit {
+        Some(limit) => {
+            let mut err = ErrorCode::PayloadTooLarge.response(format!(
+                "Request too large (body exceeded limit {} bytes)",
+                limit
+            ));
+            err.diagnostics = Some(serde_json::json!({ "limit": limit }));
+            err
+        }
+        None => ErrorCode::PayloadTooLarge.response("Request too large".to_string()),
+    }
+}
+
+pub fn missing_required_fields(fields: &[&str]) -> ErrorResponse {
+    ErrorCode::MissingRequiredFields.response(format!(
+        "Missing or empty required field(s): {}",
+        fields.join(", ")
+    ))
+}
+
+/// Strict schema rejection; every violation is listed in diagnostics.
+#[cfg(feature = "strict-schema")]
+pub fn schema_violations(violations: &[crate::schema::SchemaViolation]) -> ErrorResponse {
+    let mut err = ErrorCode::MissingRequiredFields.response(format!(
+        "Request does not match schema ({} violation(s))",
+        violations.len()
+    ));
+    err.diagnostics = Some(serde_json::json!({ "violations": violations }));
+    err
+}
+
+pub fn unsupported_media_type(content_type: Option<&str>) -> ErrorResponse {
+    let mut err = ErrorCode::UnsupportedMediaType
+        .response("Unsupported Content-Type (expected application/json)".to_string());
+    err.diagnostics = Some(serde_json::json!({ "contentType": content_type }));
+    err
+}
+
+pub fn missing_conversation_metadata(fields: &[&str]) -> ErrorResponse {
+    ErrorCode::MissingConversationMetadata.response(format!(
+        "Missing required conversation metadata: {}",
+        fields.join(", ")
+    ))
+}
+
+pub fn user_message_too_long(chars: usize, limit: usize) -> ErrorResponse {
+    let mut err = ErrorCode::UserMessageTooLong.response(format!(
+        "User message too long ({} chars > limit {} chars)",
+        chars, limit
+    ));
+    err.diagnostics = Some(serde_json::json!({ "chars": chars, "limit": limit }));
+    err
+}
+
+/// Gzip body that failed to inflate; `detail` is the decoder error.
+#[cfg(feature = "compression")]
+pub fn invalid_gzip_body(detail: &str) -> ErrorResponse {
+    let mut err = ErrorCode::InvalidContentEncoding.response("Invalid gzip body".to_string());
+    err.diagnostics = Some(serde_json::json!({ "detail": detail }));
+    err
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pair(err: &ErrorResponse) -> (i32, u16) {
+        (err.error_code, err.http_status)
+    }
+
+    #[test]
+    fn constructors_map_to_registered_code_and_status() {
+        assert_eq!(pair(&unauthorized()), (2001, 401));
+        assert_eq!(pair(&missing_api_version("2025-05-01")), (4000, 400));
+        assert_eq!(pair(&content_length_too_large(20, 10)), (4001, 413));
+        assert_eq!(pair(&body_too_large(Some(10))), (4001, 413));
+        assert_eq!(pair(&body_too_large(None)), (4001, 413));
+        assert_eq!(pair(&missing_required_fields(&["a", "b"])), (4002, 400));
+        assert_eq!(
+            pair(&unsupported_media_type(Some("text/plain"))),
+            (4003, 415)
+        );
+        assert_eq!(pair(&user_message_too_long(20, 10)), (4005, 400));
+        #[cfg(feature = "compression")]
+        assert_eq!(
+            pair(&invalid_gzip_body("corrupt deflate stream")),
+            (4006, 400)
+        );
+    }
+
+    #[test]
+    fn registry_codes_are_unique() {
+        let mut codes: Vec<i32> = ErrorCode::ALL.iter().map(|c| c.code()).collect();
+        codes.sort_unstable();
+        codes.dedup();
+        assert_eq!(codes.len(), ErrorCode::ALL.len());
+    }
+}
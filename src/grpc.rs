@@ -0,0 +1,286 @@
+//! gRPC transport (`sentra.v1.Sentra`, feature `grpc`).
+//!
+//! Mirrors `POST /validate` and `POST /analyze-tool-execution` over tonic,
+//! sharing the `AppState` and pipeline of the HTTP server. Bearer tokens are
+//! read from the `authorization` metadata entry and checked against
+//! `STRICT_AUTH_ALLOWED_TOKENS`; `x-ms-correlation-id` is honoured as in
+//! HTTP. Errors become a `Status` carrying the `ErrorResponse` message, with
+//! the domain code in the `sentra-error-code` metadata entry.
+//!
+//! The messages below are the Rust side of `proto/sentra.proto`.
+
+// `tonic::Status` is large, but it is the error type the service trait returns.
+#![allow(clippy::result_large_err)]
+
+use tonic::{Request, Response, Status};
+
+use crate::{AppState, ErrorResponse, InflightGuard};
+
+include!(concat!(env!("OUT_DIR"), "/sentra.v1.Sentra.rs"));
+
+pub use sentra_server::{Sentra, SentraServer};
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ValidateRequest {}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ValidateResponse {
+    #[prost(bool, tag = "1")]
+    pub is_successful: bool,
+    #[prost(string, tag = "2")]
+    pub status: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AnalyzeRequest {
+    #[prost(message, optional, tag = "1")]
+    pub planner_context: Option<PlannerContext>,
+    #[prost(message, optional, tag = "2")]
+    pub tool_definition: Option<ToolDefinition>,
+    /// JSON object (`inputValues`); empty means `{}`.
+    #[prost(string, tag = "3")]
+    pub input_values_json: String,
+    #[prost(message, optional, tag = "4")]
+    pub conversation_metadata: Option<ConversationMetadata>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct PlannerContext {
+    #[prost(string, optional, tag = "1")]
+    pub user_message: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub thought: Option<String>,
+    /// JSON array (`chatHistory`); empty means absent.
+    #[prost(string, tag = "3")]
+    pub chat_history_json: String,
+    /// JSON array (`previousToolOutputs`); empty means absent.
+    #[prost(string, tag = "4")]
+    pub previous_tool_outputs_json: String,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ToolDefinition {
+    #[prost(string, optional, tag = "1")]
+    pub id: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub r#type: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub name: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub description: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConversationMetadata {
+    #[prost(message, optional, tag = "1")]
+    pub agent: Option<ConversationAgent>,
+    #[prost(message, optional, tag = "2")]
+    pub user: Option<ConversationUser>,
+    #[prost(message, optional, tag = "3")]
+    pub trigger: Option<ConversationTrigger>,
+    #[prost(string, optional, tag = "4")]
+    pub conversation_id: Option<String>,
+    #[prost(string, optional, tag = "5")]
+    pub plan_id: Option<String>,
+    #[prost(string, optional, tag = "6")]
+    pub plan_step_id: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConversationAgent {
+    #[prost(string, optional, tag = "1")]
+    pub id: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub tenant_id: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub environment_id: Option<String>,
+    #[prost(bool, optional, tag = "4")]
+    pub is_published: Option<bool>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConversationUser {
+    #[prost(string, optional, tag = "1")]
+    pub id: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct ConversationTrigger {
+    #[prost(string, optional, tag = "1")]
+    pub id: Option<String>,
+    #[prost(string, optional, tag = "2")]
+    pub schema_name: Option<String>,
+}
+
+#[derive(Clone, PartialEq, prost::Message)]
+pub struct AnalyzeResponse {
+    #[prost(bool, tag = "1")]
+    pub block_action: bool,
+    #[prost(int32, optional, tag = "2")]
+    pub reason_code: Option<i32>,
+    #[prost(string, optional, tag = "3")]
+    pub reason: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub blocked_by: Option<String>,
+    /// JSON object (`diagnostics`); empty when absent.
+    #[prost(string, tag = "5")]
+    pub diagnostics_json: String,
+}
+
+/// `Sentra` service over a shared `AppState`.
+pub struct SentraService {
+    state: AppState,
+}
+
+/// Ready-to-mount tonic service. `SENTRA_MAX_REQUEST_BYTES` also caps the
+/// decoded message size.
+pub fn service(state: AppState) -> SentraServer<SentraService> {
+    let limit = state.max_request_bytes;
+    let server = SentraServer::new(SentraService { state });
+    match limit {
+        Some(limit) => server.max_decoding_message_size(limit),
+        None => server,
+    }
+}
+
+#[tonic::async_trait]
+impl Sentra for SentraService {
+    async fn validate(
+        &self,
+        request: Request<ValidateRequest>,
+    ) -> Result<Response<ValidateResponse>, Status> {
+        authorize(&request, &self.state)?;
+        Ok(Response::new(ValidateResponse {
+            is_successful: true,
+            status: "OK".into(),
+        }))
+    }
+
+    async fn analyze_tool_execution(
+        &self,
+        request: Request<AnalyzeRequest>,
+    ) -> Result<Response<AnalyzeResponse>, Status> {
+        let _inflight = InflightGuard::enter(&self.state.metric_inflight_requests);
+        let token = authorize(&request, &self.state)?;
+        let corr = request
+            .metadata()
+            .get("x-ms-correlation-id")
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("")
+            .to_string();
+        let payload = crate::AnalyzeRequest::try_from(request.into_inner())?;
+        let request_id = crate::util::request_id();
+        let response = crate::decide(&self.state, &payload, Some(&token), &corr, &request_id)
+            .await
+            .map_err(|err| status(err.with_request_id(&request_id)))?;
+        let mut response = Response::new(response.into());
+        if let Ok(value) = request_id.parse() {
+            response.metadata_mut().insert("x-sentra-request-id", value);
+        }
+        Ok(response)
+    }
+}
+
+/// Bearer token from the `authorization` metadata, checked like the HTTP
+/// `Authorization` header.
+fn authorize<T>(request: &Request<T>, state: &AppState) -> Result<String, Status> {
+    let headers = request.metadata().clone().into_headers();
+    crate::ensure_authorized(&headers, state.allowed_tokens.as_ref()).map_err(status)?;
+    crate::extract_bearer_token(&headers).map_err(status)
+}
+
+fn status(err: ErrorResponse) -> Status {
+    let code = match err.http_status {
+        400 => tonic::Code::InvalidArgument,
+        401 => tonic::Code::Unauthenticated,
+        413 => tonic::Code::ResourceExhausted,
+        415 => tonic::Code::InvalidArgument,
+        _ => tonic::Code::Internal,
+    };
+    let mut status = Status::new(code, err.message);
+    status
+        .metadata_mut()
+        .insert("sentra-error-code", err.error_code.into());
+    if let Some(value) = err.request_id.and_then(|id| id.parse().ok()) {
+        status.metadata_mut().insert("x-sentra-request-id", value);
+    }
+    status
+}
+
+/// Parse a JSON-encoded field; an empty string means the field is absent.
+fn json_field<T: serde::de::DeserializeOwned>(raw: &str, field: &str) -> Result<Option<T>, Status> {
+    if raw.is_empty() {
+        return Ok(None);
+    }
+    serde_json::from_str(raw)
+        .map(Some)
+        .map_err(|e| Status::invalid_argument(format!("{field}: invalid JSON: {e}")))
+}
+
+impl TryFrom<AnalyzeRequest> for crate::AnalyzeRequest {
+    type Error = Status;
+
+    fn try_from(req: AnalyzeRequest) -> Result<Self, Status> {
+        let planner = req.planner_context.unwrap_or_default();
+        let tool = req.tool_definition.unwrap_or_default();
+        Ok(crate::AnalyzeRequest {
+            planner_context: crate::PlannerContext {
+                user_message: planner.user_message,
+                thought: planner.thought,
+                chat_history: json_field(&planner.chat_history_json, "chat_history_json")?,
+                previous_tool_outputs: json_field(
+                    &planner.previous_tool_outputs_json,
+                    "previous_tool_outputs_json",
+                )?,
+            },
+            tool_definition: crate::ToolDefinition {
+                id: tool.id,
+                def_type: tool.r#type,
+                name: tool.name,
+                description: tool.description,
+                ..Default::default()
+            },
+            input_values: json_field(&req.input_values_json, "input_values_json")?
+                .unwrap_or_default(),
+            conversation_metadata: req.conversation_metadata.map(Into::into),
+        })
+    }
+}
+
+impl From<ConversationMetadata> for crate::ConversationMetadata {
+    fn from(meta: ConversationMetadata) -> Self {
+        crate::ConversationMetadata {
+            agent: meta.agent.map(|a| crate::ConversationAgent {
+                id: a.id,
+                tenant_id: a.tenant_id,
+                environment_id: a.environment_id,
+                is_published: a.is_published,
+            }),
+            user: meta.user.map(|u| crate::ConversationUser {
+                id: u.id,
+                tenant_id: u.tenant_id,
+            }),
+            trigger: meta.trigger.map(|t| crate::ConversationTrigger {
+                id: t.id,
+                schema_name: t.schema_name,
+            }),
+            conversation_id: meta.conversation_id,
+            plan_id: meta.plan_id,
+            plan_step_id: meta.plan_step_id,
+        }
+    }
+}
+
+impl From<crate::AnalyzeResponse> for AnalyzeResponse {
+    fn from(resp: crate::AnalyzeResponse) -> Self {
+        AnalyzeResponse {
+            block_action: resp.block_action,
+            reason_code: resp.reason_code,
+            reason: resp.reason,
+            blocked_by: resp.blocked_by,
+            diagnostics_json: resp.diagnostics.map(|d| d.to_string()).unwrap_or_default(),
+        }
+    }
+}
@@ -3,34 +3,73 @@
 //! deliberately avoids any dependencies beyond those required by the
 //! application to remain lightweight and easy to embed.
 
+#[cfg(all(
+    feature = "wasm",
+    any(
+        feature = "external-http",
+        feature = "compression",
+        feature = "metrics",
+        feature = "tls",
+        feature = "strict-schema",
+        feature = "grpc"
+    )
+))]
+compile_error!("the `wasm` feature leaves out the server; build it with `--no-default-features`");
+
+#[cfg(not(feature = "wasm"))]
 mod config;
+pub mod errors;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod local;
 pub mod plugins;
+#[cfg(feature = "strict-schema")]
+pub mod schema;
+#[cfg(not(feature = "wasm"))]
+pub mod server;
+#[cfg(not(feature = "wasm"))]
+pub mod signing;
+#[cfg(not(feature = "wasm"))]
+pub mod telemetry;
+#[cfg(feature = "tls")]
+pub mod tls;
 pub mod util;
 
-pub use config::AppConfig;
+#[cfg(not(feature = "wasm"))]
+pub use config::{AppConfig, RotationConfig, ServerOptions, UserMessageOverflow};
 
+#[cfg(not(feature = "wasm"))]
 use axum::extract::{
     rejection::{BytesRejection, FailedToBufferBody, JsonRejection},
-    DefaultBodyLimit, State,
+    DefaultBodyLimit, Extension, State,
 };
-use axum::http::{HeaderMap, StatusCode};
+#[cfg(not(feature = "wasm"))]
+use axum::http::{HeaderMap, HeaderValue, StatusCode};
+#[cfg(not(feature = "wasm"))]
 use axum::response::IntoResponse;
+#[cfg(not(feature = "wasm"))]
 use axum::{routing::post, Json, Router};
 // WebSocket and broadcast telemetry removed for production simplification.
-use flate2::write::GzEncoder;
-use flate2::Compression;
 use serde::{Deserialize, Serialize};
+#[cfg(not(feature = "wasm"))]
 use std::collections::HashSet;
+#[cfg(not(feature = "wasm"))]
 use std::fs;
+#[cfg(not(feature = "wasm"))]
 use std::io::Write;
+#[cfg(not(feature = "wasm"))]
 use std::path::PathBuf;
+#[cfg(not(feature = "wasm"))]
 use std::sync::{
     atomic::{AtomicU64, Ordering},
     Arc, Mutex,
 };
+#[cfg(not(feature = "wasm"))]
 use std::time::Instant;
 
-use crate::plugins::{PluginConfig, PluginPipeline};
+#[cfg(not(feature = "wasm"))]
+use crate::plugins::{Plugin, PluginConfig, PluginOutcome, PluginPipeline};
+#[cfg(not(feature = "wasm"))]
 use crate::util::EvalContext;
 
 pub use crate::plugins::policy_pack::PolicyRule;
@@ -88,6 +127,17 @@ pub struct ToolDefinition {
     pub output_parameters: Vec<ToolParam>,
 }
 
+impl ToolDefinition {
+    /// The tool's description followed by its input and output parameter
+    /// descriptions, skipping any that are absent.
+    pub fn descriptions(&self) -> impl Iterator<Item = &str> {
+        let params = self.input_parameters.iter().chain(&self.output_parameters);
+        std::iter::once(self.description.as_deref())
+            .chain(params.map(|p| p.description.as_deref()))
+            .flatten()
+    }
+}
+
 #[derive(Debug, Deserialize, Serialize, Default, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct ConversationAgent {
@@ -133,8 +183,13 @@ pub struct AnalyzeRequest {
 }
 
 impl AnalyzeRequest {
+    pub fn builder() -> AnalyzeRequestBuilder {
+        AnalyzeRequestBuilder::default()
+    }
+
     /// Validate required fields according to the Microsoft External Security Webhooks spec.
     /// Returns a vector of missing field descriptions (empty if valid).
+    #[cfg(not(feature = "wasm"))]
     fn missing_required_fields(&self) -> Vec<&'static str> {
         let mut missing = Vec::new();
         // plannerContext.userMessage required & must be non-empty
@@ -161,6 +216,75 @@ fn missing_required_fields(&self) -> Vec<&'static str> {
         }
         missing
     }
+
+    /// Attribution fields required when `SENTRA_REQUIRE_METADATA` is on.
+    #[cfg(not(feature = "wasm"))]
+    fn missing_metadata_fields(&self) -> Vec<&'static str> {
+        let Some(meta) = &self.conversation_metadata else {
+            return vec!["conversationMetadata"];
+        };
+        let tenant = meta
+            .agent
+            .as_ref()
+            .and_then(|a| a.tenant_id.as_deref())
+            .map(str::trim)
+            .filter(|t| !t.is_empty());
+        match tenant {
+            Some(_) => Vec::new(),
+            None => vec!["conversationMetadata.agent.tenantId"],
+        }
+    }
+}
+
+/// Typed construction of an `AnalyzeRequest`, for embedders and clients:
+/// `AnalyzeRequest::builder().user_message("hi").tool("SendEmail").input("to", "a@b.c").build()`.
+#[derive(Debug, Default, Clone)]
+pub struct AnalyzeRequestBuilder {
+    req: AnalyzeRequest,
+}
+
+impl AnalyzeRequestBuilder {
+    pub fn user_message(mut self, message: impl Into<String>) -> Self {
+        self.req.planner_context.user_message = Some(message.into());
+        self
+    }
+
+    /// Append a `{role, content}` entry to `plannerContext.chatHistory`.
+    pub fn chat_message(mut self, role: impl Into<String>, content: impl Into<String>) -> Self {
+        self.req
+            .planner_context
+            .chat_history
+            .get_or_insert_with(Vec::new)
+            .push(serde_json::json!({ "role": role.into(), "content": content.into() }));
+        self
+    }
+
+    /// Name of the tool being invoked (`toolDefinition.name`).
+    pub fn tool(mut self, name: impl Into<String>) -> Self {
+        self.req.tool_definition.name = Some(name.into());
+        self
+    }
+
+    /// Set one `inputValues` entry.
+    pub fn input(mut self, key: impl Into<String>, value: impl Into<serde_json::Value>) -> Self {
+        self.req.input_values.insert(key.into(), value.into());
+        self
+    }
+
+    /// Set `conversationMetadata.agent.tenantId`.
+    pub fn tenant_id(mut self, tenant_id: impl Into<String>) -> Self {
+        self.req
+            .conversation_metadata
+            .get_or_insert_with(Default::default)
+            .agent
+            .get_or_insert_with(Default::default)
+            .tenant_id = Some(tenant_id.into());
+        self
+    }
+
+    pub fn build(self) -> AnalyzeRequest {
+        self.req
+    }
 }
 
 #[derive(Debug, Serialize, Clone)]
@@ -187,63 +311,190 @@ pub struct ErrorResponse {
     pub http_status: u16,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub diagnostics: Option<serde_json::Value>,
+    /// Server-generated id of the failed request, as in `x-sentra-request-id`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<String>,
+}
+
+impl ErrorResponse {
+    pub fn with_request_id(mut self, request_id: &str) -> Self {
+        self.request_id = Some(request_id.to_string());
+        self
+    }
 }
 
 /// Internal application state shared across handlers.  Contains the
 /// preconstructed plugin pipeline, evaluation flags and parsed configuration.
+#[cfg(not(feature = "wasm"))]
 #[derive(Clone)]
 pub struct AppState {
     pub pipeline: PluginPipeline,
     pub plugin_config: PluginConfig,
     pub log_file: Option<String>,
     pub allowed_tokens: Option<HashSet<String>>, // strict auth allowlist
+    pub metrics_token: Option<String>,           // bearer token required by /metrics
     /// Maximum accepted raw request body size in bytes (None => unlimited)
     pub max_request_bytes: Option<usize>,
+    /// Smallest response body gzipped for clients sending `Accept-Encoding: gzip`
+    pub gzip_min_bytes: usize,
+    /// Largest serialized decision; see `cap_response`.
+    pub max_response_bytes: usize,
     /// Total plugin evaluation budget in milliseconds (default 900ms)
     pub plugin_budget_ms: u64,
     /// Per-plugin warning threshold in ms (log if exceeded)
     pub plugin_warn_ms: u64,
     /// Audit only mode (never block, still evaluate and log would-be blocks)
     pub audit_only: bool,
+    /// Include the full request in audit records (default: redacted preview only)
+    pub audit_full_request: bool,
+    /// Reject analyze requests without tenant attribution metadata
+    pub require_metadata: bool,
+    /// Longest `userMessage` scanned, in characters, and what to do past it
+    pub max_user_message_chars: Option<usize>,
+    pub user_message_overflow: UserMessageOverflow,
+    /// Validate analyze bodies against the request schema before evaluation
+    pub strict_schema: bool,
+    /// Attach allow diagnostics (plugins evaluated/skipped) to benign responses
+    pub verbose_allow: bool,
+    /// Debug-only: log each request and decision, redacted, at debug level
+    pub debug_capture: bool,
+    /// Keep evaluating after the first block to collect every plugin signal
+    pub evaluate_all: bool,
+    /// Add `pluginDiagnostics` to telemetry when several plugins signalled (needs `evaluate_all`)
+    pub telemetry_plugin_diagnostics: bool,
     /// Separate audit log file (optional). If unset falls back to LOG_FILE.
     pub audit_log_file: Option<String>,
     pub telemetry: TelemetrySink,
+    /// Fingerprint of plugin order + config, stamped on telemetry events
+    pub config_hash: Arc<str>,
     // Metrics counters
     pub metric_requests_total: Arc<AtomicU64>,
     pub metric_blocks_total: Arc<AtomicU64>,
     pub metric_audit_suppressed_total: Arc<AtomicU64>,
+    // Gauge: analyze requests currently being handled
+    pub metric_inflight_requests: Arc<AtomicU64>,
+    // Gauge: exponentially weighted block rate over recent decisions
+    pub metric_block_rate_ewma: Arc<BlockRateEwma>,
+    // Gauge: policy rules loaded by policy_pack after the maxPolicyRules cap
+    pub policy_rules_loaded: usize,
     // Histogram buckets (fixed) for request latency in ms (upper bounds) and counts
-    pub hist_buckets: Arc<Vec<u64>>,      // bucket upper bounds
+    pub hist_buckets: Arc<Vec<u64>>, // ascending bucket upper bounds (util::histogram_buckets)
     pub hist_counts: Arc<Vec<AtomicU64>>, // same length as hist_buckets
-    pub hist_sum_ms: Arc<AtomicU64>,      // sum of observed latencies (ms)
-    pub hist_count: Arc<AtomicU64>,       // total observations
+    pub hist_sum_ms: Arc<AtomicU64>, // sum of observed latencies (ms)
+    pub hist_count: Arc<AtomicU64>,  // total observations
+    // The same request latency split by returned decision
+    pub allow_hist: Arc<DecisionHistogram>,
+    pub block_hist: Arc<DecisionHistogram>,
     // Per-plugin metrics (sum ms, count, block count)
     pub plugin_metric_indices: Arc<std::collections::HashMap<String, usize>>,
-    pub plugin_metrics: Arc<Vec<PluginMetrics>>, // index aligned with plugin order
+    pub plugin_metric_names: Arc<Vec<String>>, // keys of plugin_metric_indices in declared order
+    pub plugin_metrics: Arc<Vec<PluginMetrics>>, // index aligned with plugin_metric_names
     // Process start time (epoch secs) and instant for uptime computation
     pub process_start_epoch: f64,
     pub process_start_instant: Instant,
 }
 
+/// Request latency for one decision (`allow` or `block`), counts aligned
+/// with `AppState.hist_buckets`.
+#[cfg(not(feature = "wasm"))]
+pub struct DecisionHistogram {
+    pub counts: Vec<AtomicU64>,
+    pub sum_ms: AtomicU64,
+    pub count: AtomicU64,
+}
+
+#[cfg(not(feature = "wasm"))]
+impl DecisionHistogram {
+    fn new(buckets: &[u64]) -> Self {
+        Self {
+            counts: buckets.iter().map(|_| AtomicU64::new(0)).collect(),
+            sum_ms: AtomicU64::new(0),
+            count: AtomicU64::new(0),
+        }
+    }
+
+    fn observe(&self, buckets: &[u64], ms: u64) {
+        self.sum_ms.fetch_add(ms, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        if let Some(idx) = util::bucket_index(buckets, ms) {
+            self.counts[idx].fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+#[cfg(not(feature = "wasm"))]
 pub struct PluginMetrics {
     pub eval_sum_ms: AtomicU64,
     pub eval_count: AtomicU64,
     pub block_count: AtomicU64,
+    /// Explicit non-blocking responses (`Some` with `blockAction=false`)
+    pub allow_count: AtomicU64,
+    /// Evaluations that panicked (caught by the pipeline).
+    pub panic_count: AtomicU64,
+    /// Evaluations cut off by the plugin's `pluginTimeouts` limit.
+    pub timeout_count: AtomicU64,
+    /// Would-block responses while the plugin was in its monitor window.
+    pub monitored_count: AtomicU64,
     // Per-plugin latency histogram: counts aligned with AppState.hist_buckets
     pub hist_counts: Vec<AtomicU64>,
     pub hist_sum_ms: AtomicU64,
     pub hist_count: AtomicU64,
+    // Blocks per reason code, capped at MAX_REASON_SERIES_PER_PLUGIN series;
+    // further codes are counted under reason_code="other".
+    pub block_reasons: dashmap::DashMap<i32, AtomicU64>,
+    pub block_reasons_other: AtomicU64,
+}
+
+/// Cardinality cap for `sentra_plugin_block_reason_total` per plugin.
+/// Reason codes are user-configurable for policy_pack and external plugins.
+#[cfg(not(feature = "wasm"))]
+const MAX_REASON_SERIES_PER_PLUGIN: usize = 32;
+
+#[cfg(not(feature = "wasm"))]
+impl PluginMetrics {
+    fn new(buckets: &[u64]) -> Self {
+        PluginMetrics {
+            eval_sum_ms: AtomicU64::new(0),
+            eval_count: AtomicU64::new(0),
+            block_count: AtomicU64::new(0),
+            allow_count: AtomicU64::new(0),
+            panic_count: AtomicU64::new(0),
+            timeout_count: AtomicU64::new(0),
+            monitored_count: AtomicU64::new(0),
+            hist_counts: buckets.iter().map(|_| AtomicU64::new(0)).collect(),
+            hist_sum_ms: AtomicU64::new(0),
+            hist_count: AtomicU64::new(0),
+            block_reasons: dashmap::DashMap::new(),
+            block_reasons_other: AtomicU64::new(0),
+        }
+    }
+
+    fn record_block_reason(&self, code: i32) {
+        if let Some(counter) = self.block_reasons.get(&code) {
+            counter.fetch_add(1, Ordering::Relaxed);
+        } else if self.block_reasons.len() < MAX_REASON_SERIES_PER_PLUGIN {
+            self.block_reasons
+                .entry(code)
+                .or_insert_with(|| AtomicU64::new(0))
+                .fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.block_reasons_other.fetch_add(1, Ordering::Relaxed);
+        }
+    }
 }
 
 /// Simple size-based rotating writer (single backup file <path>.1 kept).
+#[cfg(not(feature = "wasm"))]
 pub struct RotatingWriter {
     path: PathBuf,
     file: std::fs::File,
     max_bytes: Option<u64>,
     keep: usize,
+    #[cfg_attr(not(feature = "compression"), allow(dead_code))]
     compress: bool,
 }
 
+#[cfg(not(feature = "wasm"))]
 impl RotatingWriter {
     pub fn open(
         path: &str,
@@ -304,7 +555,11 @@ fn rotate_backups(&self) {
         }
     }
 
+    #[cfg(feature = "compression")]
     fn compress_latest_backup(&self) {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+
         if !self.compress || self.keep == 0 {
             return;
         }
@@ -321,6 +576,10 @@ fn compress_latest_backup(&self) {
         }
     }
 
+    /// Built without the `compression` feature: backups stay uncompressed.
+    #[cfg(not(feature = "compression"))]
+    fn compress_latest_backup(&self) {}
+
     fn reopen_current(&mut self) {
         if let Ok(newf) = fs::OpenOptions::new()
             .create(true)
@@ -333,6 +592,7 @@ fn reopen_current(&mut self) {
     }
 }
 
+#[cfg(not(feature = "wasm"))]
 #[derive(Clone)]
 pub struct TelemetrySink {
     telemetry_writer: Option<Arc<Mutex<RotatingWriter>>>,
@@ -340,11 +600,37 @@ pub struct TelemetrySink {
     log_stdout: bool,
     log_sample_n: Option<u64>,
     log_sample_counter: Arc<AtomicU64>,
-    metric_lines_total: Arc<AtomicU64>,
-    metric_write_errors_total: Arc<AtomicU64>,
+    counters: WriteCounters,
+    /// Hands lines to the background writer when `with_write_queue` is used;
+    /// otherwise lines are written on the request path.
+    queue: Option<std::sync::mpsc::SyncSender<QueuedLine>>,
+    /// Signs each written line when set (see `signing`).
+    hmac_key: Option<ring::hmac::Key>,
+}
+
+/// Upper bounds (ms) of the `sentra_telemetry_write_ms` histogram.
+#[cfg(not(feature = "wasm"))]
+pub const TELEMETRY_WRITE_BUCKETS: &[u64] = &[1, 5, 10, 50, 100, 500, 1000, 5000];
+
+/// Write metrics, shared with the background writer thread.
+#[cfg(not(feature = "wasm"))]
+#[derive(Clone)]
+struct WriteCounters {
+    lines_total: Arc<AtomicU64>,
+    write_errors_total: Arc<AtomicU64>,
     log_file_size_bytes: Arc<AtomicU64>,
+    dropped_total: Arc<AtomicU64>,
+    write_ms: Arc<DecisionHistogram>,
+}
+
+#[cfg(not(feature = "wasm"))]
+struct QueuedLine {
+    line: String,
+    writer: Arc<Mutex<RotatingWriter>>,
+    kind: TelemetryKind,
 }
 
+#[cfg(not(feature = "wasm"))]
 pub struct TelemetryLogFields<'a> {
     pub block_action: bool,
     pub reason_code: Option<i32>,
@@ -354,6 +640,7 @@ pub struct TelemetryLogFields<'a> {
     pub plugin_count: usize,
 }
 
+#[cfg(not(feature = "wasm"))]
 pub struct AuditLogFields<'a> {
     pub would_block: bool,
     pub reason_code: Option<i32>,
@@ -361,12 +648,14 @@ pub struct AuditLogFields<'a> {
     pub plugin_count: usize,
 }
 
+#[cfg(not(feature = "wasm"))]
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum TelemetryKind {
     Event,
     Audit,
 }
 
+#[cfg(not(feature = "wasm"))]
 impl TelemetrySink {
     pub fn new(
         telemetry_writer: Option<Arc<Mutex<RotatingWriter>>>,
@@ -383,12 +672,49 @@ pub fn new(
             log_stdout,
             log_sample_n,
             log_sample_counter: Arc::new(AtomicU64::new(0)),
-            metric_lines_total,
-            metric_write_errors_total,
-            log_file_size_bytes,
+            counters: WriteCounters {
+                lines_total: metric_lines_total,
+                write_errors_total: metric_write_errors_total,
+                log_file_size_bytes,
+                dropped_total: Arc::new(AtomicU64::new(0)),
+                write_ms: Arc::new(DecisionHistogram::new(TELEMETRY_WRITE_BUCKETS)),
+            },
+            queue: None,
+            hmac_key: None,
         }
     }
 
+    /// Append an HMAC-SHA256 `mac` member to every telemetry and audit line.
+    pub fn with_hmac_key(mut self, secret: &[u8]) -> Self {
+        self.hmac_key = Some(signing::signing_key(secret));
+        self
+    }
+
+    /// Write lines on a background thread fed by a queue of `capacity`
+    /// lines, so a stalled disk never holds up a request. Telemetry lines
+    /// arriving while the queue is full are dropped and counted in
+    /// `dropped_total`; audit lines are written inline instead. Lines still
+    /// queued when the process exits are lost.
+    pub fn with_write_queue(mut self, capacity: usize) -> Self {
+        let (tx, rx) = std::sync::mpsc::sync_channel::<QueuedLine>(capacity);
+        let counters = self.counters.clone();
+        let spawned = std::thread::Builder::new()
+            .name("sentra-telemetry".into())
+            .spawn(move || {
+                // Ends once every sink clone (and with it the sender) is gone.
+                for queued in rx {
+                    write_now(&counters, &queued.writer, &queued.line, queued.kind);
+                }
+            });
+        match spawned {
+            Ok(_) => self.queue = Some(tx),
+            Err(e) => {
+                tracing::warn!(error=%e, "Failed to start telemetry writer thread; writing synchronously")
+            }
+        }
+        self
+    }
+
     pub fn emit_event(&self, payload: &serde_json::Value, log: &TelemetryLogFields<'_>) {
         let writer = self.telemetry_writer.as_ref();
         let wrote = self.write_line(payload, writer, TelemetryKind::Event);
@@ -429,50 +755,73 @@ pub fn emit_audit(&self, payload: &serde_json::Value, log: &AuditLogFields<'_>)
     }
 
     pub fn lines_total(&self) -> &Arc<AtomicU64> {
-        &self.metric_lines_total
+        &self.counters.lines_total
     }
 
     pub fn write_errors_total(&self) -> &Arc<AtomicU64> {
-        &self.metric_write_errors_total
+        &self.counters.write_errors_total
     }
 
     pub fn log_file_size_bytes(&self) -> &Arc<AtomicU64> {
-        &self.log_file_size_bytes
+        &self.counters.log_file_size_bytes
+    }
+
+    /// Lines dropped because the write queue was full.
+    pub fn dropped_total(&self) -> &Arc<AtomicU64> {
+        &self.counters.dropped_total
     }
 
+    /// Time spent writing each line (lock wait included), bucketed by
+    /// `TELEMETRY_WRITE_BUCKETS`.
+    pub fn write_ms(&self) -> &DecisionHistogram {
+        &self.counters.write_ms
+    }
+
+    /// Returns true once the line is written, or queued for the background
+    /// writer.
     fn write_line(
         &self,
         payload: &serde_json::Value,
         writer: Option<&Arc<Mutex<RotatingWriter>>>,
         kind: TelemetryKind,
     ) -> bool {
-        let line = payload.to_string();
-        if let Some(target) = writer {
-            if let Ok(mut guard) = target.lock() {
-                match guard.write_line_result(&line) {
-                    Ok(_) => {
-                        self.metric_lines_total.fetch_add(1, Ordering::Relaxed);
-                        if let Some(sz) = guard.current_size() {
-                            self.log_file_size_bytes.store(sz, Ordering::Relaxed);
-                        }
-                        return true;
-                    }
-                    Err(e) => {
-                        match kind {
-                            TelemetryKind::Event => {
-                                tracing::warn!(error=%e, "Failed to write telemetry line");
-                            }
-                            TelemetryKind::Audit => {
-                                tracing::warn!(error=%e, "Failed to write audit line");
-                            }
-                        }
-                        self.metric_write_errors_total
-                            .fetch_add(1, Ordering::Relaxed);
-                    }
+        let Some(target) = writer else {
+            return false;
+        };
+        let mut line = payload.to_string();
+        if let Some(key) = &self.hmac_key {
+            line = signing::sign_line(key, &line);
+        }
+        if let Some(queue) = &self.queue {
+            let queued = QueuedLine {
+                line,
+                writer: target.clone(),
+                kind,
+            };
+            match queue.try_send(queued) {
+                Ok(()) => return true,
+                // Audit records are never dropped: write them inline, even if
+                // that waits on the disk.
+                Err(std::sync::mpsc::TrySendError::Full(queued))
+                    if kind == TelemetryKind::Audit =>
+                {
+                    return write_now(&self.counters, target, &queued.line, kind);
+                }
+                Err(std::sync::mpsc::TrySendError::Full(_)) => {
+                    let dropped = self.counters.dropped_total.fetch_add(1, Ordering::Relaxed) + 1;
+                    tracing::warn!(
+                        dropped_total = dropped,
+                        "Telemetry write queue full; line dropped"
+                    );
+                    return false;
+                }
+                // The writer thread is gone: fall back to writing inline.
+                Err(std::sync::mpsc::TrySendError::Disconnected(queued)) => {
+                    return write_now(&self.counters, target, &queued.line, kind);
                 }
             }
         }
-        false
+        write_now(&self.counters, target, &line, kind)
     }
 
     fn should_log_stdout(&self) -> bool {
@@ -481,141 +830,413 @@ fn should_log_stdout(&self) -> bool {
         }
         if let Some(n) = self.log_sample_n {
             let prev = self.log_sample_counter.fetch_add(1, Ordering::Relaxed);
-            prev % n == 0
+            prev.is_multiple_of(n)
         } else {
             true
         }
     }
 }
 
+/// Write one line to `target`, recording the outcome and its latency.
+#[cfg(not(feature = "wasm"))]
+fn write_now(
+    counters: &WriteCounters,
+    target: &Mutex<RotatingWriter>,
+    line: &str,
+    kind: TelemetryKind,
+) -> bool {
+    let start = Instant::now();
+    let Ok(mut guard) = target.lock() else {
+        return false;
+    };
+    let result = guard.write_line_result(line);
+    counters
+        .write_ms
+        .observe(TELEMETRY_WRITE_BUCKETS, start.elapsed().as_millis() as u64);
+    match result {
+        Ok(_) => {
+            counters.lines_total.fetch_add(1, Ordering::Relaxed);
+            if let Some(sz) = guard.current_size() {
+                counters.log_file_size_bytes.store(sz, Ordering::Relaxed);
+            }
+            true
+        }
+        Err(e) => {
+            match kind {
+                TelemetryKind::Event => {
+                    tracing::warn!(error=%e, "Failed to write telemetry line");
+                }
+                TelemetryKind::Audit => {
+                    tracing::warn!(error=%e, "Failed to write audit line");
+                }
+            }
+            counters.write_errors_total.fetch_add(1, Ordering::Relaxed);
+            false
+        }
+    }
+}
+
 /// Build state from environment variables.  This function reads the
 /// following variables:
 ///
 /// * `SENTRA_PLUGIN_CONFIG` (optional) – path to a JSON configuration file.
 /// * `SENTRA_PLUGINS` (optional) – comma separated list of plugin names in order.
 /// * `LOG_FILE` (optional) – path to append newline‑delimited JSON telemetry.
+#[cfg(not(feature = "wasm"))]
 pub async fn build_state_from_env() -> Result<AppState, Box<dyn std::error::Error>> {
     let config = AppConfig::from_env().map_err(|e| -> Box<dyn std::error::Error> { e.into() })?;
-    let AppConfig {
-        plugin_config,
-        plugin_order,
-        log_file,
-        audit_log_file,
-        allowed_tokens,
-        rotation,
-        log_stdout,
-        max_request_bytes,
-        plugin_budget_ms,
-        plugin_warn_ms,
-        audit_only,
-        log_sample_n,
-    } = config;
-
-    let pipeline = PluginPipeline::new(&plugin_order, &plugin_config);
-
-    // Fixed histogram bucket upper bounds in ms (inclusive style for counting):
-    let buckets: Vec<u64> = vec![1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000];
-
-    // Pre-open writers (if configured). We do not create a default file implicitly; we warn if absent.
-    let telemetry_writer = match log_file.as_deref() {
-        Some(path) => {
-            match RotatingWriter::open(path, rotation.max_bytes, rotation.keep, rotation.compress) {
-                Ok(f) => Some(Arc::new(Mutex::new(f))),
-                Err(e) => {
-                    tracing::warn!(path=%path, error=%e, "Failed to open LOG_FILE for telemetry; telemetry disabled");
-                    None
-                }
-            }
+    Ok(AppStateBuilder::from_config(config).build())
+}
+
+/// Programmatic alternative to `build_state_from_env` for embedding Sentra
+/// as a library. Starts from the same defaults as an empty environment and
+/// never reads environment variables itself.
+#[cfg(not(feature = "wasm"))]
+#[derive(Clone, Default)]
+pub struct AppStateBuilder {
+    config: AppConfig,
+    plugins: Option<Vec<Arc<dyn Plugin>>>,
+}
+
+#[cfg(not(feature = "wasm"))]
+impl AppStateBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start from a complete configuration, e.g. `AppConfig::from_env()`.
+    pub fn from_config(config: AppConfig) -> Self {
+        Self {
+            config,
+            plugins: None,
+        }
+    }
+
+    /// Built-in plugin names in evaluation order (as in `SENTRA_PLUGINS`).
+    pub fn plugin_order<I, S>(mut self, order: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.config.plugin_order = order.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// Use these plugin instances instead of `plugin_order`.
+    pub fn plugins(mut self, plugins: Vec<Arc<dyn Plugin>>) -> Self {
+        self.plugins = Some(plugins);
+        self
+    }
+
+    pub fn plugin_config(mut self, plugin_config: PluginConfig) -> Self {
+        self.config.plugin_config = plugin_config;
+        self
+    }
+
+    pub fn plugin_budget_ms(mut self, ms: u64) -> Self {
+        self.config.plugin_budget_ms = ms;
+        self
+    }
+
+    pub fn plugin_warn_ms(mut self, ms: u64) -> Self {
+        self.config.plugin_warn_ms = ms;
+        self
+    }
+
+    /// Accepted bearer tokens; without this any token is accepted.
+    pub fn allowed_tokens<I, S>(mut self, tokens: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let tokens: HashSet<String> = tokens.into_iter().map(Into::into).collect();
+        self.config.allowed_tokens = (!tokens.is_empty()).then_some(tokens);
+        self
+    }
+
+    pub fn audit_only(mut self, audit_only: bool) -> Self {
+        self.config.audit_only = audit_only;
+        self
+    }
+
+    /// JSONL telemetry file (`LOG_FILE`).
+    pub fn log_file(mut self, path: impl Into<String>) -> Self {
+        self.config.log_file = Some(path.into());
+        self
+    }
+
+    /// JSONL audit file (`AUDIT_LOG_FILE`).
+    pub fn audit_log_file(mut self, path: impl Into<String>) -> Self {
+        self.config.audit_log_file = Some(path.into());
+        self
+    }
+
+    pub fn rotation(mut self, rotation: RotationConfig) -> Self {
+        self.config.rotation = rotation;
+        self
+    }
+
+    pub fn log_stdout(mut self, log_stdout: bool) -> Self {
+        self.config.log_stdout = log_stdout;
+        self
+    }
+
+    pub fn telemetry_hmac_key(mut self, key: impl Into<String>) -> Self {
+        self.config.telemetry_hmac_key = Some(key.into());
+        self
+    }
+
+    pub fn metrics_token(mut self, token: impl Into<String>) -> Self {
+        self.config.metrics_token = Some(token.into());
+        self
+    }
+
+    pub fn telemetry_queue(mut self, capacity: usize) -> Self {
+        self.config.telemetry_queue = Some(capacity).filter(|n| *n > 0);
+        self
+    }
+
+    pub fn build(self) -> AppState {
+        let AppConfig {
+            plugin_config,
+            plugin_order,
+            log_file,
+            audit_log_file,
+            allowed_tokens,
+            rotation,
+            log_stdout,
+            max_request_bytes,
+            gzip_min_bytes,
+            max_response_bytes,
+            plugin_budget_ms,
+            plugin_warn_ms,
+            audit_only,
+            audit_full_request,
+            log_sample_n,
+            telemetry_hmac_key,
+            telemetry_queue,
+            metrics_token,
+            require_metadata,
+            max_user_message_chars,
+            user_message_overflow,
+            strict_schema,
+            verbose_allow,
+            debug_capture,
+            evaluate_all,
+            telemetry_plugin_diagnostics,
+        } = self.config;
+        if rotation.compress && !cfg!(feature = "compression") {
+            tracing::warn!(
+                "LOG_ROTATE_COMPRESS set but Sentra was built without the compression feature; rotated logs stay uncompressed"
+            );
         }
-        None => {
-            tracing::warn!("Telemetry disabled: LOG_FILE not set");
-            None
+        if debug_capture {
+            tracing::warn!(
+                "SENTRA_DEBUG_CAPTURE is on: redacted request bodies and decisions are logged at debug level; for debugging only"
+            );
         }
-    };
-    let audit_writer = match audit_log_file.as_deref() {
-        Some(path) => {
-            match RotatingWriter::open(path, rotation.max_bytes, rotation.keep, rotation.compress) {
-                Ok(f) => Some(Arc::new(Mutex::new(f))),
-                Err(e) => {
-                    tracing::warn!(path=%path, error=%e, "Failed to open AUDIT_LOG_FILE; audit records will fall back or be disabled");
-                    None
+        if strict_schema && !cfg!(feature = "strict-schema") {
+            tracing::warn!(
+                "SENTRA_STRICT_SCHEMA set but Sentra was built without the strict-schema feature; payloads are not schema-checked"
+            );
+        }
+        if telemetry_plugin_diagnostics && !evaluate_all {
+            tracing::warn!(
+                "SENTRA_TELEMETRY_PLUGIN_DIAGNOSTICS has no effect without SENTRA_EVALUATE_ALL"
+            );
+        }
+
+        // Caller-supplied plugins replace the name-based registry; metrics and
+        // the config hash then follow their names.
+        let (pipeline, plugin_order) = match self.plugins {
+            Some(plugins) => {
+                let pipeline = PluginPipeline::from_plugins(plugins);
+                let order = pipeline.names().into_iter().map(String::from).collect();
+                (pipeline, order)
+            }
+            None => (
+                PluginPipeline::new(&plugin_order, &plugin_config),
+                plugin_order,
+            ),
+        };
+        let config_hash: Arc<str> = telemetry::config_hash(&plugin_order, &plugin_config).into();
+        let policy_rules_loaded = if pipeline.names().contains(&"policy_pack") {
+            plugins::policy_pack::loaded_rule_count(&plugin_config)
+        } else {
+            0
+        };
+
+        // Fixed histogram bucket upper bounds in ms (inclusive style for counting):
+        let buckets = util::histogram_buckets(vec![1, 2, 5, 10, 20, 50, 100, 200, 500, 1000, 2000]);
+
+        // Pre-open writers (if configured). We do not create a default file implicitly; we warn if absent.
+        let telemetry_writer = match log_file.as_deref() {
+            Some(path) => {
+                match RotatingWriter::open(
+                    path,
+                    rotation.max_bytes,
+                    rotation.keep,
+                    rotation.compress,
+                ) {
+                    Ok(f) => Some(Arc::new(Mutex::new(f))),
+                    Err(e) => {
+                        tracing::warn!(path=%path, error=%e, "Failed to open LOG_FILE for telemetry; telemetry disabled");
+                        None
+                    }
                 }
             }
+            None => {
+                tracing::warn!("Telemetry disabled: LOG_FILE not set");
+                None
+            }
+        };
+        let audit_writer = match audit_log_file.as_deref() {
+            Some(path) => {
+                match RotatingWriter::open(
+                    path,
+                    rotation.max_bytes,
+                    rotation.keep,
+                    rotation.compress,
+                ) {
+                    Ok(f) => Some(Arc::new(Mutex::new(f))),
+                    Err(e) => {
+                        tracing::warn!(path=%path, error=%e, "Failed to open AUDIT_LOG_FILE; audit records will fall back or be disabled");
+                        None
+                    }
+                }
+            }
+            None => None,
+        };
+
+        let metric_requests_total = Arc::new(AtomicU64::new(0));
+        let metric_blocks_total = Arc::new(AtomicU64::new(0));
+        let metric_audit_suppressed_total = Arc::new(AtomicU64::new(0));
+        let metric_lines_total = Arc::new(AtomicU64::new(0));
+        let metric_write_errors_total = Arc::new(AtomicU64::new(0));
+        let log_file_size_bytes = Arc::new(AtomicU64::new(0));
+
+        let mut telemetry = TelemetrySink::new(
+            telemetry_writer,
+            audit_writer,
+            log_stdout,
+            log_sample_n,
+            metric_lines_total.clone(),
+            metric_write_errors_total.clone(),
+            log_file_size_bytes.clone(),
+        );
+        if let Some(key) = telemetry_hmac_key.as_deref() {
+            telemetry = telemetry.with_hmac_key(key.as_bytes());
+        }
+        if let Some(capacity) = telemetry_queue {
+            telemetry = telemetry.with_write_queue(capacity);
         }
-        None => None,
-    };
 
-    let metric_requests_total = Arc::new(AtomicU64::new(0));
-    let metric_blocks_total = Arc::new(AtomicU64::new(0));
-    let metric_audit_suppressed_total = Arc::new(AtomicU64::new(0));
-    let metric_lines_total = Arc::new(AtomicU64::new(0));
-    let metric_write_errors_total = Arc::new(AtomicU64::new(0));
-    let log_file_size_bytes = Arc::new(AtomicU64::new(0));
-
-    let telemetry = TelemetrySink::new(
-        telemetry_writer,
-        audit_writer,
-        log_stdout,
-        log_sample_n,
-        metric_lines_total.clone(),
-        metric_write_errors_total.clone(),
-        log_file_size_bytes.clone(),
-    );
+        // Prepare per-plugin metrics structures based on declared order. A
+        // repeated name (already skipped by the pipeline) shares one series.
+        let mut index_map = std::collections::HashMap::new();
+        let mut metric_names = Vec::new();
+        let mut plugin_metrics_vec = Vec::new();
+        for name in &plugin_order {
+            if index_map.contains_key(name) {
+                continue;
+            }
+            index_map.insert(name.clone(), metric_names.len());
+            metric_names.push(name.clone());
+            plugin_metrics_vec.push(PluginMetrics::new(&buckets));
+        }
 
-    // Prepare per-plugin metrics structures based on declared order.
-    let mut index_map = std::collections::HashMap::new();
-    let mut plugin_metrics_vec = Vec::new();
-    for (i, name) in plugin_order.iter().enumerate() {
-        index_map.insert(name.clone(), i);
-        plugin_metrics_vec.push(PluginMetrics {
-            eval_sum_ms: AtomicU64::new(0),
-            eval_count: AtomicU64::new(0),
-            block_count: AtomicU64::new(0),
-            hist_counts: buckets.iter().map(|_| AtomicU64::new(0)).collect(),
-            hist_sum_ms: AtomicU64::new(0),
-            hist_count: AtomicU64::new(0),
-        });
+        let start_time = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default();
+
+        AppState {
+            pipeline,
+            plugin_config,
+            log_file,
+            allowed_tokens,
+            metrics_token,
+            max_request_bytes,
+            gzip_min_bytes,
+            max_response_bytes,
+            plugin_budget_ms,
+            plugin_warn_ms,
+            audit_only,
+            audit_full_request,
+            require_metadata,
+            max_user_message_chars,
+            user_message_overflow,
+            strict_schema,
+            verbose_allow,
+            debug_capture,
+            evaluate_all,
+            telemetry_plugin_diagnostics,
+            audit_log_file,
+            telemetry,
+            config_hash,
+            metric_requests_total,
+            metric_blocks_total,
+            metric_audit_suppressed_total,
+            metric_inflight_requests: Arc::new(AtomicU64::new(0)),
+            metric_block_rate_ewma: Arc::new(BlockRateEwma::default()),
+            policy_rules_loaded,
+            hist_buckets: Arc::new(buckets.clone()),
+            hist_counts: Arc::new(buckets.iter().map(|_| AtomicU64::new(0)).collect()),
+            hist_sum_ms: Arc::new(AtomicU64::new(0)),
+            hist_count: Arc::new(AtomicU64::new(0)),
+            allow_hist: Arc::new(DecisionHistogram::new(&buckets)),
+            block_hist: Arc::new(DecisionHistogram::new(&buckets)),
+            plugin_metric_indices: Arc::new(index_map),
+            plugin_metric_names: Arc::new(metric_names),
+            plugin_metrics: Arc::new(plugin_metrics_vec),
+            process_start_epoch: start_time.as_secs_f64(),
+            process_start_instant: Instant::now(),
+        }
     }
+}
 
-    let start_time = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap_or_default();
-
-    Ok(AppState {
-        pipeline,
-        plugin_config,
-        log_file,
-        allowed_tokens,
-        max_request_bytes,
-        plugin_budget_ms,
-        plugin_warn_ms,
-        audit_only,
-        audit_log_file,
-        telemetry,
-        metric_requests_total,
-        metric_blocks_total,
-        metric_audit_suppressed_total,
-        hist_buckets: Arc::new(buckets.clone()),
-        hist_counts: Arc::new(buckets.iter().map(|_| AtomicU64::new(0)).collect()),
-        hist_sum_ms: Arc::new(AtomicU64::new(0)),
-        hist_count: Arc::new(AtomicU64::new(0)),
-        plugin_metric_indices: Arc::new(index_map),
-        plugin_metrics: Arc::new(plugin_metrics_vec),
-        process_start_epoch: start_time.as_secs_f64(),
-        process_start_instant: Instant::now(),
-    })
+/// axum's `DefaultBodyLimit`, which applies when `SENTRA_MAX_REQUEST_BYTES`
+/// is unset. Middleware that buffers the body itself uses the same cap.
+#[cfg(any(feature = "compression", feature = "strict-schema"))]
+const DEFAULT_MAX_REQUEST_BYTES: usize = 2 * 1024 * 1024;
+
+/// Largest body accepted on the analyze route, configured or default.
+#[cfg(any(feature = "compression", feature = "strict-schema"))]
+fn request_body_limit(state: &AppState) -> usize {
+    state.max_request_bytes.unwrap_or(DEFAULT_MAX_REQUEST_BYTES)
 }
 
 /// Build the Axum router and attach handlers.  The router holds a copy
 /// of the `AppState` for each invocation.
+#[cfg(not(feature = "wasm"))]
 pub fn app(state: AppState) -> Router {
     let max_request_bytes = state.max_request_bytes;
 
+    let analyze = post(analyze_handler);
+    #[cfg(feature = "strict-schema")]
+    let analyze = analyze.route_layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        validate_schema,
+    ));
+    #[cfg(feature = "compression")]
+    let analyze = analyze.route_layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        decompress_gzip_body,
+    ));
+    let analyze = analyze.route_layer(axum::middleware::from_fn(assign_request_id));
+    let validate = post(validate_handler).route_layer(axum::middleware::from_fn(assign_request_id));
+
     let router = Router::new()
-        .route("/validate", post(validate_handler))
-        .route("/analyze-tool-execution", post(analyze_handler))
+        .route("/validate", validate)
+        .route("/analyze-tool-execution", analyze)
         .route("/healthz", axum::routing::get(healthz_handler))
-        .route("/metrics", axum::routing::get(metrics_handler));
+        .route("/reason-codes", axum::routing::get(reason_codes_handler));
+    #[cfg(feature = "metrics")]
+    let router = router.route("/metrics", axum::routing::get(metrics_handler));
+    #[cfg(feature = "compression")]
+    let router = router.layer(axum::middleware::from_fn_with_state(
+        state.clone(),
+        compress_gzip_response,
+    ));
 
     let router = if let Some(limit) = max_request_bytes {
         router.layer(DefaultBodyLimit::max(limit))
@@ -627,6 +1248,7 @@ pub fn app(state: AppState) -> Router {
 }
 
 /// Query parameters/// Query parameters for versioning.  Only `api-version` matters.
+#[cfg(not(feature = "wasm"))]
 #[derive(Debug, Deserialize)]
 struct VersionQuery {
     #[serde(rename = "api-version")]
@@ -634,21 +1256,43 @@ struct VersionQuery {
 }
 
 // Constant API version supported by this implementation.
+#[cfg(not(feature = "wasm"))]
 const API_VERSION: &str = "2025-05-01";
 
+#[cfg(not(feature = "wasm"))]
 fn respond_with_error(err: ErrorResponse) -> axum::response::Response {
     let status = StatusCode::from_u16(err.http_status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
     (status, Json(err)).into_response()
 }
 
+/// Server-generated id of the request being handled, set by
+/// `assign_request_id` ahead of every other analyze/validate layer.
+#[cfg(not(feature = "wasm"))]
+#[derive(Clone, Debug)]
+struct RequestId(String);
+
+/// Tag the request with a fresh `RequestId` for the layers and handler below
+/// (their `ErrorResponse`s carry it as `requestId`) and echo it in the
+/// `x-sentra-request-id` response header.
+#[cfg(not(feature = "wasm"))]
+async fn assign_request_id(
+    mut req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    let request_id = util::request_id();
+    let header = HeaderValue::from_str(&request_id).ok();
+    req.extensions_mut().insert(RequestId(request_id));
+    let mut response = next.run(req).await;
+    if let Some(value) = header {
+        response.headers_mut().insert("x-sentra-request-id", value);
+    }
+    response
+}
+
+#[cfg(not(feature = "wasm"))]
 fn ensure_api_version(params: &VersionQuery) -> Result<(), ErrorResponse> {
     match params.api_version.as_deref() {
-        None => Err(ErrorResponse {
-            error_code: 4000,
-            message: format!("Missing api-version (expected {})", API_VERSION),
-            http_status: 400,
-            diagnostics: None,
-        }),
+        None => Err(errors::missing_api_version(API_VERSION)),
         Some(v) if v != API_VERSION => {
             tracing::info!(client_api_version=%v, supported=API_VERSION, "Proceeding with forward-compatible api-version");
             Ok(())
@@ -657,31 +1301,24 @@ fn ensure_api_version(params: &VersionQuery) -> Result<(), ErrorResponse> {
     }
 }
 
-fn authorization_error() -> ErrorResponse {
-    ErrorResponse {
-        error_code: 2001,
-        message: "Unauthorized".into(),
-        http_status: 401,
-        diagnostics: None,
-    }
-}
-
+#[cfg(not(feature = "wasm"))]
 fn extract_bearer_token(headers: &HeaderMap) -> Result<String, ErrorResponse> {
     let raw = headers
         .get("authorization")
         .and_then(|v| v.to_str().ok())
-        .ok_or_else(authorization_error)?;
+        .ok_or_else(errors::unauthorized)?;
 
     if raw.len() < 7 || !raw[..6].eq_ignore_ascii_case("bearer") {
-        return Err(authorization_error());
+        return Err(errors::unauthorized());
     }
     let token = raw[6..].trim();
     if token.is_empty() {
-        return Err(authorization_error());
+        return Err(errors::unauthorized());
     }
     Ok(token.to_string())
 }
 
+#[cfg(not(feature = "wasm"))]
 fn ensure_authorized(
     headers: &HeaderMap,
     allowed_tokens: Option<&HashSet<String>>,
@@ -689,7 +1326,7 @@ fn ensure_authorized(
     let token = extract_bearer_token(headers)?;
     if let Some(tokens) = allowed_tokens {
         if !tokens.contains(&token) {
-            return Err(authorization_error());
+            return Err(errors::unauthorized());
         }
     }
     Ok(())
@@ -697,88 +1334,196 @@ fn ensure_authorized(
 
 /// Handler for the `/validate` endpoint.  Ensures the correct API version is
 /// provided and that an authorized bearer token accompanies the request.
+#[cfg(not(feature = "wasm"))]
 async fn validate_handler(
     state: State<AppState>,
     axum::extract::Query(params): axum::extract::Query<VersionQuery>,
+    Extension(RequestId(request_id)): Extension<RequestId>,
     headers: HeaderMap,
 ) -> axum::response::Response {
+    let fail = |err: ErrorResponse| respond_with_error(err.with_request_id(&request_id));
     if let Err(err) = ensure_api_version(&params) {
-        return respond_with_error(err);
+        return fail(err);
     }
     if let Err(err) = ensure_authorized(&headers, state.allowed_tokens.as_ref()) {
-        return respond_with_error(err);
+        return fail(err);
     }
     let ok = serde_json::json!({ "isSuccessful": true, "status": "OK" });
     (StatusCode::OK, Json(ok)).into_response()
 }
 
+/// Smoothing factor of `sentra_block_rate_ewma`: each decision moves the
+/// rate 5% of the way toward 1 (block) or 0 (allow), so it reflects roughly
+/// the last 20 decisions.
+#[cfg(not(feature = "wasm"))]
+const BLOCK_RATE_EWMA_ALPHA: f64 = 0.05;
+
+/// `sentra_block_rate_ewma`: an exponentially weighted moving average of
+/// block decisions, stored as `f64` bits so it can be updated lock-free.
+#[cfg(not(feature = "wasm"))]
+#[derive(Default)]
+pub struct BlockRateEwma(AtomicU64);
+
+#[cfg(not(feature = "wasm"))]
+impl BlockRateEwma {
+    /// Current smoothed rate in `[0, 1]`; 0 before the first decision.
+    pub fn get(&self) -> f64 {
+        f64::from_bits(self.0.load(Ordering::Relaxed))
+    }
+
+    fn observe(&self, blocked: bool) {
+        let sample = if blocked { 1.0 } else { 0.0 };
+        let _ = self
+            .0
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |bits| {
+                let rate = f64::from_bits(bits);
+                Some((rate + BLOCK_RATE_EWMA_ALPHA * (sample - rate)).to_bits())
+            });
+    }
+}
+
+/// Keeps `sentra_inflight_requests` accurate across every return path of
+/// `analyze_handler`: incremented on entry, decremented on drop.
+#[cfg(not(feature = "wasm"))]
+struct InflightGuard(Arc<AtomicU64>);
+
+#[cfg(not(feature = "wasm"))]
+impl InflightGuard {
+    fn enter(gauge: &Arc<AtomicU64>) -> Self {
+        gauge.fetch_add(1, Ordering::Relaxed);
+        InflightGuard(gauge.clone())
+    }
+}
+
+#[cfg(not(feature = "wasm"))]
+impl Drop for InflightGuard {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
 /// Handler for `/analyze-tool-execution`.  Parses the request, constructs
 /// evaluation context and invokes the plugin pipeline.  Responds with an
 /// `AnalyzeResponse` on success or an `ErrorResponse` if validation fails.
+#[cfg(not(feature = "wasm"))]
 async fn analyze_handler(
     state: State<AppState>,
     axum::extract::Query(params): axum::extract::Query<VersionQuery>,
+    Extension(RequestId(request_id)): Extension<RequestId>,
     headers: HeaderMap,
     payload: Result<Json<AnalyzeRequest>, JsonRejection>,
 ) -> axum::response::Response {
+    let request_id = request_id.as_str();
+    let _inflight = InflightGuard::enter(&state.metric_inflight_requests);
+    let fail = |err: ErrorResponse| respond_with_error(err.with_request_id(request_id));
     // Size guard: rely on Content-Length header if provided.
     if let Some(limit) = state.max_request_bytes {
         if let Some(len_header) = headers.get("content-length").and_then(|v| v.to_str().ok()) {
             if let Ok(clen) = len_header.parse::<usize>() {
                 if clen > limit {
-                    let err = ErrorResponse {
-                        error_code: 4001,
-                        message: format!(
-                            "Request too large ({} bytes > limit {} bytes)",
-                            clen, limit
-                        ),
-                        http_status: 413,
-                        diagnostics: None,
-                    };
-                    return respond_with_error(err);
+                    return fail(errors::content_length_too_large(clen, limit));
                 }
             }
         }
     }
     if let Err(err) = ensure_api_version(&params) {
-        return respond_with_error(err);
+        return fail(err);
     }
     if let Err(err) = ensure_authorized(&headers, state.allowed_tokens.as_ref()) {
-        return respond_with_error(err);
+        return fail(err);
+    }
+    if let Err(err) = ensure_json_content_type(&headers) {
+        return fail(err);
     }
 
     let payload = match payload {
         Ok(Json(inner)) => inner,
         Err(rejection) => {
-            return handle_json_rejection(&state, rejection);
+            return handle_json_rejection(&state, rejection, request_id);
         }
     };
 
+    let token = extract_bearer_token(&headers).ok();
+    let corr = headers
+        .get("x-ms-correlation-id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+    match decide(&state, &payload, token.as_deref(), corr, request_id).await {
+        Ok(response) => {
+            let headers = decision_headers(&response);
+            (StatusCode::OK, headers, Json(response)).into_response()
+        }
+        Err(err) => fail(err),
+    }
+}
+
+/// Decide on `req` without the HTTP layer: payload validation, pipeline run,
+/// audit-only override, telemetry and metrics, exactly as
+/// `POST /analyze-tool-execution` does. Transport checks (auth, api-version,
+/// size, content type) are left to the caller, and without a bearer token
+/// neither `PluginConfig::trust` nor `bypass_tokens` applies.
+#[cfg(not(feature = "wasm"))]
+pub async fn evaluate(
+    req: &AnalyzeRequest,
+    state: &AppState,
+) -> Result<AnalyzeResponse, ErrorResponse> {
+    decide(state, req, None, "", &util::request_id()).await
+}
+
+#[cfg(not(feature = "wasm"))]
+async fn decide(
+    state: &AppState,
+    payload: &AnalyzeRequest,
+    token: Option<&str>,
+    corr: &str,
+    request_id: &str,
+) -> Result<AnalyzeResponse, ErrorResponse> {
     // Validate required payload fields (spec compliance)
     let missing = payload.missing_required_fields();
     if !missing.is_empty() {
-        let all_missing = missing.join(", ");
-        let err = ErrorResponse {
-            error_code: 4002,
-            message: format!("Missing or empty required field(s): {}", all_missing),
-            http_status: 400,
-            diagnostics: None,
-        };
-        return (StatusCode::BAD_REQUEST, Json(err)).into_response();
+        return Err(errors::missing_required_fields(&missing));
+    }
+    if state.require_metadata {
+        let missing = payload.missing_metadata_fields();
+        if !missing.is_empty() {
+            return Err(errors::missing_conversation_metadata(&missing));
+        }
     }
+    let payload = &*limit_user_message(state, payload)?;
 
     let start = Instant::now();
     // Build evaluation context per request
-    let ctx = EvalContext::from_request(
-        &payload,
+    let mut ctx = EvalContext::from_request(
+        payload,
         &state.plugin_config,
         state.plugin_budget_ms,
         state.plugin_warn_ms,
     );
-    let (would_be_response, plugin_timings) = state
-        .pipeline
-        .evaluate_with_timings(&payload, &ctx, &state.plugin_config)
-        .await;
+    ctx.trusted = state.plugin_config.trust.is_trusted(payload, token);
+    if ctx.trusted {
+        tracing::info!(skip = ?state.plugin_config.trust.skip_plugins, "trusted request");
+    }
+    let bypassed = state.plugin_config.bypasses(payload, token);
+    let plugins::Evaluation {
+        response: would_be_response,
+        timings: plugin_timings,
+        outcomes: plugin_outcomes,
+        signals,
+        allowed: allowing_plugins,
+        skipped: skipped_plugins,
+        trusted_skips,
+        panicked: panicked_plugins,
+        timed_out: timed_out_plugins,
+        monitored,
+    } = if bypassed {
+        tracing::info!("bypass-listed caller, plugins not run");
+        plugins::Evaluation::allow()
+    } else {
+        state
+            .pipeline
+            .evaluate(payload, &ctx, &state.plugin_config, !state.evaluate_all)
+            .await
+    };
     // If audit only mode is enabled and a block would occur, override outward response.
     let response = if state.audit_only && would_be_response.block_action {
         AnalyzeResponse {
@@ -791,37 +1536,54 @@ async fn analyze_handler(
     } else {
         would_be_response.clone()
     };
+    let response = if state.verbose_allow && !response.block_action {
+        AnalyzeResponse {
+            diagnostics: Some(allow_diagnostics(
+                &plugin_timings,
+                &plugin_outcomes,
+                &skipped_plugins,
+            )),
+            ..response
+        }
+    } else {
+        response
+    };
+    let response = cap_response(response, state.max_response_bytes);
     let latency_ms = start.elapsed().as_millis();
     // Histogram update
     let latency_u64 = latency_ms as u64;
     state.hist_sum_ms.fetch_add(latency_u64, Ordering::Relaxed);
     state.hist_count.fetch_add(1, Ordering::Relaxed);
-    // find first bucket >= value
-    for (idx, ub) in state.hist_buckets.iter().enumerate() {
-        if latency_u64 <= *ub {
-            state.hist_counts[idx].fetch_add(1, Ordering::Relaxed);
-            break;
-        }
+    if let Some(idx) = util::bucket_index(&state.hist_buckets, latency_u64) {
+        state.hist_counts[idx].fetch_add(1, Ordering::Relaxed);
     }
+    let decision_hist = if response.block_action {
+        &state.block_hist
+    } else {
+        &state.allow_hist
+    };
+    decision_hist.observe(&state.hist_buckets, latency_u64);
 
     // Construct telemetry event payload
-    let corr = headers
-        .get("x-ms-correlation-id")
-        .and_then(|v| v.to_str().ok())
-        .unwrap_or("");
     let audit_suppressed = state.audit_only && would_be_response.block_action;
-    let telem = serde_json::json!({
-        "schemaVersion": 1,
-        "ts": chrono::Utc::now().to_rfc3339(),
-        "correlationId": corr,
-        "blockAction": response.block_action,
-        "reasonCode": response.reason_code,
-        "blockedBy": response.blocked_by.clone(),
-        "latencyMs": latency_ms,
-        "diagnostics": response.diagnostics.clone(),
-        "auditSuppressed": if audit_suppressed { Some(true) } else { None },
-        "pluginTimings": plugin_timings.iter().map(|(n,t)| serde_json::json!({"plugin":n, "ms": t})).collect::<Vec<_>>()
-    });
+    let mut event = telemetry::TelemetryEvent::new(
+        &response,
+        audit_suppressed,
+        corr,
+        latency_ms,
+        &plugin_timings,
+        &state.config_hash,
+    )
+    .with_outcomes(&plugin_outcomes)
+    .with_trusted_skips(&trusted_skips);
+    event.request_id = Some(request_id);
+    event.skipped_plugins = &skipped_plugins;
+    event.bypassed = bypassed.then_some(true);
+    event.monitored = &monitored;
+    if state.telemetry_plugin_diagnostics && signals.len() > 1 {
+        event.plugin_diagnostics = Some(plugin_diagnostics(&signals));
+    }
+    let telem = serde_json::to_value(&event).unwrap_or_default();
     state.telemetry.emit_event(
         &telem,
         &TelemetryLogFields {
@@ -834,18 +1596,33 @@ async fn analyze_handler(
         },
     );
 
+    // Debug-only capture; skipped entirely unless debug logging is on.
+    if state.debug_capture
+        && tracing::enabled!(target: "sentra::debug_capture", tracing::Level::DEBUG)
+    {
+        let request = util::redact_json(&serde_json::to_value(payload).unwrap_or_default());
+        let decision = util::redact_json(&serde_json::to_value(&response).unwrap_or_default());
+        tracing::debug!(
+            target: "sentra::debug_capture",
+            correlation_id = corr,
+            request = %request,
+            decision = %decision,
+            "debug capture (redacted)"
+        );
+    }
+
     if state.audit_only && would_be_response.block_action {
-        let record = serde_json::json!({
-            "schemaVersion": 1,
-            "ts": chrono::Utc::now().to_rfc3339(),
-            "correlationId": corr,
-            "auditOnly": true,
-            "wouldBlock": true,
-            "wouldResponse": &would_be_response,
-            "request": &payload,
-        });
+        let mut record = telemetry::AuditEvent::new(
+            corr,
+            &would_be_response,
+            audit_preview(payload, &would_be_response),
+        );
+        record.request_id = Some(request_id);
+        if state.audit_full_request {
+            record.request = Some(payload);
+        }
         state.telemetry.emit_audit(
-            &record,
+            &serde_json::to_value(&record).unwrap_or_default(),
             &AuditLogFields {
                 would_block: would_be_response.block_action,
                 reason_code: would_be_response.reason_code,
@@ -854,8 +1631,31 @@ async fn analyze_handler(
             },
         );
     }
+    for would_block in &monitored {
+        let mut record = telemetry::AuditEvent::monitored(
+            corr,
+            would_block,
+            audit_preview(payload, would_block),
+        );
+        record.request_id = Some(request_id);
+        if state.audit_full_request {
+            record.request = Some(payload);
+        }
+        state.telemetry.emit_audit(
+            &serde_json::to_value(&record).unwrap_or_default(),
+            &AuditLogFields {
+                would_block: true,
+                reason_code: would_block.reason_code,
+                blocked_by: would_block.blocked_by.as_deref(),
+                plugin_count: plugin_timings.len(),
+            },
+        );
+    }
     // Metrics increments
     state.metric_requests_total.fetch_add(1, Ordering::Relaxed);
+    state
+        .metric_block_rate_ewma
+        .observe(would_be_response.block_action);
     if would_be_response.block_action {
         state.metric_blocks_total.fetch_add(1, Ordering::Relaxed);
     }
@@ -864,8 +1664,12 @@ async fn analyze_handler(
             .metric_audit_suppressed_total
             .fetch_add(1, Ordering::Relaxed);
     }
-    // Per-plugin timing metrics
-    for (name, ms) in &plugin_timings {
+    // Per-plugin timing metrics, for plugins whose `eval` actually ran
+    for ((name, ms), _) in plugin_timings
+        .iter()
+        .zip(&plugin_outcomes)
+        .filter(|(_, outcome)| **outcome != PluginOutcome::Skipped)
+    {
         if let Some(idx) = state.plugin_metric_indices.get(name.as_str()) {
             if let Some(pm) = state.plugin_metrics.get(*idx) {
                 pm.eval_sum_ms.fetch_add(*ms, Ordering::Relaxed);
@@ -874,27 +1678,264 @@ async fn analyze_handler(
                 let ms_u64 = *ms;
                 pm.hist_sum_ms.fetch_add(ms_u64, Ordering::Relaxed);
                 pm.hist_count.fetch_add(1, Ordering::Relaxed);
-                for (bidx, ub) in state.hist_buckets.iter().enumerate() {
-                    if ms_u64 <= *ub {
-                        pm.hist_counts[bidx].fetch_add(1, Ordering::Relaxed);
-                        break;
-                    }
+                if let Some(bidx) = util::bucket_index(&state.hist_buckets, ms_u64) {
+                    pm.hist_counts[bidx].fetch_add(1, Ordering::Relaxed);
                 }
             }
         }
     }
+    for name in &allowing_plugins {
+        if let Some(pm) = state
+            .plugin_metric_indices
+            .get(name.as_str())
+            .and_then(|idx| state.plugin_metrics.get(*idx))
+        {
+            pm.allow_count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    for name in &panicked_plugins {
+        if let Some(pm) = state
+            .plugin_metric_indices
+            .get(name.as_str())
+            .and_then(|idx| state.plugin_metrics.get(*idx))
+        {
+            pm.panic_count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    for name in &timed_out_plugins {
+        if let Some(pm) = state
+            .plugin_metric_indices
+            .get(name.as_str())
+            .and_then(|idx| state.plugin_metrics.get(*idx))
+        {
+            pm.timeout_count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+    for name in monitored.iter().filter_map(|r| r.blocked_by.as_deref()) {
+        if let Some(pm) = state
+            .plugin_metric_indices
+            .get(name)
+            .and_then(|idx| state.plugin_metrics.get(*idx))
+        {
+            pm.monitored_count.fetch_add(1, Ordering::Relaxed);
+        }
+    }
     // Per-plugin block counter (would-be blocker)
     if let Some(blocked_by) = &would_be_response.blocked_by {
         if let Some(idx) = state.plugin_metric_indices.get(blocked_by.as_str()) {
             if let Some(pm) = state.plugin_metrics.get(*idx) {
                 pm.block_count.fetch_add(1, Ordering::Relaxed);
+                if let Some(code) = would_be_response.reason_code {
+                    pm.record_block_reason(code);
+                }
             }
         }
     }
-    (StatusCode::OK, Json(response)).into_response()
+    Ok(response)
+}
+
+/// Keep a decision within `limit` serialized bytes, so plugin- or
+/// upstream-controlled content cannot inflate responses and telemetry.
+/// Oversized diagnostics shrink to their contract members plus `truncated`
+/// and `originalBytes`; if that is not enough, the reason is cut to the bytes
+/// left (or dropped), then the diagnostics are dropped. Only a `limit`
+/// smaller than the bare decision fields can still be exceeded.
+#[cfg(not(feature = "wasm"))]
+fn cap_response(mut response: AnalyzeResponse, limit: usize) -> AnalyzeResponse {
+    let size = |r: &AnalyzeResponse| serde_json::to_vec(r).map_or(0, |b| b.len());
+    let original = size(&response);
+    if original <= limit {
+        return response;
+    }
+    tracing::warn!(
+        bytes = original,
+        limit,
+        blocked_by = ?response.blocked_by,
+        "response over size cap, summarizing diagnostics"
+    );
+    response.diagnostics = response.diagnostics.map(|diagnostics| {
+        let mut summary = plugins::contract_members(diagnostics);
+        summary.insert("truncated".into(), true.into());
+        summary.insert("originalBytes".into(), original.into());
+        serde_json::Value::Object(summary)
+    });
+    loop {
+        let excess = size(&response).saturating_sub(limit);
+        let Some(reason) = response.reason.as_mut().filter(|_| excess > 0) else {
+            break;
+        };
+        let Some(mut keep) = reason.len().checked_sub(excess + '…'.len_utf8()) else {
+            response.reason = None;
+            break;
+        };
+        while !reason.is_char_boundary(keep) {
+            keep -= 1;
+        }
+        reason.truncate(keep);
+        reason.push('…');
+    }
+    if size(&response) > limit {
+        response.diagnostics = None;
+    }
+    let capped = size(&response);
+    if capped > limit {
+        tracing::warn!(
+            bytes = capped,
+            limit,
+            "response cap below the bare decision size"
+        );
+    }
+    response
+}
+
+/// Explain an allow decision: which plugins ran and passed, and which were
+/// skipped because the budget ran out. Plugins that did not apply (or that a
+/// trusted request skipped) are in neither list.
+#[cfg(not(feature = "wasm"))]
+fn allow_diagnostics(
+    timings: &[(String, u64)],
+    outcomes: &[PluginOutcome],
+    skipped: &[String],
+) -> serde_json::Value {
+    let evaluated: Vec<&str> = timings
+        .iter()
+        .zip(outcomes)
+        .filter(|(_, outcome)| **outcome != PluginOutcome::Skipped)
+        .map(|((name, _), _)| name.as_str())
+        .collect();
+    plugins::Diagnostics::new("pipeline", "allow")
+        .with("evaluated", evaluated.into())
+        .with("skipped", skipped.into())
+        .into()
+}
+
+/// Compact per-plugin summary of every blocking signal: plugin, reason code
+/// and diagnostics code (full diagnostics stay on the winning response only).
+#[cfg(not(feature = "wasm"))]
+fn plugin_diagnostics(signals: &[AnalyzeResponse]) -> serde_json::Value {
+    signals
+        .iter()
+        .map(|s| {
+            serde_json::json!({
+                "plugin": s.blocked_by,
+                "reasonCode": s.reason_code,
+                "code": s.diagnostics.as_ref().and_then(|d| d.get("code")),
+            })
+        })
+        .collect()
+}
+
+/// Mirror the outward decision into response headers so proxies can route
+/// without parsing the JSON body.
+#[cfg(not(feature = "wasm"))]
+fn decision_headers(response: &AnalyzeResponse) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        "x-sentra-block",
+        HeaderValue::from_static(if response.block_action {
+            "true"
+        } else {
+            "false"
+        }),
+    );
+    if let Some(code) = response.reason_code {
+        headers.insert("x-sentra-reason-code", HeaderValue::from(code));
+    }
+    if let Some(value) = response
+        .blocked_by
+        .as_deref()
+        .and_then(|b| HeaderValue::from_str(b).ok())
+    {
+        headers.insert("x-sentra-blocked-by", value);
+    }
+    headers
+}
+
+/// Maximum characters kept in the audit `preview` field.
+#[cfg(not(feature = "wasm"))]
+const AUDIT_PREVIEW_CHARS: usize = 120;
+
+/// Redacted preview of the content that triggered a block: the input field
+/// named in the diagnostics, else the first request text holding a secret
+/// or PII, else the user message. Secrets and PII found by the `secrets` and
+/// `pii` detectors are masked before the generic `util::redact` pass.
+#[cfg(not(feature = "wasm"))]
+fn audit_preview(req: &AnalyzeRequest, resp: &AnalyzeResponse) -> String {
+    let mask = |text: &str| plugins::pii::redact(&plugins::secrets::redact(text));
+    let named = resp
+        .diagnostics
+        .as_ref()
+        .and_then(|d| d.get("field"))
+        .and_then(|f| f.as_str())
+        .and_then(|field| req.input_values.get(field))
+        .and_then(|v| v.as_str());
+    let masked = named
+        .map(mask)
+        .or_else(|| {
+            util::request_texts(req)
+                .into_iter()
+                .map(|text| (text, mask(text)))
+                .find(|(text, masked)| masked != text)
+                .map(|(_, masked)| masked)
+        })
+        .unwrap_or_else(|| mask(req.planner_context.user_message.as_deref().unwrap_or("")));
+    util::redacted_preview(&masked, AUDIT_PREVIEW_CHARS)
+}
+
+/// Apply `max_user_message_chars`: a copy with the message cut on a char
+/// boundary in truncate mode, an error in reject mode.
+#[cfg(not(feature = "wasm"))]
+fn limit_user_message<'a>(
+    state: &AppState,
+    payload: &'a AnalyzeRequest,
+) -> Result<std::borrow::Cow<'a, AnalyzeRequest>, ErrorResponse> {
+    use std::borrow::Cow;
+
+    let (Some(limit), Some(message)) = (
+        state.max_user_message_chars,
+        payload.planner_context.user_message.as_deref(),
+    ) else {
+        return Ok(Cow::Borrowed(payload));
+    };
+    let Some((cut, _)) = message.char_indices().nth(limit) else {
+        return Ok(Cow::Borrowed(payload));
+    };
+    match state.user_message_overflow {
+        UserMessageOverflow::Reject => Err(errors::user_message_too_long(
+            message.chars().count(),
+            limit,
+        )),
+        UserMessageOverflow::Truncate => {
+            let mut truncated = payload.clone();
+            truncated.planner_context.user_message = Some(message[..cut].to_string());
+            Ok(Cow::Owned(truncated))
+        }
+    }
+}
+
+/// Require a JSON `Content-Type` (`application/json` or `application/*+json`),
+/// ignoring parameters such as `charset`.
+#[cfg(not(feature = "wasm"))]
+fn ensure_json_content_type(headers: &HeaderMap) -> Result<(), ErrorResponse> {
+    let raw = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok());
+    let essence = raw
+        .and_then(|ct| ct.split(';').next())
+        .map(|ct| ct.trim().to_ascii_lowercase());
+    match essence.as_deref() {
+        Some("application/json") => Ok(()),
+        Some(ct) if ct.starts_with("application/") && ct.ends_with("+json") => Ok(()),
+        _ => Err(errors::unsupported_media_type(raw)),
+    }
 }
 
-fn handle_json_rejection(state: &AppState, rejection: JsonRejection) -> axum::response::Response {
+#[cfg(not(feature = "wasm"))]
+fn handle_json_rejection(
+    state: &AppState,
+    rejection: JsonRejection,
+    request_id: &str,
+) -> axum::response::Response {
     match rejection {
         JsonRejection::BytesRejection(BytesRejection::FailedToBufferBody(
             FailedToBufferBody::LengthLimitError(_),
@@ -904,24 +1945,184 @@ fn handle_json_rejection(state: &AppState, rejection: JsonRejection) -> axum::re
             } else {
                 tracing::warn!("request body exceeded limit but no max_request_bytes configured");
             }
-            let message = match state.max_request_bytes {
-                Some(limit) => format!("Request too large (body exceeded limit {} bytes)", limit),
-                None => "Request too large".to_string(),
-            };
-            let err = ErrorResponse {
-                error_code: 4001,
-                message,
-                http_status: 413,
-                diagnostics: None,
-            };
-            respond_with_error(err)
+            respond_with_error(
+                errors::body_too_large(state.max_request_bytes).with_request_id(request_id),
+            )
         }
         JsonRejection::BytesRejection(bytes) => bytes.into_response(),
         other => other.into_response(),
     }
 }
 
+/// Inflate `Content-Encoding: gzip` bodies of authorized requests ahead of
+/// JSON extraction. The inflated size is held to `request_body_limit` as
+/// well, so a small compressed body cannot expand past the limit.
+#[cfg(feature = "compression")]
+async fn decompress_gzip_body(
+    State(state): State<AppState>,
+    Extension(RequestId(request_id)): Extension<RequestId>,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    use axum::http::header::{CONTENT_ENCODING, CONTENT_LENGTH};
+    use flate2::read::MultiGzDecoder;
+    use std::io::Read;
+
+    let gzip = req
+        .headers()
+        .get(CONTENT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.trim().eq_ignore_ascii_case("gzip"));
+    // Unauthorized requests are never inflated; the handler rejects them.
+    if !gzip || ensure_authorized(req.headers(), state.allowed_tokens.as_ref()).is_err() {
+        return next.run(req).await;
+    }
+
+    let fail = |err: ErrorResponse| respond_with_error(err.with_request_id(&request_id));
+    let limit = request_body_limit(&state);
+    let (mut parts, body) = req.into_parts();
+    let compressed = match axum::body::to_bytes(body, limit).await {
+        Ok(bytes) => bytes,
+        Err(_) => return fail(errors::body_too_large(Some(limit))),
+    };
+    // Read one byte past the limit to tell "exactly at the cap" from "over".
+    // Inflating is CPU-bound, so keep it off the runtime threads.
+    let cap = limit as u64 + 1;
+    let inflated = tokio::task::spawn_blocking(move || {
+        let mut inflated = Vec::new();
+        MultiGzDecoder::new(&compressed[..])
+            .take(cap)
+            .read_to_end(&mut inflated)
+            .map(|_| inflated)
+    })
+    .await
+    .unwrap_or_else(|e| Err(std::io::Error::other(e)));
+    let inflated = match inflated {
+        Ok(inflated) => inflated,
+        Err(e) => return fail(errors::invalid_gzip_body(&e.to_string())),
+    };
+    if inflated.len() > limit {
+        tracing::warn!(limit, "decompressed request body exceeded limit");
+        return fail(errors::body_too_large(Some(limit)));
+    }
+
+    parts.headers.remove(CONTENT_ENCODING);
+    parts.headers.insert(CONTENT_LENGTH, inflated.len().into());
+    next.run(axum::extract::Request::from_parts(
+        parts,
+        axum::body::Body::from(inflated),
+    ))
+    .await
+}
+
+/// gzip response bodies of at least `gzip_min_bytes` when the client sends
+/// `Accept-Encoding: gzip`. Smaller bodies are sent as is, since the gzip
+/// framing would outweigh the saving.
+#[cfg(feature = "compression")]
+async fn compress_gzip_response(
+    State(state): State<AppState>,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    use axum::http::header::{ACCEPT_ENCODING, CONTENT_ENCODING, CONTENT_LENGTH, VARY};
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    let gzip = req
+        .headers()
+        .get_all(ACCEPT_ENCODING)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(accepts_gzip);
+    let resp = next.run(req).await;
+    if !gzip || resp.headers().contains_key(CONTENT_ENCODING) {
+        return resp;
+    }
+
+    let (mut parts, body) = resp.into_parts();
+    let bytes = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            tracing::warn!(error = %e, "failed to buffer response for compression");
+            return StatusCode::INTERNAL_SERVER_ERROR.into_response();
+        }
+    };
+    parts
+        .headers
+        .append(VARY, HeaderValue::from_static("accept-encoding"));
+    if bytes.len() < state.gzip_min_bytes {
+        return axum::response::Response::from_parts(parts, axum::body::Body::from(bytes));
+    }
+    let mut enc = GzEncoder::new(Vec::with_capacity(bytes.len() / 4), Compression::fast());
+    let compressed = match enc.write_all(&bytes).and_then(|_| enc.finish()) {
+        Ok(compressed) => compressed,
+        Err(_) => {
+            return axum::response::Response::from_parts(parts, axum::body::Body::from(bytes))
+        }
+    };
+    parts
+        .headers
+        .insert(CONTENT_ENCODING, HeaderValue::from_static("gzip"));
+    parts
+        .headers
+        .insert(CONTENT_LENGTH, compressed.len().into());
+    axum::response::Response::from_parts(parts, axum::body::Body::from(compressed))
+}
+
+/// Whether an `Accept-Encoding` value lists gzip (or `*`) with a non-zero
+/// quality.
+#[cfg(feature = "compression")]
+fn accepts_gzip(value: &str) -> bool {
+    value.split(',').any(|item| {
+        let mut params = item.split(';');
+        let coding = params.next().unwrap_or("").trim();
+        let q_zero = params.any(|p| {
+            p.trim()
+                .strip_prefix("q=")
+                .and_then(|q| q.trim().parse::<f32>().ok())
+                .is_some_and(|q| q == 0.0)
+        });
+        (coding.eq_ignore_ascii_case("gzip") || coding == "*") && !q_zero
+    })
+}
+
+/// `SENTRA_STRICT_SCHEMA`: check the raw body against the request schema
+/// before the typed extractor sees it. Unauthorized, non-JSON and
+/// unparseable requests pass through so the handler rejects them as usual.
+#[cfg(feature = "strict-schema")]
+async fn validate_schema(
+    State(state): State<AppState>,
+    Extension(RequestId(request_id)): Extension<RequestId>,
+    req: axum::extract::Request,
+    next: axum::middleware::Next,
+) -> axum::response::Response {
+    if !state.strict_schema
+        || ensure_authorized(req.headers(), state.allowed_tokens.as_ref()).is_err()
+        || ensure_json_content_type(req.headers()).is_err()
+    {
+        return next.run(req).await;
+    }
+    let fail = |err: ErrorResponse| respond_with_error(err.with_request_id(&request_id));
+    let (parts, body) = req.into_parts();
+    let limit = request_body_limit(&state);
+    let bytes = match axum::body::to_bytes(body, limit).await {
+        Ok(bytes) => bytes,
+        Err(_) => return fail(errors::body_too_large(Some(limit))),
+    };
+    if let Ok(value) = serde_json::from_slice::<serde_json::Value>(&bytes) {
+        if let Err(violations) = schema::validate(&value) {
+            return fail(errors::schema_violations(&violations));
+        }
+    }
+    next.run(axum::extract::Request::from_parts(
+        parts,
+        axum::body::Body::from(bytes),
+    ))
+    .await
+}
+
 /// Simple health endpoint for container readiness / liveness checks.
+#[cfg(not(feature = "wasm"))]
 async fn healthz_handler(State(state): State<AppState>) -> axum::response::Response {
     let json = serde_json::json!({
         "status": "ok",
@@ -932,14 +2133,160 @@ async fn healthz_handler(State(state): State<AppState>) -> axum::response::Respo
     (StatusCode::OK, Json(json)).into_response()
 }
 
-/// Prometheus-style metrics exposition. Text format with simple counters.
-async fn metrics_handler(State(state): State<AppState>) -> axum::response::Response {
+/// Reason code catalog: built-in codes plus those configured for policy
+/// rules, external services and custom exfil patterns.
+#[cfg(not(feature = "wasm"))]
+async fn reason_codes_handler(State(state): State<AppState>) -> axum::response::Response {
+    let catalog = plugins::reasons::catalog(&state.plugin_config);
+    (StatusCode::OK, Json(catalog)).into_response()
+}
+
+/// Escape a Prometheus label value: backslash, double quote and newline
+/// would otherwise end the label or the sample line early.
+#[cfg(feature = "metrics")]
+fn escape_label(value: &str) -> std::borrow::Cow<'_, str> {
+    if !value.contains(['\\', '"', '\n']) {
+        return std::borrow::Cow::Borrowed(value);
+    }
+    let mut out = String::with_capacity(value.len() + 2);
+    for c in value.chars() {
+        match c {
+            '\\' => out.push_str("\\\\"),
+            '"' => out.push_str("\\\""),
+            '\n' => out.push_str("\\n"),
+            c => out.push(c),
+        }
+    }
+    std::borrow::Cow::Owned(out)
+}
+
+/// Cumulative histogram as JSON: `buckets` maps each upper bound (and
+/// `+Inf`) to the observations at or below it, as in the text format.
+#[cfg(feature = "metrics")]
+fn histogram_json(bounds: &[u64], counts: &[AtomicU64], sum: u64, count: u64) -> serde_json::Value {
+    let mut buckets = serde_json::Map::new();
+    let mut cumulative = 0;
+    for (ub, c) in bounds.iter().zip(counts) {
+        cumulative += c.load(Ordering::Relaxed);
+        buckets.insert(ub.to_string(), cumulative.into());
+    }
+    buckets.insert("+Inf".into(), count.into());
+    serde_json::json!({ "buckets": buckets, "sum": sum, "count": count })
+}
+
+/// The `/metrics` series as one JSON object, read from the same atomics as
+/// the text format. Per-plugin series sit under `plugins.<name>`.
+#[cfg(feature = "metrics")]
+fn metrics_json(state: &AppState) -> serde_json::Value {
+    let load = |a: &AtomicU64| a.load(Ordering::Relaxed);
+    let decision = |hist: &DecisionHistogram| {
+        histogram_json(
+            &state.hist_buckets,
+            &hist.counts,
+            load(&hist.sum_ms),
+            load(&hist.count),
+        )
+    };
+    let mut plugins = serde_json::Map::new();
+    for name in state.plugin_metric_names.iter() {
+        let idx = state.plugin_metric_indices.get(name);
+        let Some(pm) = idx.and_then(|idx| state.plugin_metrics.get(*idx)) else {
+            continue;
+        };
+        let mut reasons: serde_json::Map<String, serde_json::Value> = pm
+            .block_reasons
+            .iter()
+            .map(|e| (e.key().to_string(), load(e.value()).into()))
+            .collect();
+        let other = load(&pm.block_reasons_other);
+        if other > 0 {
+            reasons.insert("other".into(), other.into());
+        }
+        plugins.insert(
+            name.clone(),
+            serde_json::json!({
+                "eval_ms_sum": load(&pm.eval_sum_ms),
+                "eval_ms_count": load(&pm.eval_count),
+                "blocks_total": load(&pm.block_count),
+                "allows_total": load(&pm.allow_count),
+                "panics_total": load(&pm.panic_count),
+                "timeouts_total": load(&pm.timeout_count),
+                "monitored_total": load(&pm.monitored_count),
+                "block_reason_total": reasons,
+                "latency_ms": histogram_json(
+                    &state.hist_buckets,
+                    &pm.hist_counts,
+                    load(&pm.hist_sum_ms),
+                    load(&pm.hist_count),
+                ),
+            }),
+        );
+    }
+    let telemetry = &state.telemetry;
+    let write_ms = telemetry.write_ms();
+    serde_json::json!({
+        "requests_total": load(&state.metric_requests_total),
+        "blocks_total": load(&state.metric_blocks_total),
+        "audit_suppressed_total": load(&state.metric_audit_suppressed_total),
+        "inflight_requests": load(&state.metric_inflight_requests),
+        "block_rate_ewma": state.metric_block_rate_ewma.get(),
+        "policy_rules_loaded": state.policy_rules_loaded,
+        "telemetry_lines_total": load(telemetry.lines_total()),
+        "telemetry_write_errors_total": load(telemetry.write_errors_total()),
+        "telemetry_dropped_total": load(telemetry.dropped_total()),
+        "telemetry_write_ms": histogram_json(
+            TELEMETRY_WRITE_BUCKETS,
+            &write_ms.counts,
+            load(&write_ms.sum_ms),
+            load(&write_ms.count),
+        ),
+        "request_latency_ms": histogram_json(
+            &state.hist_buckets,
+            &state.hist_counts,
+            load(&state.hist_sum_ms),
+            load(&state.hist_count),
+        ),
+        "decision_latency_ms": {
+            "allow": decision(&state.allow_hist),
+            "block": decision(&state.block_hist),
+        },
+        "log_file_size_bytes": load(telemetry.log_file_size_bytes()),
+        "build_info": { "version": env!("CARGO_PKG_VERSION"), "schemaVersion": "1" },
+        "process_start_time_seconds": state.process_start_epoch,
+        "process_uptime_seconds": state.process_start_instant.elapsed().as_secs_f64(),
+        "plugins": plugins,
+    })
+}
+
+/// Prometheus-style metrics exposition. Text format with simple counters;
+/// clients sending `Accept: application/json` get `metrics_json` instead.
+#[cfg(feature = "metrics")]
+async fn metrics_handler(
+    State(state): State<AppState>,
+    headers: axum::http::HeaderMap,
+) -> axum::response::Response {
+    if let Some(expected) = state.metrics_token.as_deref() {
+        match extract_bearer_token(&headers) {
+            Ok(token) if token == expected => {}
+            Ok(_) => return respond_with_error(errors::unauthorized()),
+            Err(err) => return respond_with_error(err),
+        }
+    }
+    let wants_json = headers
+        .get_all(axum::http::header::ACCEPT)
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .any(|v| v.to_ascii_lowercase().contains("application/json"));
+    if wants_json {
+        return (StatusCode::OK, Json(metrics_json(&state))).into_response();
+    }
     // Histogram exposition
     let mut buf = String::new();
     use std::fmt::Write as _;
     let requests = state.metric_requests_total.load(Ordering::Relaxed);
     let blocks = state.metric_blocks_total.load(Ordering::Relaxed);
     let suppressed = state.metric_audit_suppressed_total.load(Ordering::Relaxed);
+    let inflight = state.metric_inflight_requests.load(Ordering::Relaxed);
     let telem = state.telemetry.lines_total().load(Ordering::Relaxed);
     let telem_errs = state.telemetry.write_errors_total().load(Ordering::Relaxed);
     let sum_ms = state.hist_sum_ms.load(Ordering::Relaxed);
@@ -970,6 +2317,37 @@ async fn metrics_handler(State(state): State<AppState>) -> axum::response::Respo
     .ok();
     writeln!(&mut buf, "# TYPE sentra_audit_suppressed_total counter").ok();
     writeln!(&mut buf, "sentra_audit_suppressed_total {}", suppressed).ok();
+    writeln!(
+        &mut buf,
+        "# HELP sentra_inflight_requests Analyze requests currently being processed"
+    )
+    .ok();
+    writeln!(&mut buf, "# TYPE sentra_inflight_requests gauge").ok();
+    writeln!(&mut buf, "sentra_inflight_requests {}", inflight).ok();
+    writeln!(
+        &mut buf,
+        "# HELP sentra_block_rate_ewma Exponentially weighted block rate over recent decisions (alpha {})\n# TYPE sentra_block_rate_ewma gauge",
+        BLOCK_RATE_EWMA_ALPHA
+    )
+    .ok();
+    writeln!(
+        &mut buf,
+        "sentra_block_rate_ewma {:.6}",
+        state.metric_block_rate_ewma.get()
+    )
+    .ok();
+    writeln!(
+        &mut buf,
+        "# HELP sentra_policy_rules_loaded Policy rules loaded by policy_pack (after maxPolicyRules)"
+    )
+    .ok();
+    writeln!(&mut buf, "# TYPE sentra_policy_rules_loaded gauge").ok();
+    writeln!(
+        &mut buf,
+        "sentra_policy_rules_loaded {}",
+        state.policy_rules_loaded
+    )
+    .ok();
     writeln!(
         &mut buf,
         "# HELP sentra_telemetry_lines_total Telemetry/audit JSON lines written"
@@ -993,6 +2371,47 @@ async fn metrics_handler(State(state): State<AppState>) -> axum::response::Respo
         telem_errs
     )
     .ok();
+    writeln!(
+        &mut buf,
+        "# HELP sentra_telemetry_dropped_total Telemetry/audit lines dropped because the write queue was full\n# TYPE sentra_telemetry_dropped_total counter"
+    )
+    .ok();
+    writeln!(
+        &mut buf,
+        "sentra_telemetry_dropped_total {}",
+        state.telemetry.dropped_total().load(Ordering::Relaxed)
+    )
+    .ok();
+    writeln!(
+        &mut buf,
+        "# HELP sentra_telemetry_write_ms Telemetry/audit line write latency histogram (ms)\n# TYPE sentra_telemetry_write_ms histogram"
+    )
+    .ok();
+    let write_ms = state.telemetry.write_ms();
+    let mut cumulative: u64 = 0;
+    for (i, ub) in TELEMETRY_WRITE_BUCKETS.iter().enumerate() {
+        cumulative += write_ms.counts[i].load(Ordering::Relaxed);
+        writeln!(
+            &mut buf,
+            "sentra_telemetry_write_ms_bucket{{le=\"{}\"}} {}",
+            ub, cumulative
+        )
+        .ok();
+    }
+    let write_count = write_ms.count.load(Ordering::Relaxed);
+    writeln!(
+        &mut buf,
+        "sentra_telemetry_write_ms_bucket{{le=\"+Inf\"}} {}",
+        write_count
+    )
+    .ok();
+    writeln!(
+        &mut buf,
+        "sentra_telemetry_write_ms_sum {}",
+        write_ms.sum_ms.load(Ordering::Relaxed)
+    )
+    .ok();
+    writeln!(&mut buf, "sentra_telemetry_write_ms_count {}", write_count).ok();
     // Histogram
     writeln!(
         &mut buf,
@@ -1020,6 +2439,43 @@ async fn metrics_handler(State(state): State<AppState>) -> axum::response::Respo
     .ok();
     writeln!(&mut buf, "sentra_request_latency_ms_sum {}", sum_ms).ok();
     writeln!(&mut buf, "sentra_request_latency_ms_count {}", count).ok();
+    writeln!(
+        &mut buf,
+        "# HELP sentra_decision_latency_ms Request latency histogram milliseconds by decision\n# TYPE sentra_decision_latency_ms histogram"
+    )
+    .ok();
+    for (decision, hist) in [("allow", &state.allow_hist), ("block", &state.block_hist)] {
+        let mut cumulative: u64 = 0;
+        for (i, ub) in state.hist_buckets.iter().enumerate() {
+            cumulative += hist.counts[i].load(Ordering::Relaxed);
+            writeln!(
+                &mut buf,
+                "sentra_decision_latency_ms_bucket{{decision=\"{}\",le=\"{}\"}} {}",
+                decision, ub, cumulative
+            )
+            .ok();
+        }
+        let dcount = hist.count.load(Ordering::Relaxed);
+        writeln!(
+            &mut buf,
+            "sentra_decision_latency_ms_bucket{{decision=\"{}\",le=\"+Inf\"}} {}",
+            decision, dcount
+        )
+        .ok();
+        writeln!(
+            &mut buf,
+            "sentra_decision_latency_ms_sum{{decision=\"{}\"}} {}",
+            decision,
+            hist.sum_ms.load(Ordering::Relaxed)
+        )
+        .ok();
+        writeln!(
+            &mut buf,
+            "sentra_decision_latency_ms_count{{decision=\"{}\"}} {}",
+            decision, dcount
+        )
+        .ok();
+    }
     // Build info gauge (value 1)
     writeln!(
         &mut buf,
@@ -1045,12 +2501,37 @@ async fn metrics_handler(State(state): State<AppState>) -> axum::response::Respo
         &mut buf,
         "# HELP sentra_plugin_blocks_total Blocking decisions per plugin (would-be blocks)\n# TYPE sentra_plugin_blocks_total counter"
     ).ok();
+    writeln!(
+        &mut buf,
+        "# HELP sentra_plugin_allows_total Explicit non-blocking decisions per plugin (abstentions not counted)\n# TYPE sentra_plugin_allows_total counter"
+    ).ok();
+    writeln!(
+        &mut buf,
+        "# HELP sentra_plugin_panics_total Plugin evaluations that panicked (caught; see panicFailClosed)\n# TYPE sentra_plugin_panics_total counter"
+    ).ok();
+    writeln!(
+        &mut buf,
+        "# HELP sentra_plugin_timeouts_total Plugin evaluations cut off by pluginTimeouts\n# TYPE sentra_plugin_timeouts_total counter"
+    ).ok();
+    writeln!(
+        &mut buf,
+        "# HELP sentra_plugin_monitored_total Would-block responses from plugins in their monitor window (not enforced)\n# TYPE sentra_plugin_monitored_total counter"
+    ).ok();
+    writeln!(
+        &mut buf,
+        "# HELP sentra_plugin_block_reason_total Blocking decisions per plugin and reason code (would-be blocks)\n# TYPE sentra_plugin_block_reason_total counter"
+    ).ok();
     writeln!(
         &mut buf,
         "# HELP sentra_plugin_latency_ms Plugin evaluation latency histogram (ms) per plugin\n# TYPE sentra_plugin_latency_ms histogram"
     ).ok();
-    for (name, idx) in state.plugin_metric_indices.iter() {
-        if let Some(pm) = state.plugin_metrics.get(*idx) {
+    // Declared order, so scrapes list plugins identically every time.
+    for name in state.plugin_metric_names.iter() {
+        let idx = state.plugin_metric_indices.get(name);
+        if let Some(pm) = idx.and_then(|idx| state.plugin_metrics.get(*idx)) {
+            // External plugin names come from config and may contain
+            // characters that are special inside a label value.
+            let name = escape_label(name);
             let sum = pm.eval_sum_ms.load(Ordering::Relaxed);
             let c = pm.eval_count.load(Ordering::Relaxed);
             let b = pm.block_count.load(Ordering::Relaxed);
@@ -1072,6 +2553,57 @@ async fn metrics_handler(State(state): State<AppState>) -> axum::response::Respo
                 name, b
             )
             .ok();
+            writeln!(
+                &mut buf,
+                "sentra_plugin_allows_total{{plugin=\"{}\"}} {}",
+                name,
+                pm.allow_count.load(Ordering::Relaxed)
+            )
+            .ok();
+            writeln!(
+                &mut buf,
+                "sentra_plugin_panics_total{{plugin=\"{}\"}} {}",
+                name,
+                pm.panic_count.load(Ordering::Relaxed)
+            )
+            .ok();
+            writeln!(
+                &mut buf,
+                "sentra_plugin_timeouts_total{{plugin=\"{}\"}} {}",
+                name,
+                pm.timeout_count.load(Ordering::Relaxed)
+            )
+            .ok();
+            writeln!(
+                &mut buf,
+                "sentra_plugin_monitored_total{{plugin=\"{}\"}} {}",
+                name,
+                pm.monitored_count.load(Ordering::Relaxed)
+            )
+            .ok();
+            let mut reasons: Vec<(i32, u64)> = pm
+                .block_reasons
+                .iter()
+                .map(|e| (*e.key(), e.value().load(Ordering::Relaxed)))
+                .collect();
+            reasons.sort_unstable();
+            for (code, count) in reasons {
+                writeln!(
+                    &mut buf,
+                    "sentra_plugin_block_reason_total{{plugin=\"{}\",reason_code=\"{}\"}} {}",
+                    name, code, count
+                )
+                .ok();
+            }
+            let other = pm.block_reasons_other.load(Ordering::Relaxed);
+            if other > 0 {
+                writeln!(
+                    &mut buf,
+                    "sentra_plugin_block_reason_total{{plugin=\"{}\",reason_code=\"other\"}} {}",
+                    name, other
+                )
+                .ok();
+            }
             // Per-plugin histogram buckets
             let mut cumulative: u64 = 0;
             for (i, ub) in state.hist_buckets.iter().enumerate() {
@@ -0,0 +1,83 @@
+//! Runtime-free evaluation with the local (non-network) plugins, for callers
+//! that pre-screen requests before they reach a Sentra server, e.g. from a
+//! CLI, a worker without a tokio runtime, or a browser/edge module.
+//!
+//! With the `wasm` feature (and `--no-default-features`) the crate builds
+//! only this path, the plugins and the request types, leaving out tokio,
+//! axum and reqwest, so it compiles for `wasm32-wasip1`. Plugin deadlines
+//! read `std::time::Instant`, which `wasm32-unknown-unknown` does not
+//! provide.
+
+use std::future::Future;
+
+use futures_util::FutureExt;
+
+use crate::plugins::{Diagnostics, Evaluation, PluginConfig, PluginPipeline};
+use crate::util::EvalContext;
+use crate::{AnalyzeRequest, AnalyzeResponse};
+
+/// Plugins run by `evaluate_local`, in order. None of them performs I/O, so
+/// their futures complete on first poll.
+pub const LOCAL_PLUGINS: &[&str] = &[
+    "tool_denylist",
+    "exfil",
+    "secrets",
+    "pii",
+    "domain_block",
+    "policy_pack",
+];
+
+/// Budget for the local pass; generous because nothing here can block.
+const LOCAL_BUDGET_MS: u64 = 900;
+
+/// Build the `LOCAL_PLUGINS` pipeline for `cfg`. Build it once and reuse it
+/// across `evaluate_local` calls: construction compiles the configured
+/// patterns and policy rules.
+pub fn local_pipeline(cfg: &PluginConfig) -> PluginPipeline {
+    let order: Vec<String> = LOCAL_PLUGINS.iter().map(|p| p.to_string()).collect();
+    PluginPipeline::new(&order, cfg)
+}
+
+/// Evaluate `req` against `pipeline` (from `local_pipeline`, built with the
+/// same `cfg`) synchronously: first block wins, as in the server pipeline.
+/// Needs no async runtime.
+pub fn evaluate_local(
+    req: &AnalyzeRequest,
+    pipeline: &PluginPipeline,
+    cfg: &PluginConfig,
+) -> AnalyzeResponse {
+    let ctx = EvalContext::from_request(req, cfg, LOCAL_BUDGET_MS, LOCAL_BUDGET_MS);
+    settle(pipeline.evaluate(req, &ctx, cfg, true))
+}
+
+/// Poll `evaluation` once. A local plugin that would have to wait has no
+/// runtime to wake it, so the request fails closed (reason code 900) rather
+/// than passing unchecked.
+fn settle(evaluation: impl Future<Output = Evaluation>) -> AnalyzeResponse {
+    match evaluation.now_or_never() {
+        Some(evaluation) => evaluation.response,
+        None => {
+            tracing::error!("local evaluation did not complete without awaiting");
+            AnalyzeResponse {
+                block_action: true,
+                reason_code: Some(900),
+                reason: Some("Plugin failure (fail-closed)".into()),
+                blocked_by: Some("local".into()),
+                diagnostics: Some(Diagnostics::new("local", "pending").into()),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_evaluation_fails_closed() {
+        let resp = settle(futures_util::future::pending());
+        assert!(resp.block_action);
+        assert_eq!(resp.reason_code, Some(900));
+        assert_eq!(resp.diagnostics.unwrap()["code"], "pending");
+    }
+}
@@ -1,10 +1,25 @@
+#[cfg(not(feature = "wasm"))]
 use std::env;
+#[cfg(not(feature = "wasm"))]
+use std::path::PathBuf;
 
-use sentra::{app, build_state_from_env};
+#[cfg(not(feature = "wasm"))]
+use sentra::{app, build_state_from_env, server, ServerOptions};
+#[cfg(not(feature = "wasm"))]
 use tokio::net::TcpListener;
+#[cfg(not(feature = "wasm"))]
 use tokio::signal;
+#[cfg(not(feature = "wasm"))]
 use tracing_subscriber::{fmt, EnvFilter};
 
+/// A `wasm` build is the library core only; there is no server to run.
+#[cfg(feature = "wasm")]
+fn main() {
+    eprintln!("sentra was built with the `wasm` feature, which leaves out the server");
+    std::process::exit(1);
+}
+
+#[cfg(not(feature = "wasm"))]
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Initialise structured logging. Reads RUST_LOG environment variable.
@@ -15,6 +30,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Build application state from environment variables and optional config
     let state = build_state_from_env().await?;
+    #[cfg(feature = "grpc")]
+    spawn_grpc(&state);
     let app = app(state);
 
     // Determine port to bind on. Default to 8080 if unspecified.
@@ -24,15 +41,71 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .unwrap_or(8080);
     let addr: std::net::SocketAddr = ([0, 0, 0, 0], port).into();
 
+    // Optional built-in TLS. Unreadable or half-configured certificates
+    // abort startup instead of silently falling back to plain HTTP.
+    #[cfg(feature = "tls")]
+    let tls = match tls_paths()? {
+        Some((cert, key)) => Some(sentra::tls::load_config(&cert, &key)?),
+        None => None,
+    };
+    #[cfg(not(feature = "tls"))]
+    if tls_paths()?.is_some() {
+        return Err("SENTRA_TLS_CERT is set but Sentra was built without the tls feature".into());
+    }
+
+    let options = ServerOptions::from_env()?;
+
     // Run the server with graceful shutdown on Ctrl+C
     let listener = TcpListener::bind(addr).await?;
-    tracing::info!("listening on {}", addr);
-    axum::serve(listener, app)
-        .with_graceful_shutdown(shutdown_signal())
-        .await?;
+    #[cfg(feature = "tls")]
+    if let Some(config) = tls {
+        tracing::info!(http2 = options.http2, "listening on {} (TLS)", addr);
+        server::serve_tls(listener, app, &options, config, shutdown_signal()).await;
+        return Ok(());
+    }
+    tracing::info!(http2 = options.http2, "listening on {}", addr);
+    server::serve(listener, app, &options, shutdown_signal()).await;
     Ok(())
 }
 
+/// Certificate and key paths from `SENTRA_TLS_CERT` / `SENTRA_TLS_KEY`,
+/// which must be set together.
+#[cfg(not(feature = "wasm"))]
+fn tls_paths() -> Result<Option<(PathBuf, PathBuf)>, String> {
+    match (
+        env::var_os("SENTRA_TLS_CERT"),
+        env::var_os("SENTRA_TLS_KEY"),
+    ) {
+        (Some(cert), Some(key)) => Ok(Some((cert.into(), key.into()))),
+        (None, None) => Ok(None),
+        _ => Err("SENTRA_TLS_CERT and SENTRA_TLS_KEY must be set together".into()),
+    }
+}
+
+/// Serve `sentra.v1.Sentra` next to HTTP when `SENTRA_GRPC_PORT` is set.
+#[cfg(feature = "grpc")]
+fn spawn_grpc(state: &sentra::AppState) {
+    let Some(port) = env::var("SENTRA_GRPC_PORT")
+        .ok()
+        .and_then(|s| s.parse::<u16>().ok())
+    else {
+        return;
+    };
+    let addr: std::net::SocketAddr = ([0, 0, 0, 0], port).into();
+    let service = sentra::grpc::service(state.clone());
+    tracing::info!("gRPC listening on {}", addr);
+    tokio::spawn(async move {
+        if let Err(e) = tonic::transport::Server::builder()
+            .add_service(service)
+            .serve_with_shutdown(addr, shutdown_signal())
+            .await
+        {
+            tracing::error!(error = %e, "gRPC server failed");
+        }
+    });
+}
+
+#[cfg(not(feature = "wasm"))]
 async fn shutdown_signal() {
     // Wait for Ctrl+C
     let _ = signal::ctrl_c().await;
@@ -0,0 +1,147 @@
+use super::pii::PiiPlugin;
+use super::secrets::SecretsPlugin;
+use super::{DiagnosticsLevel, Plugin, PluginConfig};
+use crate::util::{request_texts, EvalContext, Precomputed};
+use crate::{AnalyzeRequest, AnalyzeResponse, PlannerContext};
+use base64::engine::general_purpose::{STANDARD, STANDARD_NO_PAD, URL_SAFE, URL_SAFE_NO_PAD};
+use base64::Engine;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Decodes base64 tokens found in the request and re-runs the secrets and
+/// PII checks on the decoded text, catching credentials or addresses that
+/// were encoded to slip past the literal scanners.
+#[derive(Default)]
+pub struct Base64DecodePlugin {
+    pii: PiiPlugin,
+}
+
+impl Base64DecodePlugin {
+    pub fn new(cfg: &PluginConfig) -> Self {
+        Base64DecodePlugin {
+            pii: PiiPlugin::new(cfg),
+        }
+    }
+}
+
+/// Shortest token considered; shorter runs are mostly ordinary words.
+const MIN_TOKEN_LEN: usize = 16;
+/// Longer tokens (embedded files, images) are skipped rather than decoded.
+const MAX_TOKEN_LEN: usize = 4096;
+/// Total decoded bytes examined per request.
+const MAX_DECODED_BYTES: usize = 64 * 1024;
+
+static B64_TOKEN_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"[A-Za-z0-9+/_-]{16,}={0,2}").unwrap());
+
+fn decode_token(token: &str) -> Option<String> {
+    [&STANDARD, &STANDARD_NO_PAD, &URL_SAFE, &URL_SAFE_NO_PAD]
+        .iter()
+        .find_map(|engine| engine.decode(token).ok())
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+}
+
+/// Collect the UTF-8 decodings of base64 tokens in `texts`, stopping once
+/// `MAX_DECODED_BYTES` is reached.
+fn decoded_segments<'a>(texts: impl Iterator<Item = &'a str>) -> Vec<String> {
+    let mut out = Vec::new();
+    let mut budget = MAX_DECODED_BYTES;
+    for text in texts {
+        for m in B64_TOKEN_RE.find_iter(text) {
+            let token = m.as_str();
+            if token.len() < MIN_TOKEN_LEN || token.len() > MAX_TOKEN_LEN {
+                continue;
+            }
+            if let Some(decoded) = decode_token(token) {
+                if decoded.len() > budget {
+                    return out;
+                }
+                budget -= decoded.len();
+                out.push(decoded);
+            }
+        }
+    }
+    out
+}
+
+#[async_trait::async_trait]
+impl Plugin for Base64DecodePlugin {
+    fn name(&self) -> &str {
+        "base64_decode"
+    }
+
+    async fn eval(
+        &self,
+        req: &AnalyzeRequest,
+        ctx: &EvalContext,
+        cfg: &PluginConfig,
+    ) -> Option<AnalyzeResponse> {
+        // Base64 is case-sensitive, so decode from the original request text
+        // rather than the lower-cased `Precomputed` copy.
+        let segments = decoded_segments(request_texts(req).into_iter());
+        if segments.is_empty() {
+            return None;
+        }
+        let decoded = segments.join(" ");
+        // Inner plugins see a request whose only text is the decoded payload.
+        let decoded_req = AnalyzeRequest {
+            planner_context: PlannerContext {
+                user_message: Some(decoded),
+                ..PlannerContext::default()
+            },
+            tool_definition: req.tool_definition.clone(),
+            ..AnalyzeRequest::default()
+        };
+        let mut decoded_pre = Precomputed::from_request_message(
+            decoded_req.planner_context.user_message.as_deref(),
+            None,
+            &decoded_req.input_values,
+        );
+        if cfg.normalize_unicode {
+            decoded_pre.normalize_unicode();
+        }
+        let decoded_ctx = EvalContext {
+            pre: Arc::new(decoded_pre),
+            deadline: ctx.deadline.clone(),
+            plugin_warn_ms: ctx.plugin_warn_ms,
+            trusted: ctx.trusted,
+        };
+        let inner: [&dyn Plugin; 2] = [&SecretsPlugin, &self.pii];
+        for plugin in inner {
+            if let Some(mut resp) = plugin.eval(&decoded_req, &decoded_ctx, cfg).await {
+                if !resp.block_action {
+                    continue;
+                }
+                resp.blocked_by = Some(self.name().to_string());
+                match resp.diagnostics.as_mut() {
+                    Some(Value::Object(diag))
+                        if cfg.diagnostics_level != DiagnosticsLevel::Minimal =>
+                    {
+                        diag.insert("encoding".into(), Value::from("base64"));
+                    }
+                    _ => {}
+                }
+                return Some(resp);
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_non_utf8_and_oversized_tokens() {
+        // Valid base64 of bytes that are not UTF-8.
+        let binary = STANDARD.encode([
+            0xff_u8, 0xfe, 0xfd, 0xfc, 0xfb, 0xfa, 0xf9, 0xf8, 0xf7, 0xf6, 0xf5, 0xf4,
+        ]);
+        assert!(decoded_segments([binary.as_str()].into_iter()).is_empty());
+
+        let huge = STANDARD.encode("A".repeat(MAX_TOKEN_LEN));
+        assert!(decoded_segments([huge.as_str()].into_iter()).is_empty());
+    }
+}
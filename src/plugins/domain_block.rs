@@ -1,8 +1,10 @@
-use super::{Plugin, PluginConfig};
-use crate::util::EvalContext;
+use super::{Diagnostics, Plugin, PluginConfig};
+use crate::util::{extract_urls, EvalContext};
 use crate::{AnalyzeRequest, AnalyzeResponse};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
-fn domain_in_text(text: &str, domains: &[String]) -> Option<(String, String)> {
+/// Returns the matched domain and its byte offset within `text`.
+fn domain_in_text(text: &str, domains: &[String]) -> Option<(String, usize)> {
     for domain in domains {
         let mut search_start = 0;
         while let Some(rel) = text[search_start..].find(domain) {
@@ -28,7 +30,7 @@ fn domain_in_text(text: &str, domains: &[String]) -> Option<(String, String)> {
                 .unwrap_or(true);
 
             if before_ok && after_ok {
-                return Some((domain.clone(), format!("pos:{}", abs_start)));
+                return Some((domain.clone(), abs_start));
             }
 
             search_start = abs_end;
@@ -37,9 +39,164 @@ fn domain_in_text(text: &str, domains: &[String]) -> Option<(String, String)> {
     None
 }
 
+/// Returns true if `host` is `domain` or one of its subdomains.
+fn host_matches(host: &str, domain: &str) -> bool {
+    let host = host.trim_end_matches('.');
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
+
+/// Blocklist entry equal to an IP-literal URL host. Entries may be written
+/// with or without brackets and in any IPv6 notation
+/// (`[2001:db8::1]`, `2001:0db8:0:0:0:0:0:1`).
+fn ip_entry_matches(ip: IpAddr, domains: &[String]) -> Option<&String> {
+    domains.iter().find(|d| {
+        d.trim_start_matches('[')
+            .trim_end_matches(']')
+            .parse::<IpAddr>()
+            .is_ok_and(|entry| entry == ip)
+    })
+}
+
+/// Where a blocklisted domain was found.
+enum DomainHit {
+    /// Host component of a parsed URL.
+    Host { domain: String, host: String },
+    /// Boundary-aware substring match at the given byte offset.
+    Text { domain: String, pos: usize },
+}
+
+/// Check `text` for blocklisted domains. URLs are parsed and compared on
+/// their host, which also sees through percent-encoded or otherwise
+/// obfuscated hosts. With `host_only`, URL spans are then blanked so that
+/// paths and query strings mentioning a domain do not trigger the substring
+/// scan; the rest of the text is still scanned.
+fn scan_text(text: &str, domains: &[String], host_only: bool) -> Option<DomainHit> {
+    let urls = extract_urls(text);
+    for candidate in &urls {
+        let Ok(parsed) = url::Url::parse(candidate) else {
+            continue;
+        };
+        // Matching is on the parsed host only: userinfo, port, path and
+        // query never count as the host.
+        let found = match parsed.host() {
+            Some(url::Host::Domain(host)) => domains.iter().find(|d| host_matches(host, d)),
+            Some(url::Host::Ipv4(v4)) => ip_entry_matches(IpAddr::V4(v4), domains),
+            Some(url::Host::Ipv6(v6)) => ip_entry_matches(IpAddr::V6(v6), domains),
+            None => None,
+        };
+        if let Some(domain) = found {
+            return Some(DomainHit::Host {
+                domain: domain.clone(),
+                host: parsed.host_str().unwrap_or_default().to_string(),
+            });
+        }
+    }
+    let hit = if host_only && !urls.is_empty() {
+        // Blank with spaces of equal length so offsets stay valid.
+        let mut blanked = text.to_string();
+        for candidate in &urls {
+            let start = candidate.as_ptr() as usize - text.as_ptr() as usize;
+            blanked.replace_range(start..start + candidate.len(), &" ".repeat(candidate.len()));
+        }
+        domain_in_text(&blanked, domains)
+    } else {
+        domain_in_text(text, domains)
+    };
+    hit.map(|(domain, pos)| DomainHit::Text { domain, pos })
+}
+
+fn domain_response(hit: DomainHit, cfg: &PluginConfig) -> AnalyzeResponse {
+    let diag = Diagnostics::for_config("domain_block", "domain", cfg);
+    let diag = match hit {
+        DomainHit::Host { domain, host } => diag.detail(domain).with("host", host.into()),
+        DomainHit::Text { domain, pos } => diag.offsets(pos, pos + domain.len()).detail(domain),
+    };
+    AnalyzeResponse {
+        block_action: true,
+        reason_code: Some(113),
+        reason: Some("Input contains disallowed domain.".into()),
+        blocked_by: Some("domain_block".into()),
+        diagnostics: Some(diag.into()),
+    }
+}
+
+/// Classify an IP literal host. Anything other than `public` is considered
+/// internal for `block_private_ips`.
+fn classify_ip(ip: IpAddr) -> &'static str {
+    match ip {
+        IpAddr::V4(v4) => classify_v4(v4),
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return classify_v4(v4);
+            }
+            let first = v6.segments()[0];
+            if v6 == Ipv6Addr::new(0xfd00, 0xec2, 0, 0, 0, 0, 0, 0x254) {
+                "metadata"
+            } else if v6.is_loopback() {
+                "loopback"
+            } else if v6.is_unspecified() {
+                "unspecified"
+            } else if first & 0xffc0 == 0xfe80 {
+                "link_local"
+            } else if first & 0xfe00 == 0xfc00 {
+                "unique_local"
+            } else {
+                "public"
+            }
+        }
+    }
+}
+
+fn classify_v4(v4: Ipv4Addr) -> &'static str {
+    let [a, b, ..] = v4.octets();
+    if v4 == Ipv4Addr::new(169, 254, 169, 254) || v4 == Ipv4Addr::new(100, 100, 100, 200) {
+        "metadata"
+    } else if v4.is_loopback() {
+        "loopback"
+    } else if v4.is_unspecified() {
+        "unspecified"
+    } else if v4.is_link_local() {
+        "link_local"
+    } else if v4.is_private() || (a == 100 && (64..128).contains(&b)) {
+        "private"
+    } else {
+        "public"
+    }
+}
+
+/// Find the first URL whose host is an IP literal disallowed by config.
+/// Hosts are normalised by the `url` crate, so alternate encodings such as
+/// `http://0xA9FEA9FE/` resolve to their dotted form.
+fn ip_literal_violation(urls: &[String], cfg: &PluginConfig) -> Option<(IpAddr, &'static str)> {
+    if !cfg.block_private_ips && !cfg.block_ip_literals {
+        return None;
+    }
+    for text in urls {
+        for candidate in extract_urls(text) {
+            let Ok(parsed) = url::Url::parse(candidate) else {
+                continue;
+            };
+            let ip = match parsed.host() {
+                Some(url::Host::Ipv4(v4)) => IpAddr::V4(v4),
+                Some(url::Host::Ipv6(v6)) => IpAddr::V6(v6),
+                _ => continue,
+            };
+            let class = classify_ip(ip);
+            if cfg.block_ip_literals || class != "public" {
+                return Some((ip, class));
+            }
+        }
+    }
+    None
+}
+
 /// Blocks any request containing substrings from a configured domain
-/// blocklist.  Domains should be lower case.  Both URLs and arbitrary
-/// strings in `inputValues` are scanned.
+/// blocklist (compared case-insensitively).  Both URLs and arbitrary
+/// strings in `inputValues` are scanned; URLs are additionally matched on
+/// their parsed host.
 #[derive(Default)]
 pub struct DomainBlockPlugin;
 
@@ -60,31 +217,30 @@ async fn eval(
             static DEFAULT: &[&str] = &["example.com", "mailinator.com", "tempmail", "evil.com"];
             DEFAULT.iter().map(|s| s.to_string()).collect::<Vec<_>>()
         } else {
-            cfg.domain_blocklist.clone()
+            // Scanned text is lower-cased, so the entries must be too.
+            cfg.domain_blocklist
+                .iter()
+                .map(|d| d.to_lowercase())
+                .collect()
         };
-        // AC matcher no longer required after boundary-aware matching change.
-        // Boundary aware domain detection on full text
-        if let Some((dom, _loc)) = domain_in_text(&ctx.pre.full_text_lower, &list) {
-            let diag = serde_json::json!({"plugin":"domain_block","code":"domain","detail":dom});
+        if let Some(hit) = scan_text(&ctx.pre.full_text_lower, &list, cfg.domain_host_only) {
+            return Some(domain_response(hit, cfg));
+        }
+        if let Some((ip, class)) = ip_literal_violation(&ctx.pre.urls_lower, cfg) {
+            let diag = Diagnostics::for_config("domain_block", "ip_literal", cfg)
+                .detail(ip.to_string())
+                .with("ipClass", class.into());
             return Some(AnalyzeResponse {
                 block_action: true,
                 reason_code: Some(113),
-                reason: Some("Input contains disallowed domain.".into()),
+                reason: Some("Input contains disallowed IP address.".into()),
                 blocked_by: Some("domain_block".into()),
-                diagnostics: Some(diag),
+                diagnostics: Some(diag.into()),
             });
         }
         for s in &ctx.pre.strings {
-            if let Some((dom, _)) = domain_in_text(s, &list) {
-                let diag =
-                    serde_json::json!({"plugin":"domain_block","code":"domain","detail":dom});
-                return Some(AnalyzeResponse {
-                    block_action: true,
-                    reason_code: Some(113),
-                    reason: Some("Input contains disallowed domain.".into()),
-                    blocked_by: Some("domain_block".into()),
-                    diagnostics: Some(diag),
-                });
+            if let Some(hit) = scan_text(s, &list, cfg.domain_host_only) {
+                return Some(domain_response(hit, cfg));
             }
         }
         None
@@ -93,7 +249,8 @@ async fn eval(
 
 #[cfg(test)]
 mod tests {
-    use super::domain_in_text;
+    use super::{domain_in_text, ip_literal_violation, scan_text, DomainHit};
+    use crate::plugins::PluginConfig;
 
     fn domains(list: &[&str]) -> Vec<String> {
         list.iter().map(|s| s.to_string()).collect()
@@ -119,4 +276,108 @@ fn handles_unicode_following_character() {
         let result = domain_in_text(text, &domains(&["evil.com"]));
         assert!(result.is_some());
     }
+
+    fn host_hit(hit: Option<DomainHit>) -> Option<(String, String)> {
+        match hit {
+            Some(DomainHit::Host { domain, host }) => Some((domain, host)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn matches_url_host_suffix_on_label_boundary() {
+        let list = domains(&["evil.com", "evil.example"]);
+        assert_eq!(
+            host_hit(scan_text("http://notevil.com.evil.example/x", &list, false)),
+            Some(("evil.example".into(), "notevil.com.evil.example".into()))
+        );
+        // Percent-encoded dot in the host is normalised by the URL parser.
+        assert!(host_hit(scan_text("https://evil%2ecom/login", &list, false)).is_some());
+        assert!(scan_text("http://notevil.com/", &list, false).is_none());
+    }
+
+    #[test]
+    fn host_only_ignores_domain_in_path_and_query() {
+        let list = domains(&["evil.com"]);
+        let text = "see https://safe.example/search?q=evil.com";
+        assert!(matches!(
+            scan_text(text, &list, false),
+            Some(DomainHit::Text { .. })
+        ));
+        assert!(scan_text(text, &list, true).is_none());
+        // Free text next to the URL is still scanned.
+        let mixed = "https://safe.example/?q=evil.com or mail evil.com";
+        match scan_text(mixed, &list, true) {
+            Some(DomainHit::Text { pos, .. }) => assert_eq!(&mixed[pos..pos + 8], "evil.com"),
+            _ => panic!("expected text hit"),
+        }
+    }
+
+    #[test]
+    fn matches_ipv6_and_ip_hosts_against_ip_entries() {
+        let list = domains(&["[2001:db8::1]", "10.0.0.7"]);
+        assert_eq!(
+            host_hit(scan_text("fetch http://[2001:db8::1]/x", &list, true)),
+            Some(("[2001:db8::1]".into(), "[2001:db8::1]".into()))
+        );
+        // Other notations of the same address, and no trailing slash.
+        assert!(host_hit(scan_text("http://[2001:0db8:0:0::1]:8443", &list, true)).is_some());
+        let plain = domains(&["2001:db8::1"]);
+        assert!(host_hit(scan_text("http://[2001:db8::1]", &plain, true)).is_some());
+        assert!(host_hit(scan_text("http://10.0.0.7:9000/a", &list, true)).is_some());
+        assert!(scan_text("http://[2001:db8::2]/", &list, true).is_none());
+    }
+
+    #[test]
+    fn userinfo_and_port_are_not_the_host() {
+        let list = domains(&["evil.com"]);
+        assert!(host_hit(scan_text("http://user@evil.com:8443/x", &list, true)).is_some());
+        assert!(scan_text("http://evil.com@safe.example/login", &list, true).is_none());
+    }
+
+    fn urls(list: &[&str]) -> Vec<String> {
+        list.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn blocks_metadata_ip_when_private_ips_blocked() {
+        let cfg = PluginConfig {
+            block_private_ips: true,
+            ..PluginConfig::default()
+        };
+        let hit = ip_literal_violation(
+            &urls(&["fetch http://169.254.169.254/latest/meta-data/"]),
+            &cfg,
+        );
+        assert_eq!(
+            hit.map(|(ip, c)| (ip.to_string(), c)),
+            Some(("169.254.169.254".into(), "metadata"))
+        );
+        // Hex-encoded host normalises to the same address.
+        assert!(ip_literal_violation(&urls(&["http://0xA9FEA9FE/"]), &cfg).is_some());
+        assert_eq!(
+            ip_literal_violation(&urls(&["http://[::1]:8080/x"]), &cfg).map(|(_, c)| c),
+            Some("loopback")
+        );
+    }
+
+    #[test]
+    fn public_ip_allowed_unless_all_literals_blocked() {
+        let private_only = PluginConfig {
+            block_private_ips: true,
+            ..PluginConfig::default()
+        };
+        let public = urls(&["https://8.8.8.8/dns"]);
+        assert!(ip_literal_violation(&public, &private_only).is_none());
+
+        let all = PluginConfig {
+            block_ip_literals: true,
+            ..PluginConfig::default()
+        };
+        assert_eq!(
+            ip_literal_violation(&public, &all).map(|(_, c)| c),
+            Some("public")
+        );
+        assert!(ip_literal_violation(&public, &PluginConfig::default()).is_none());
+    }
 }
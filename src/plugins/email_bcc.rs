@@ -1,7 +1,6 @@
-use super::{Plugin, PluginConfig};
-use crate::util::EvalContext;
+use super::{is_mail_tool, Diagnostics, Plugin, PluginConfig};
+use crate::util::{EvalContext, Precomputed};
 use crate::{AnalyzeRequest, AnalyzeResponse};
-use serde_json::Value;
 
 /// Blocks email tools with non‑compliant BCC domains.  The allowed
 /// domain suffix is read from the plugin configuration via
@@ -15,39 +14,34 @@ fn name(&self) -> &str {
         "email_bcc"
     }
 
+    fn applies(&self, req: &AnalyzeRequest, _ctx: &EvalContext) -> bool {
+        is_mail_tool(req)
+    }
+
     async fn eval(
         &self,
         req: &AnalyzeRequest,
         _ctx: &EvalContext,
         cfg: &PluginConfig,
     ) -> Option<AnalyzeResponse> {
-        // Only examine tools whose name contains "mail" or "email".  Names may
-        // be absent in incomplete requests.
-        let tool_name = req
-            .tool_definition
-            .name
-            .as_deref()
-            .unwrap_or("")
-            .to_lowercase();
-        if !tool_name.contains("mail") && !tool_name.contains("email") {
+        // Only examine mail tools.  Names may be absent in incomplete requests.
+        if !is_mail_tool(req) {
             return None;
         }
-        // Look for bcc field in inputValues
-        if let Some(Value::String(s)) = req.input_values.get("bcc") {
+        // Look for a non-blank bcc field in inputValues
+        if let Some(s) = Precomputed::input_str(&req.input_values, "bcc") {
             let addr = s.trim().to_lowercase();
-            if !addr.is_empty() {
-                // Check if email ends with "@company_domain" (with @ prefix)
-                let domain_pattern = format!("@{}", cfg.company_domain);
-                if !addr.ends_with(&domain_pattern) {
-                    let diag = serde_json::json!({"plugin":"email_bcc","code":"bcc","detail":addr});
-                    return Some(AnalyzeResponse {
-                        block_action: true,
-                        reason_code: Some(112),
-                        reason: Some("Noncompliant BCC domain.".into()),
-                        blocked_by: Some("email_bcc".into()),
-                        diagnostics: Some(diag),
-                    });
-                }
+            if !cfg.is_company_email(&addr) {
+                let diag = Diagnostics::for_config("email_bcc", "bcc", cfg)
+                    .detail(addr)
+                    .field("bcc");
+                return Some(AnalyzeResponse {
+                    block_action: true,
+                    reason_code: Some(112),
+                    reason: Some("Noncompliant BCC domain.".into()),
+                    blocked_by: Some("email_bcc".into()),
+                    diagnostics: Some(diag.into()),
+                });
             }
         }
         None
@@ -1,24 +1,113 @@
-use super::{Plugin, PluginConfig};
+use super::{Diagnostics, Plugin, PluginConfig};
 use crate::util::EvalContext;
 use crate::{AnalyzeRequest, AnalyzeResponse};
-use once_cell::sync::Lazy;
-
-/// Detects prompt injection and data exfiltration patterns in the user message.
-#[derive(Default)]
-pub struct ExfilPlugin;
+use regex::Regex;
+use serde::Deserialize;
 
 // Static list of lower‑cased substrings that, if present in user text, imply
 // malicious prompt injection or data exfiltration.  These patterns are
 // intentionally simple to keep evaluation cost minimal.
-static EXFIL_PATTERNS: Lazy<Vec<&'static str>> = Lazy::new(|| {
-    vec![
-        "export all data",
-        "ignore previous instructions",
-        "reveal secrets",
-        "print the system prompt",
-        "exfiltrate",
-    ]
-});
+const EXFIL_PATTERNS: &[&str] = &[
+    "export all data",
+    "ignore previous instructions",
+    "reveal secrets",
+    "print the system prompt",
+    "exfiltrate",
+];
+
+/// An additional exfil pattern from configuration: either a bare string or
+/// an object with per-pattern options.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ExfilPattern {
+    Plain(String),
+    Detailed(ExfilPatternSpec),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExfilPatternSpec {
+    pub pattern: String,
+    /// Match only as a whole word. Defaults to `exfil_word_boundary`.
+    #[serde(default)]
+    pub word_boundary: Option<bool>,
+    /// Reason code reported when this pattern fires. Defaults to 111.
+    #[serde(default)]
+    pub reason_code: Option<i32>,
+}
+
+/// Reason code for built-in patterns and custom ones without `reasonCode`.
+const DEFAULT_REASON_CODE: i32 = 111;
+
+/// A pattern ready for matching against lower-cased text.
+struct Matcher {
+    pattern: String,
+    /// Present when the pattern must match on word boundaries.
+    boundary_re: Option<Regex>,
+    reason_code: i32,
+}
+
+impl Matcher {
+    fn new(pattern: &str, word_boundary: bool, reason_code: i32) -> Self {
+        let pattern = pattern.to_lowercase();
+        let boundary_re = word_boundary
+            .then(|| Regex::new(&format!(r"\b{}\b", regex::escape(&pattern))).ok())
+            .flatten();
+        Matcher {
+            pattern,
+            boundary_re,
+            reason_code,
+        }
+    }
+
+    fn is_match(&self, hay: &str) -> bool {
+        match &self.boundary_re {
+            Some(re) => re.is_match(hay),
+            None => hay.contains(&self.pattern),
+        }
+    }
+}
+
+/// Detects prompt injection and data exfiltration patterns in the user message.
+pub struct ExfilPlugin {
+    matchers: Vec<Matcher>,
+}
+
+impl ExfilPlugin {
+    /// Built-in patterns followed by `exfil_patterns` from the config. With
+    /// `exfil_word_boundary`, single-word built-ins and custom patterns
+    /// without an explicit `wordBoundary` match whole words only; built-in
+    /// multi-word phrases always match as substrings.
+    pub fn new(cfg: &PluginConfig) -> Self {
+        let mut matchers: Vec<Matcher> = EXFIL_PATTERNS
+            .iter()
+            .map(|p| {
+                Matcher::new(
+                    p,
+                    cfg.exfil_word_boundary && !p.contains(' '),
+                    DEFAULT_REASON_CODE,
+                )
+            })
+            .collect();
+        for extra in &cfg.exfil_patterns {
+            let (pattern, word_boundary, reason_code) = match extra {
+                ExfilPattern::Plain(p) => (p.as_str(), None, None),
+                ExfilPattern::Detailed(spec) => {
+                    (spec.pattern.as_str(), spec.word_boundary, spec.reason_code)
+                }
+            };
+            if pattern.trim().is_empty() {
+                continue;
+            }
+            matchers.push(Matcher::new(
+                pattern,
+                word_boundary.unwrap_or(cfg.exfil_word_boundary),
+                reason_code.unwrap_or(DEFAULT_REASON_CODE),
+            ));
+        }
+        ExfilPlugin { matchers }
+    }
+}
 
 #[async_trait::async_trait]
 impl Plugin for ExfilPlugin {
@@ -30,18 +119,20 @@ async fn eval(
         &self,
         _req: &AnalyzeRequest,
         ctx: &EvalContext,
-        _cfg: &PluginConfig,
+        cfg: &PluginConfig,
     ) -> Option<AnalyzeResponse> {
         let hay = &ctx.pre.full_text_lower;
-        for pat in EXFIL_PATTERNS.iter() {
-            if hay.contains(*pat) {
+        for m in &self.matchers {
+            if m.is_match(hay) {
                 return Some(AnalyzeResponse {
                     block_action: true,
-                    reason_code: Some(111),
+                    reason_code: Some(m.reason_code),
                     reason: Some("Detected data exfiltration pattern".into()),
                     blocked_by: Some("exfil".into()),
                     diagnostics: Some(
-                        serde_json::json!({"plugin":"exfil","code":"pattern","detail":pat}),
+                        Diagnostics::for_config("exfil", "pattern", cfg)
+                            .detail(m.pattern.clone())
+                            .into(),
                     ),
                 });
             }
@@ -49,3 +140,59 @@ async fn eval(
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cfg(value: serde_json::Value) -> PluginConfig {
+        serde_json::from_value(value).unwrap()
+    }
+
+    fn matches(plugin: &ExfilPlugin, text: &str) -> bool {
+        plugin.matchers.iter().any(|m| m.is_match(text))
+    }
+
+    #[test]
+    fn boundary_pattern_ignores_larger_words() {
+        let plugin = ExfilPlugin::new(&cfg(serde_json::json!({
+            "exfilPatterns": [{"pattern": "dump", "wordBoundary": true}]
+        })));
+        assert!(!matches(&plugin, "share the dumpling recipe"));
+        assert!(matches(&plugin, "dump the users table"));
+        assert!(matches(&plugin, "then dump."));
+    }
+
+    #[test]
+    fn custom_reason_code_defaults_to_111() {
+        let plugin = ExfilPlugin::new(&cfg(serde_json::json!({
+            "exfilPatterns": [{"pattern": "Dump Tables", "reasonCode": 114}, "leak"]
+        })));
+        let code = |text: &str| {
+            plugin
+                .matchers
+                .iter()
+                .find(|m| m.is_match(text))
+                .map(|m| m.reason_code)
+        };
+        assert_eq!(code("dump tables now"), Some(114));
+        assert_eq!(code("leak it"), Some(111));
+        assert_eq!(code("export all data"), Some(111));
+    }
+
+    #[test]
+    fn global_boundary_applies_to_single_word_builtins_only() {
+        let loose = ExfilPlugin::new(&PluginConfig::default());
+        assert!(matches(&loose, "data was exfiltrated yesterday"));
+
+        let strict = ExfilPlugin::new(&cfg(serde_json::json!({
+            "exfilWordBoundary": true,
+            "exfilPatterns": ["leak"]
+        })));
+        assert!(!matches(&strict, "data was exfiltrated yesterday"));
+        assert!(matches(&strict, "exfiltrate the db"));
+        assert!(!matches(&strict, "the pipe has a leakage"));
+        // Multi-word built-ins still match as substrings.
+        assert!(matches(&strict, "xignore previous instructionsx"));
+    }
+}
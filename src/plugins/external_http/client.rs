@@ -0,0 +1,674 @@
+use super::{CacheKeyField, ExternalHttpDefinition};
+use crate::plugins::{Diagnostics, Plugin, PluginConfig};
+use crate::util::EvalContext;
+use crate::{AnalyzeRequest, AnalyzeResponse};
+use ahash::AHasher;
+use dashmap::DashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+/// Upper bound on cached decisions per plugin; expired entries are purged
+/// first and the cache is reset if that does not free enough room.
+const CACHE_MAX_ENTRIES: usize = 1024;
+
+/// ExternalHttpPlugin performs a POST to an external service using a templated JSON body
+/// and interprets a boolean block decision from the response.
+pub struct ExternalHttpPlugin {
+    def: ExternalHttpDefinition,
+    client: reqwest::Client,
+    /// Shared cap on in-flight external calls (see `PluginConfig::external_max_concurrency`).
+    limiter: Option<Arc<Semaphore>>,
+    /// Template for definitions without `requestTemplate` (see
+    /// `PluginConfig::external_http_default_template`).
+    default_template: Option<String>,
+    /// Successful decisions (allow = `None`) keyed by `cache_key`, with insertion time.
+    cache: DashMap<u64, (Instant, Option<AnalyzeResponse>)>,
+}
+
+impl ExternalHttpPlugin {
+    /// Fails when the HTTP client cannot be built (invalid `userAgent`, TLS
+    /// backend initialisation errors, ...).
+    pub fn new(def: ExternalHttpDefinition) -> Result<Self, reqwest::Error> {
+        let timeout = std::time::Duration::from_millis(def.timeout_ms);
+        let mut builder = reqwest::Client::builder().timeout(timeout);
+        if let Some(agent) = &def.user_agent {
+            builder = builder.user_agent(agent.as_str());
+        }
+        let client = builder.build()?;
+        Ok(Self {
+            def,
+            client,
+            limiter: None,
+            default_template: None,
+            cache: DashMap::new(),
+        })
+    }
+
+    /// Attach a semaphore shared with other external plugins; each call holds
+    /// one permit for its duration.
+    pub fn with_limiter(mut self, limiter: Arc<Semaphore>) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// Template used when the definition has no `requestTemplate` of its
+    /// own, ahead of the built-in default.
+    pub fn with_default_template(mut self, template: Option<String>) -> Self {
+        self.default_template = template;
+        self
+    }
+
+    fn render_body(&self, req: &AnalyzeRequest) -> String {
+        const DEFAULT_TEMPLATE: &str = r#"{
+  "userMessage": "${userMessage}",
+  "toolName": "${toolName}",
+  "input": ${inputJson}
+}"#;
+        let template = self
+            .def
+            .request_template
+            .as_deref()
+            .or(self.default_template.as_deref())
+            .unwrap_or(DEFAULT_TEMPLATE);
+
+        let user_message_raw = req.planner_context.user_message.as_deref().unwrap_or("");
+        let tool_name_raw = req.tool_definition.name.as_deref().unwrap_or("");
+        let tenant_id_raw = tenant_id(req);
+        let conversation_id_raw = req
+            .conversation_metadata
+            .as_ref()
+            .and_then(|m| m.conversation_id.as_deref());
+
+        // Absent context renders as `null` (JSON variants) or an empty string.
+        render_template(template, |name| {
+            Some(match name {
+                "inputJson" => serde_json::Value::Object(req.input_values.clone()).to_string(),
+                "chatHistoryJson" => json_or_null(req.planner_context.chat_history.as_ref()),
+                "prevOutputsJson" => {
+                    json_or_null(req.planner_context.previous_tool_outputs.as_ref())
+                }
+                "tenantIdJson" => json_or_null(tenant_id_raw),
+                "conversationIdJson" => json_or_null(conversation_id_raw),
+                "userMessageJson" => serde_json::Value::from(user_message_raw).to_string(),
+                "toolNameJson" => serde_json::Value::from(tool_name_raw).to_string(),
+                "tenantId" => escape_json_string(tenant_id_raw.unwrap_or("")),
+                "conversationId" => escape_json_string(conversation_id_raw.unwrap_or("")),
+                "userMessage" => escape_json_string(user_message_raw),
+                "toolName" => escape_json_string(tool_name_raw),
+                _ => return None,
+            })
+        })
+    }
+
+    fn cache_key(&self, req: &AnalyzeRequest, body: &str) -> Option<u64> {
+        if self.def.cache_ttl_ms == 0 {
+            return None;
+        }
+        let mut hasher = AHasher::default();
+        body.hash(&mut hasher);
+        for field in &self.def.cache_key_fields {
+            match field {
+                CacheKeyField::TenantId => tenant_id(req).hash(&mut hasher),
+                CacheKeyField::ToolName => req.tool_definition.name.hash(&mut hasher),
+            }
+        }
+        Some(hasher.finish())
+    }
+
+    fn cached(&self, key: u64) -> Option<Option<AnalyzeResponse>> {
+        let ttl = Duration::from_millis(self.def.cache_ttl_ms);
+        let entry = self.cache.get(&key)?;
+        if entry.0.elapsed() < ttl {
+            return Some(entry.1.clone());
+        }
+        drop(entry);
+        self.cache.remove(&key);
+        None
+    }
+
+    fn remember(&self, key: Option<u64>, decision: &Option<AnalyzeResponse>) {
+        let Some(key) = key else { return };
+        if self.cache.len() >= CACHE_MAX_ENTRIES {
+            let ttl = Duration::from_millis(self.def.cache_ttl_ms);
+            self.cache.retain(|_, (at, _)| at.elapsed() < ttl);
+            if self.cache.len() >= CACHE_MAX_ENTRIES {
+                self.cache.clear();
+            }
+        }
+        self.cache.insert(key, (Instant::now(), decision.clone()));
+    }
+
+    fn extract_block(&self, val: &serde_json::Value) -> Option<bool> {
+        let field = self.def.block_field.as_str();
+        if field == "block" {
+            if let Some(b) = val.get("block").and_then(|v| self.scalar_block(v)) {
+                return Some(b);
+            }
+        } else if field == "allow" {
+            if let Some(a) = val.get("allow").and_then(|v| v.as_bool()) {
+                return Some(!a);
+            }
+        } else if field == "/" {
+            // Treat '/' as root for convenience in configs (serde_json uses empty string for root pointer).
+            if self.def.non_empty_pointer_blocks {
+                match val {
+                    serde_json::Value::Array(a) => return Some(!a.is_empty()),
+                    serde_json::Value::Object(o) => return Some(!o.is_empty()),
+                    serde_json::Value::Bool(b) => return Some(*b),
+                    _ => {}
+                }
+            }
+            if let Some(b) = self.numeric_block(val) {
+                return Some(b);
+            }
+        } else if field.starts_with('/') || field.contains('/') {
+            // Treat as JSON pointer (serde_json returns Option)
+            if let Some(ptr) = val.pointer(field) {
+                if let Some(b) = self.scalar_block(ptr) {
+                    return Some(b);
+                }
+                if self.def.non_empty_pointer_blocks {
+                    match ptr {
+                        serde_json::Value::Array(a) => return Some(!a.is_empty()),
+                        serde_json::Value::Object(o) => return Some(!o.is_empty()),
+                        _ => {}
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// A boolean, or a number compared against `blockNumericThreshold`.
+    fn scalar_block(&self, val: &serde_json::Value) -> Option<bool> {
+        val.as_bool().or_else(|| self.numeric_block(val))
+    }
+
+    fn numeric_block(&self, val: &serde_json::Value) -> Option<bool> {
+        let threshold = self.def.block_numeric_threshold?;
+        val.as_f64().map(|n| n >= threshold)
+    }
+}
+
+/// Tenant of the calling agent, falling back to the user's tenant.
+fn tenant_id(req: &AnalyzeRequest) -> Option<&str> {
+    req.conversation_metadata.as_ref().and_then(|m| {
+        m.agent
+            .as_ref()
+            .and_then(|a| a.tenant_id.as_deref())
+            .or_else(|| m.user.as_ref().and_then(|u| u.tenant_id.as_deref()))
+    })
+}
+
+fn json_or_null<T: serde::Serialize>(value: Option<T>) -> String {
+    value
+        .and_then(|v| serde_json::to_string(&v).ok())
+        .unwrap_or_else(|| "null".to_string())
+}
+
+/// Substitute `${name}` placeholders in a single pass, so text inserted for
+/// one placeholder (say, an input value containing `${userMessageJson}`) is
+/// never expanded again. Unknown placeholders are kept verbatim.
+fn render_template(template: &str, value: impl Fn(&str) -> Option<String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("${") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let expanded = after
+            .find('}')
+            .and_then(|end| value(&after[..end]).map(|v| (end, v)));
+        match expanded {
+            Some((end, v)) => {
+                out.push_str(&v);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push_str("${");
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// `value` escaped for use inside a JSON string literal (quotes,
+/// backslashes and control characters), without the surrounding quotes.
+fn escape_json_string(value: &str) -> String {
+    if value.is_empty() {
+        return String::new();
+    }
+    // A string always serializes to `"..."`, so trimming one byte at each
+    // end removes exactly the quotes.
+    let quoted = serde_json::Value::from(value).to_string();
+    quoted[1..quoted.len() - 1].to_string()
+}
+
+#[async_trait::async_trait]
+impl Plugin for ExternalHttpPlugin {
+    fn name(&self) -> &str {
+        &self.def.name
+    }
+
+    async fn eval(
+        &self,
+        req: &AnalyzeRequest,
+        _ctx: &EvalContext,
+        cfg: &PluginConfig,
+    ) -> Option<AnalyzeResponse> {
+        let body = self.render_body(req);
+        let cache_key = self.cache_key(req, &body);
+        if let Some(decision) = cache_key.and_then(|key| self.cached(key)) {
+            return decision;
+        }
+        // Wait for a shared permit no longer than the call timeout itself.
+        let _permit = match &self.limiter {
+            Some(limiter) => {
+                let wait = std::time::Duration::from_millis(self.def.timeout_ms);
+                match tokio::time::timeout(wait, limiter.clone().acquire_owned()).await {
+                    Ok(Ok(permit)) => Some(permit),
+                    _ => {
+                        if !self.def.fail_open {
+                            tracing::warn!(plugin=%self.def.name, "external_http concurrency limit saturated (fail-closed)");
+                            return Some(AnalyzeResponse {
+                                block_action: true,
+                                reason_code: Some(self.def.reason_code),
+                                reason: Some(
+                                    self.def
+                                        .reason
+                                        .clone()
+                                        .unwrap_or_else(|| "External HTTP unavailable".into()),
+                                ),
+                                blocked_by: Some(self.def.name.clone()),
+                                diagnostics: Some(
+                                    Diagnostics::for_config("external_http", "saturated", cfg)
+                                        .into(),
+                                ),
+                            });
+                        }
+                        tracing::warn!(plugin=%self.def.name, "external_http concurrency limit saturated (fail-open)");
+                        return None;
+                    }
+                }
+            }
+            None => None,
+        };
+        let mut rb = self
+            .client
+            .post(&self.def.url)
+            .header("content-type", "application/json");
+        if let Some(tok) = &self.def.bearer_token {
+            rb = rb.bearer_auth(tok);
+        }
+        let resp = match rb.body(body).send().await {
+            Ok(r) => r,
+            Err(err) => {
+                if !self.def.fail_open {
+                    tracing::warn!(plugin=%self.def.name, error=?err, "external_http network error (fail-closed)");
+                    return Some(AnalyzeResponse {
+                        block_action: true,
+                        reason_code: Some(self.def.reason_code),
+                        reason: Some(
+                            self.def
+                                .reason
+                                .clone()
+                                .unwrap_or_else(|| "External HTTP error".into()),
+                        ),
+                        blocked_by: Some(self.def.name.clone()),
+                        diagnostics: Some(
+                            Diagnostics::for_config("external_http", "network_error", cfg).into(),
+                        ),
+                    });
+                } else {
+                    tracing::warn!(plugin=%self.def.name, error=?err, "external_http network error (fail-open)");
+                    return None;
+                }
+            }
+        };
+        let status = resp.status();
+        // 429/503 with Retry-After is the service shedding load, not a
+        // decision: handle it like a network error instead of parsing the body.
+        if matches!(status.as_u16(), 429 | 503) {
+            if let Some(retry_after) = resp
+                .headers()
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|v| v.to_str().ok())
+            {
+                if !self.def.fail_open {
+                    tracing::warn!(plugin=%self.def.name, status=%status, retry_after, "external_http backpressure (fail-closed)");
+                    return Some(AnalyzeResponse {
+                        block_action: true,
+                        reason_code: Some(self.def.reason_code),
+                        reason: Some(
+                            self.def
+                                .reason
+                                .clone()
+                                .unwrap_or_else(|| "External HTTP unavailable".into()),
+                        ),
+                        blocked_by: Some(self.def.name.clone()),
+                        diagnostics: Some(
+                            Diagnostics::for_config("external_http", "network_error", cfg)
+                                .with("status", status.as_u16().into())
+                                .with("retryAfter", retry_after.into())
+                                .into(),
+                        ),
+                    });
+                }
+                tracing::warn!(plugin=%self.def.name, status=%status, retry_after, "external_http backpressure (fail-open)");
+                return None;
+            }
+        }
+        let text = match resp.text().await {
+            Ok(t) => t,
+            Err(err) => {
+                if !self.def.fail_open {
+                    return Some(AnalyzeResponse {
+                        block_action: true,
+                        reason_code: Some(self.def.reason_code),
+                        reason: Some(
+                            self.def
+                                .reason
+                                .clone()
+                                .unwrap_or_else(|| "External HTTP read error".into()),
+                        ),
+                        blocked_by: Some(self.def.name.clone()),
+                        diagnostics: Some(
+                            Diagnostics::for_config("external_http", "read_error", cfg).into(),
+                        ),
+                    });
+                }
+                tracing::warn!(plugin=%self.def.name, error=?err, "external_http read error (fail-open)");
+                return None;
+            }
+        };
+        let json: serde_json::Value = match serde_json::from_str(&text) {
+            Ok(v) => v,
+            Err(err) => {
+                if !self.def.fail_open {
+                    return Some(AnalyzeResponse {
+                        block_action: true,
+                        reason_code: Some(self.def.reason_code),
+                        reason: Some(
+                            self.def
+                                .reason
+                                .clone()
+                                .unwrap_or_else(|| "External HTTP parse error".into()),
+                        ),
+                        blocked_by: Some(self.def.name.clone()),
+                        diagnostics: Some(
+                            Diagnostics::for_config("external_http", "parse_error", cfg)
+                                .with("status", status.as_u16().into())
+                                .into(),
+                        ),
+                    });
+                }
+                tracing::warn!(plugin=%self.def.name, error=?err, "external_http parse error (fail-open)");
+                return None;
+            }
+        };
+        // Only genuine decisions are cached; errors above are retried next time.
+        let decision = match self.extract_block(&json) {
+            Some(true) => Some(AnalyzeResponse {
+                block_action: true,
+                reason_code: Some(self.def.reason_code),
+                reason: Some(
+                    self.def
+                        .reason
+                        .clone()
+                        .unwrap_or_else(|| "External policy block".into()),
+                ),
+                blocked_by: Some(self.def.name.clone()),
+                diagnostics: Some(
+                    Diagnostics::for_config("external_http", "block", cfg)
+                        .with("status", status.as_u16().into())
+                        .into(),
+                ),
+            }),
+            // The service explicitly allowed: report it so allows are counted.
+            Some(false) => Some(AnalyzeResponse {
+                block_action: false,
+                reason_code: None,
+                reason: None,
+                blocked_by: None,
+                diagnostics: None,
+            }),
+            // If block field absent treat as allow (abstain)
+            None => None,
+        };
+        self.remember(cache_key, &decision);
+        decision
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnalyzeRequest, PlannerContext, ToolDefinition};
+    use serde_json::{json, Map, Value};
+
+    fn make_plugin(template: Option<&str>) -> ExternalHttpPlugin {
+        let def = ExternalHttpDefinition {
+            name: "external_test".to_string(),
+            url: "http://example.com".to_string(),
+            bearer_token: None,
+            user_agent: None,
+            timeout_ms: 500,
+            request_template: template.map(|t| t.to_string()),
+            block_field: "block".to_string(),
+            reason_code: 801,
+            reason: None,
+            fail_open: true,
+            non_empty_pointer_blocks: false,
+            block_numeric_threshold: None,
+            cache_ttl_ms: 0,
+            cache_key_fields: Vec::new(),
+        };
+        ExternalHttpPlugin::new(def).unwrap()
+    }
+
+    fn make_request(user_message: &str, tool_name: &str, input: Value) -> AnalyzeRequest {
+        let input_map = match input {
+            Value::Object(map) => map,
+            other => {
+                let mut map = Map::new();
+                map.insert("value".to_string(), other);
+                map
+            }
+        };
+        AnalyzeRequest {
+            planner_context: PlannerContext {
+                user_message: Some(user_message.to_string()),
+                ..PlannerContext::default()
+            },
+            tool_definition: ToolDefinition {
+                name: Some(tool_name.to_string()),
+                ..ToolDefinition::default()
+            },
+            input_values: input_map,
+            ..AnalyzeRequest::default()
+        }
+    }
+
+    #[test]
+    fn render_body_handles_special_characters_in_default_template() {
+        let plugin = make_plugin(None);
+        let req = make_request(
+            "line1\nline2 with \"quotes\" and \\slashes\\",
+            "Tool\nName",
+            json!({"nested": "value"}),
+        );
+        let body = plugin.render_body(&req);
+        let parsed: Value = serde_json::from_str(&body).expect("body is valid JSON");
+        assert_eq!(
+            parsed.get("userMessage").and_then(Value::as_str),
+            Some("line1\nline2 with \"quotes\" and \\slashes\\")
+        );
+        assert_eq!(
+            parsed.get("toolName").and_then(Value::as_str),
+            Some("Tool\nName")
+        );
+        assert_eq!(parsed.get("input"), Some(&json!({"nested": "value"})));
+    }
+
+    #[test]
+    fn render_body_exposes_conversation_context() {
+        let plugin = make_plugin(Some(
+            r#"{"tenant": "${tenantId}", "conv": ${conversationIdJson}, "history": ${chatHistoryJson}, "prev": ${prevOutputsJson}}"#,
+        ));
+        let mut req = make_request("hi", "Tool", json!({}));
+        req.planner_context.chat_history =
+            Some(vec![json!({"role": "user", "content": "earlier"})]);
+        req.conversation_metadata = Some(crate::ConversationMetadata {
+            agent: Some(crate::ConversationAgent {
+                tenant_id: Some("tenant-42".to_string()),
+                ..Default::default()
+            }),
+            conversation_id: Some("conv-1".to_string()),
+            ..Default::default()
+        });
+        let parsed: Value = serde_json::from_str(&plugin.render_body(&req)).unwrap();
+        assert_eq!(parsed["tenant"], json!("tenant-42"));
+        assert_eq!(parsed["conv"], json!("conv-1"));
+        assert_eq!(parsed["history"][0]["content"], json!("earlier"));
+        assert_eq!(parsed["prev"], Value::Null);
+
+        // Without metadata the placeholders still yield valid JSON.
+        let bare = make_request("hi", "Tool", json!({}));
+        let parsed: Value = serde_json::from_str(&plugin.render_body(&bare)).unwrap();
+        assert_eq!(parsed["tenant"], json!(""));
+        assert_eq!(parsed["conv"], Value::Null);
+        assert_eq!(parsed["history"], Value::Null);
+    }
+
+    #[test]
+    fn render_body_supports_json_placeholders() {
+        let plugin = make_plugin(Some(
+            "{\"msg\": ${userMessageJson}, \"tool\": ${toolNameJson}}",
+        ));
+        let req = make_request("escape", "Name", json!({}));
+        let body = plugin.render_body(&req);
+        let parsed: Value = serde_json::from_str(&body).expect("body is valid JSON");
+        assert_eq!(parsed.get("msg").and_then(Value::as_str), Some("escape"));
+        assert_eq!(parsed.get("tool").and_then(Value::as_str), Some("Name"));
+    }
+
+    /// Every placeholder, quoted or bare as each one requires.
+    const ALL_PLACEHOLDERS: &str = r#"{"m": "${userMessage}", "mj": ${userMessageJson},
+        "t": "${toolName}", "tj": ${toolNameJson}, "input": ${inputJson},
+        "tenant": "${tenantId}", "tenantJ": ${tenantIdJson},
+        "conv": "${conversationId}", "convJ": ${conversationIdJson},
+        "history": ${chatHistoryJson}, "prev": ${prevOutputsJson}, "other": "${unknown}"}"#;
+
+    /// Random text from fragments that tend to break naive escaping:
+    /// quotes, backslashes, control characters, non-BMP characters and
+    /// placeholder syntax. Seeded xorshift, so failures reproduce.
+    fn random_text(state: &mut u64) -> String {
+        const POOL: &[&str] = &[
+            "a",
+            "Z",
+            "0",
+            " ",
+            "\"",
+            "\\",
+            "\n",
+            "\r",
+            "\t",
+            "\u{0}",
+            "\u{1b}",
+            "\u{1f}",
+            "\u{7f}",
+            "é",
+            "日本",
+            "🦀",
+            "\u{2028}",
+            "\u{feff}",
+            "${userMessage}",
+            "${inputJson}",
+            "${toolNameJson}",
+            "${",
+            "}",
+            "{",
+            "]",
+            "/",
+        ];
+        let mut next = || {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            *state
+        };
+        let len = (next() % 12) as usize;
+        (0..len)
+            .map(|_| POOL[(next() % POOL.len() as u64) as usize])
+            .collect()
+    }
+
+    #[test]
+    fn render_body_always_renders_valid_json() {
+        let builtin = make_plugin(None);
+        let all = make_plugin(Some(ALL_PLACEHOLDERS));
+        let mut state = 0x9e37_79b9_7f4a_7c15;
+        for _ in 0..500 {
+            let message = random_text(&mut state);
+            let tool = random_text(&mut state);
+            let value = random_text(&mut state);
+            let tenant = random_text(&mut state);
+            let mut req = make_request(&message, &tool, json!({ "field": value }));
+            req.conversation_metadata = serde_json::from_value(json!({
+                "conversationId": random_text(&mut state),
+                "agent": { "tenantId": tenant }
+            }))
+            .unwrap();
+
+            let body = builtin.render_body(&req);
+            let parsed: Value =
+                serde_json::from_str(&body).unwrap_or_else(|e| panic!("{e}: {body}"));
+            assert_eq!(parsed["userMessage"], message.as_str());
+            assert_eq!(parsed["toolName"], tool.as_str());
+            assert_eq!(parsed["input"]["field"], value.as_str());
+
+            let body = all.render_body(&req);
+            let parsed: Value =
+                serde_json::from_str(&body).unwrap_or_else(|e| panic!("{e}: {body}"));
+            assert_eq!(parsed["m"], parsed["mj"]);
+            assert_eq!(parsed["m"], message.as_str());
+            assert_eq!(parsed["t"], parsed["tj"]);
+            assert_eq!(parsed["tenant"], tenant.as_str());
+            assert_eq!(parsed["tenantJ"], tenant.as_str());
+            assert_eq!(parsed["conv"], parsed["convJ"]);
+            assert_eq!(parsed["input"]["field"], value.as_str());
+            assert_eq!(parsed["other"], "${unknown}");
+        }
+    }
+
+    #[test]
+    fn inserted_values_are_not_expanded_again() {
+        let plugin = make_plugin(Some(r#"{"m": ${userMessageJson}, "input": ${inputJson}}"#));
+        let req = make_request("hi", "Tool", json!({ "x": "${userMessageJson}" }));
+        let parsed: Value = serde_json::from_str(&plugin.render_body(&req)).unwrap();
+        assert_eq!(parsed["input"]["x"], "${userMessageJson}");
+        assert_eq!(escape_json_string(""), "");
+        assert_eq!(escape_json_string("\u{1}\"\\"), "\\u0001\\\"\\\\");
+    }
+
+    #[test]
+    fn render_body_prefers_own_then_configured_default_template() {
+        let shared = Some("{\"shared\": ${toolNameJson}}".to_string());
+        let req = make_request("hi", "Name", json!({}));
+
+        let plugin = make_plugin(None).with_default_template(shared.clone());
+        let parsed: Value = serde_json::from_str(&plugin.render_body(&req)).unwrap();
+        assert_eq!(parsed, json!({"shared": "Name"}));
+
+        let plugin = make_plugin(Some("{\"own\": ${toolNameJson}}")).with_default_template(shared);
+        let parsed: Value = serde_json::from_str(&plugin.render_body(&req)).unwrap();
+        assert_eq!(parsed, json!({"own": "Name"}));
+
+        let plugin = make_plugin(None).with_default_template(None);
+        let parsed: Value = serde_json::from_str(&plugin.render_body(&req)).unwrap();
+        assert_eq!(parsed["toolName"], "Name");
+    }
+}
@@ -0,0 +1,84 @@
+//! Externally hosted policy checks: each `external_http` definition in the
+//! plugin config becomes a plugin that POSTs a templated JSON body to a
+//! service and reads a block decision back. The HTTP client lives in
+//! `client` and is only built with the `external-http` feature; the
+//! definitions always parse so configs stay portable across builds.
+
+#[cfg(feature = "external-http")]
+mod client;
+
+#[cfg(feature = "external-http")]
+pub use client::ExternalHttpPlugin;
+
+/// Definition for an external HTTP plugin.
+#[derive(Clone, Debug, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExternalHttpDefinition {
+    pub name: String,
+    pub url: String,
+    #[serde(default)]
+    pub bearer_token: Option<String>,
+    /// `User-Agent` sent to the service (reqwest's default when unset).
+    #[serde(default)]
+    pub user_agent: Option<String>,
+    #[serde(default = "external_http_default_timeout")]
+    pub timeout_ms: u64,
+    #[serde(default)]
+    pub request_template: Option<String>,
+    #[serde(default = "external_http_default_block_field")]
+    pub block_field: String,
+    #[serde(default = "external_http_default_reason_code")]
+    pub reason_code: i32,
+    #[serde(default)]
+    pub reason: Option<String>,
+    #[serde(default = "external_http_default_fail_open")]
+    pub fail_open: bool,
+    /// If true and blockField is a JSON pointer, a non-empty array or object at that pointer will be treated as block.
+    #[serde(default)]
+    pub non_empty_pointer_blocks: bool,
+    /// If set, a number at the block field (e.g. `{"risk": 2}` with
+    /// blockField `/risk`) blocks when it is at least this value and allows
+    /// otherwise.
+    #[serde(default)]
+    pub block_numeric_threshold: Option<f64>,
+    /// Reuse decisions for identical request bodies for this long (0 disables caching).
+    #[serde(default)]
+    pub cache_ttl_ms: u64,
+    /// Request attributes mixed into the cache key in addition to the rendered body,
+    /// so templates that omit them cannot share decisions across tenants or tools.
+    #[serde(default)]
+    pub cache_key_fields: Vec<CacheKeyField>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheKeyField {
+    #[serde(alias = "tenantId")]
+    TenantId,
+    #[serde(alias = "toolName")]
+    ToolName,
+}
+
+fn external_http_default_timeout() -> u64 {
+    500
+}
+fn external_http_default_block_field() -> String {
+    "block".to_string()
+}
+fn external_http_default_reason_code() -> i32 {
+    801
+}
+fn external_http_default_fail_open() -> bool {
+    true
+}
+
+/// Sum of the timeouts of the external definitions referenced by `order`.
+/// Used at startup to warn when serial external calls could outlast the
+/// plugin budget.
+pub fn total_timeout_ms(order: &[String], defs: &[ExternalHttpDefinition]) -> u64 {
+    order
+        .iter()
+        .filter_map(|name| defs.iter().find(|d| d.name == *name))
+        .map(|d| d.timeout_ms)
+        .sum()
+}
@@ -2,29 +2,46 @@
 //!
 //! Each plugin encapsulates one class of check.  The `PluginPipeline`
 //! orchestrates the registered plugins until the first blocking plugin
-//! response. Aggregated (run-all) mode has been removed for production
-//! simplicity.
+//! response. `evaluate_all_with_timings` keeps going after a block to
+//! collect every signal for tuning; the first block still decides.
 
+use std::panic::AssertUnwindSafe;
 use std::sync::Arc;
 
+use futures_util::FutureExt;
+
 use crate::util::EvalContext;
 use crate::{AnalyzeRequest, AnalyzeResponse};
 
+pub mod base64_decode;
 pub mod domain_block;
 pub mod email_bcc;
 pub mod exfil;
 pub mod external_http;
+pub mod output_sink;
 pub mod pii;
 pub mod policy_pack;
+pub mod reasons;
+pub mod recipient_limit;
+pub mod role_injection;
 pub mod secrets;
+pub mod tool_denylist;
+pub mod tool_output_injection;
 
+use self::base64_decode::Base64DecodePlugin;
 use self::domain_block::DomainBlockPlugin;
 use self::email_bcc::EmailBccPlugin;
 use self::exfil::ExfilPlugin;
+#[cfg(feature = "external-http")]
 use self::external_http::ExternalHttpPlugin;
+use self::output_sink::OutputSinkPlugin;
 use self::pii::PiiPlugin;
 use self::policy_pack::PolicyPackPlugin;
+use self::recipient_limit::RecipientLimitPlugin;
+use self::role_injection::RoleInjectionPlugin;
 use self::secrets::SecretsPlugin;
+use self::tool_denylist::ToolDenylistPlugin;
+use self::tool_output_injection::ToolOutputInjectionPlugin;
 
 /// Configuration parameters for plugins loaded from environment or a JSON file.
 #[derive(Clone, Debug, serde::Deserialize)]
@@ -33,39 +50,504 @@ pub struct PluginConfig {
     /// lower case.  An empty list disables keyword scanning.
     #[serde(default, alias = "piiKeywords")]
     pub pii_keywords: Vec<String>,
+    /// Regex keywords (e.g. an employee-ID format), matched
+    /// case-insensitively alongside `pii_keywords`.
+    #[serde(default, alias = "piiKeywordRegex")]
+    pub pii_keyword_regex: Vec<String>,
     /// Additional domains that should never appear in inputs.  Lower case.
     #[serde(default, alias = "domainBlocklist")]
     pub domain_blocklist: Vec<String>,
+    /// Match URLs in `domain_block` on their parsed host only, ignoring
+    /// blocklisted domains that appear in a URL's path or query. Text
+    /// outside URLs is still scanned for substrings.
+    #[serde(default, alias = "domainHostOnly")]
+    pub domain_host_only: bool,
+    /// Block URLs whose host is a loopback, private, link-local or cloud
+    /// metadata IP literal (SSRF targets). Evaluated by `domain_block`.
+    #[serde(default, alias = "blockPrivateIps")]
+    pub block_private_ips: bool,
+    /// Block URLs whose host is any IP literal, including public addresses.
+    #[serde(default, alias = "blockIpLiterals")]
+    pub block_ip_literals: bool,
+    /// Require IBAN candidates to pass the mod-97 checksum before the pii
+    /// plugin blocks. Set to false for shape-only matching.
+    #[serde(default = "default_true", alias = "ibanChecksum")]
+    pub iban_checksum: bool,
+    /// Only flag AWS access key IDs in their exact format (uppercase `AKIA`
+    /// plus 16 uppercase alphanumerics). Overridden by
+    /// `SENTRA_SECRETS_AWS_STRICT`.
+    #[serde(default, alias = "secretsAwsStrict")]
+    pub secrets_aws_strict: bool,
+    /// How much matched content plugins put into block diagnostics.
+    /// Overridden by `SENTRA_DIAGNOSTICS_LEVEL` (or `SENTRA_DIAGNOSTICS_MODE`).
+    /// `diagnosticsMode` is accepted as an alias, with `compact` for
+    /// `minimal`.
+    #[serde(
+        default,
+        alias = "diagnosticsLevel",
+        alias = "diagnostics_mode",
+        alias = "diagnosticsMode"
+    )]
+    pub diagnostics_level: DiagnosticsLevel,
+    /// Extra exfil patterns, appended to the built-in list. Entries are
+    /// strings or `{ "pattern": ..., "wordBoundary": bool }` objects.
+    #[serde(default, alias = "exfilPatterns")]
+    pub exfil_patterns: Vec<exfil::ExfilPattern>,
+    /// Match single-word exfil patterns on word boundaries instead of as
+    /// raw substrings (per-pattern `wordBoundary` takes precedence).
+    #[serde(default, alias = "exfilWordBoundary")]
+    pub exfil_word_boundary: bool,
+    /// Phrases that `tool_output_injection` looks for in previous tool
+    /// outputs. Replaces the built-in list when non-empty.
+    #[serde(default, alias = "toolOutputPatterns")]
+    pub tool_output_patterns: Vec<String>,
     /// Policy rules for the policy pack plugin.
     #[serde(default)]
     pub policies: Vec<policy_pack::PolicyRule>,
+    /// Cap on the total number of policy rules; rules past it are dropped
+    /// with a warning at startup.
+    #[serde(default = "default_max_policy_rules", alias = "maxPolicyRules")]
+    pub max_policy_rules: usize,
     /// The company domain used for email bcc validation.  Defaults to
     /// `yourcompany.com`.
     #[serde(default = "default_company_domain")]
     pub company_domain: String,
+    /// Further domains treated like `company_domain` (e.g. a subsidiary's).
+    #[serde(default, alias = "companyDomains")]
+    pub company_domains: Vec<String>,
+    /// When true, addresses on any subdomain of `company_domain`
+    /// (e.g. `@eu.company.com` for `company.com`) count as internal.
+    #[serde(default, alias = "companyDomainMatchSubdomains")]
+    pub company_domain_match_subdomains: bool,
+    /// Compare addresses in canonical form (`util::canonical_email`:
+    /// `+tag` and trailing-dot variants fold together). Off by default.
+    #[serde(default, alias = "canonicalizeEmails")]
+    pub canonicalize_emails: bool,
+    /// Most recipients (`to` + `cc` + `bcc`) a mail tool may address before
+    /// `recipient_limit` blocks.
+    #[serde(default = "default_max_recipients", alias = "maxRecipients")]
+    pub max_recipients: usize,
+    /// Tool names `tool_denylist` always blocks (case-insensitive), e.g.
+    /// `ExecuteShell`.
+    #[serde(default, alias = "blockedTools")]
+    pub blocked_tools: Vec<String>,
+    /// Regexes a whole tool name must match for `tool_denylist` to block
+    /// (case-insensitive, e.g. `.*Shell.*`). Same limits as policy rules.
+    #[serde(default, alias = "blockedToolPatterns")]
+    pub blocked_tool_patterns: Vec<String>,
+    /// Substrings of an `outputParameters` name or type that mark the tool
+    /// as an exfil sink for `output_sink` (compared as lowercase
+    /// alphanumerics, so `webhook` also matches `WebhookUrl`).
+    #[serde(
+        default = "default_output_sink_indicators",
+        alias = "outputSinkIndicators"
+    )]
+    pub output_sink_indicators: Vec<String>,
     /// External HTTP plugin definitions. Each entry becomes an explicit plugin instance
     /// addressable by its unique `name` in the SENTRA_PLUGINS ordering variable.
     #[serde(default, alias = "externalHttp")]
     pub external_http: Vec<external_http::ExternalHttpDefinition>,
+    /// Request template for `externalHttp` entries without their own
+    /// `requestTemplate`; the built-in template applies when unset.
+    #[serde(default, alias = "externalHttpDefaultTemplate")]
+    pub external_http_default_template: Option<String>,
+    /// Upper bound on simultaneous outbound calls across all external HTTP
+    /// plugins (shared semaphore). `None` leaves outbound calls unbounded.
+    #[serde(default, alias = "externalMaxConcurrency")]
+    pub external_max_concurrency: Option<usize>,
+    /// Lets privileged automations skip selected plugins. Inert until
+    /// `trust.tokens` is configured.
+    #[serde(default)]
+    pub trust: TrustConfig,
+    /// Plugins rolled out in monitor mode: their blocks are logged but do
+    /// not decide, until `promote_after` clean evaluations switch them to
+    /// enforcing.
+    #[serde(default)]
+    pub monitor: MonitorConfig,
+    /// Treat a panicking plugin as a block (reason code 900) instead of
+    /// ignoring it.
+    #[serde(default, alias = "panicFailClosed")]
+    pub panic_fail_closed: bool,
+    /// Hard per-plugin time limits, keyed by plugin name. A plugin still
+    /// running at its limit is cut off; see `PluginTimeout`.
+    #[serde(default, alias = "pluginTimeouts")]
+    pub plugin_timeouts: std::collections::BTreeMap<String, PluginTimeout>,
+    /// Strip zero-width characters (and NFKC-fold, with the
+    /// `unicode-normalization` feature) before plugins scan request text.
+    #[serde(default, alias = "normalizeUnicode")]
+    pub normalize_unicode: bool,
+    /// Also scan `toolDefinition.description` and parameter descriptions
+    /// with the request text (`exfil`, `policy_pack` rules without `arg`).
+    #[serde(default, alias = "scanToolMetadata")]
+    pub scan_tool_metadata: bool,
+    /// Bearer tokens whose requests are allowed without running any plugin.
+    #[serde(default, alias = "bypassTokens")]
+    pub bypass_tokens: Vec<String>,
+    /// Agent tenants (`conversationMetadata.agent.tenantId`) whose requests
+    /// are allowed without running any plugin, keyed by the bearer token
+    /// allowed to claim them. The tenant id is caller-supplied, so a tenant
+    /// only bypasses when it arrives with one of its own tokens.
+    #[serde(default, alias = "bypassTenants")]
+    pub bypass_tenants: std::collections::BTreeMap<String, Vec<String>>,
+}
+
+/// Trust condition for privileged callers. A request is trusted only when
+/// BOTH hold: its bearer token is listed in `tokens`, and `inputValues`
+/// carries `field` set to boolean `true`. Trusted requests skip the plugins
+/// in `skip_plugins`; every other plugin still runs.
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct TrustConfig {
+    #[serde(default = "default_trust_field")]
+    pub field: String,
+    /// Bearer tokens allowed to assert trust (a subset of the accepted tokens).
+    #[serde(default)]
+    pub tokens: Vec<String>,
+    #[serde(default, alias = "skipPlugins")]
+    pub skip_plugins: Vec<String>,
+}
+
+fn default_trust_field() -> String {
+    "__sentra_trusted".to_owned()
+}
+
+impl Default for TrustConfig {
+    fn default() -> Self {
+        Self {
+            field: default_trust_field(),
+            tokens: Vec::new(),
+            skip_plugins: Vec::new(),
+        }
+    }
+}
+
+/// Time limit for one plugin (`pluginTimeouts.<name>`). On expiry the
+/// plugin abstains, or blocks with reason code 900 when `failClosed` is set.
+/// The limit can only interrupt a plugin at an `.await`, so it bounds I/O
+/// (external calls) rather than a tight CPU loop.
+#[derive(Clone, Debug, serde::Deserialize)]
+pub struct PluginTimeout {
+    pub ms: u64,
+    #[serde(default, alias = "failClosed")]
+    pub fail_closed: bool,
+}
+
+/// Monitor window for new plugins. A listed plugin runs normally, but a
+/// block from it is only logged (and reported in `Evaluation::monitored`)
+/// while it is in the window. After `promote_after` evaluations that did not
+/// panic it enforces like any other plugin; a panic restarts the count.
+/// Without `promote_after` the plugin stays in monitor mode.
+#[derive(Clone, Debug, Default, serde::Deserialize)]
+pub struct MonitorConfig {
+    #[serde(default)]
+    pub plugins: Vec<String>,
+    #[serde(default, alias = "promoteAfter")]
+    pub promote_after: Option<u64>,
+}
+
+impl PluginConfig {
+    /// Whether `req`, authenticated with `token`, comes from a caller on
+    /// `bypass_tokens`, or from a `bypass_tenants` tenant listed under that
+    /// same token.
+    pub fn bypasses(&self, req: &AnalyzeRequest, token: Option<&str>) -> bool {
+        let Some(token) = token else {
+            return false;
+        };
+        if self.bypass_tokens.iter().any(|t| t == token) {
+            return true;
+        }
+        let Some(tenants) = self.bypass_tenants.get(token) else {
+            return false;
+        };
+        let tenant = req
+            .conversation_metadata
+            .as_ref()
+            .and_then(|m| m.agent.as_ref())
+            .and_then(|a| a.tenant_id.as_deref())
+            .map(str::trim)
+            .filter(|t| !t.is_empty());
+        tenant.is_some_and(|tenant| tenants.iter().any(|t| t == tenant))
+    }
+}
+
+impl TrustConfig {
+    /// Whether `req`, authenticated with `token`, satisfies the trust condition.
+    pub fn is_trusted(&self, req: &AnalyzeRequest, token: Option<&str>) -> bool {
+        let Some(token) = token else {
+            return false;
+        };
+        self.tokens.iter().any(|t| t == token)
+            && req.input_values.get(&self.field) == Some(&serde_json::Value::Bool(true))
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_max_recipients() -> usize {
+    25
+}
+
+/// Default cap on the total number of policy rules (`maxPolicyRules`).
+pub const DEFAULT_MAX_POLICY_RULES: usize = 1000;
+
+fn default_max_policy_rules() -> usize {
+    DEFAULT_MAX_POLICY_RULES
+}
+
+fn default_output_sink_indicators() -> Vec<String> {
+    ["external_url", "webhook", "callback_url", "upload_url"]
+        .into_iter()
+        .map(String::from)
+        .collect()
 }
 
+/// Placeholder used when `company_domain` is not configured. Startup warns
+/// (or fails with `SENTRA_REQUIRE_COMPANY_DOMAIN`) if it is still in effect.
+pub const DEFAULT_COMPANY_DOMAIN: &str = "yourcompany.com";
+
 fn default_company_domain() -> String {
-    // Default company domain; explicit String from &str
-    "yourcompany.com".to_owned()
+    DEFAULT_COMPANY_DOMAIN.to_owned()
 }
 
 impl Default for PluginConfig {
     fn default() -> Self {
         Self {
             pii_keywords: Vec::new(),
+            pii_keyword_regex: Vec::new(),
             domain_blocklist: Vec::new(),
+            domain_host_only: false,
+            block_private_ips: false,
+            block_ip_literals: false,
+            iban_checksum: true,
+            secrets_aws_strict: false,
+            diagnostics_level: DiagnosticsLevel::default(),
+            exfil_patterns: Vec::new(),
+            exfil_word_boundary: false,
+            tool_output_patterns: Vec::new(),
             policies: Vec::new(),
+            max_policy_rules: default_max_policy_rules(),
             company_domain: default_company_domain(),
+            company_domains: Vec::new(),
+            company_domain_match_subdomains: false,
+            canonicalize_emails: false,
+            max_recipients: default_max_recipients(),
+            blocked_tools: Vec::new(),
+            blocked_tool_patterns: Vec::new(),
+            output_sink_indicators: default_output_sink_indicators(),
             external_http: Vec::new(),
+            external_http_default_template: None,
+            external_max_concurrency: None,
+            trust: TrustConfig::default(),
+            monitor: MonitorConfig::default(),
+            panic_fail_closed: false,
+            plugin_timeouts: Default::default(),
+            normalize_unicode: false,
+            scan_tool_metadata: false,
+            bypass_tokens: Vec::new(),
+            bypass_tenants: Default::default(),
         }
     }
 }
 
+impl PluginConfig {
+    /// Returns true if `email` belongs to `company_domain` or one of
+    /// `company_domains`. The comparison is case-insensitive; subdomains
+    /// match only with `company_domain_match_subdomains`, and with
+    /// `canonicalize_emails` the address is canonicalized first.
+    pub fn is_company_email(&self, email: &str) -> bool {
+        let canonical;
+        let email = if self.canonicalize_emails {
+            canonical = crate::util::canonical_email(email);
+            canonical.as_str()
+        } else {
+            email
+        };
+        let Some((_, domain)) = email.rsplit_once('@') else {
+            return false;
+        };
+        let domain = domain.to_lowercase();
+        std::iter::once(&self.company_domain)
+            .chain(&self.company_domains)
+            .any(|company| {
+                let company = company.to_lowercase();
+                domain == company
+                    || self.company_domain_match_subdomains
+                        && domain
+                            .strip_suffix(company.as_str())
+                            .is_some_and(|prefix| prefix.ends_with('.'))
+            })
+    }
+}
+
+/// Make a plugin verdict safe to return and log, whatever its origin:
+/// control characters in `reason`, `blockedBy` and string diagnostics become
+/// spaces (so nothing can forge extra log lines), and `reason` is capped at
+/// `util::MAX_REASON_CHARS`.
+fn sanitize_response(resp: &mut AnalyzeResponse) {
+    fn clean(value: &mut serde_json::Value) {
+        match value {
+            serde_json::Value::String(s) => *s = crate::util::sanitize_text(s, usize::MAX),
+            serde_json::Value::Array(items) => items.iter_mut().for_each(clean),
+            serde_json::Value::Object(map) => map.values_mut().for_each(clean),
+            _ => {}
+        }
+    }
+    if let Some(reason) = &mut resp.reason {
+        *reason = crate::util::sanitize_text(reason, crate::util::MAX_REASON_CHARS);
+    }
+    if let Some(blocked_by) = &mut resp.blocked_by {
+        *blocked_by = crate::util::sanitize_text(blocked_by, usize::MAX);
+    }
+    if let Some(diagnostics) = &mut resp.diagnostics {
+        clean(diagnostics);
+    }
+}
+
+/// True for tools whose name mentions mail (`SendEmail`, `mail_merge`, ...);
+/// the scope of the mail-specific plugins.
+pub(crate) fn is_mail_tool(req: &AnalyzeRequest) -> bool {
+    let tool_name = req
+        .tool_definition
+        .name
+        .as_deref()
+        .unwrap_or("")
+        .to_lowercase();
+    tool_name.contains("mail")
+}
+
+/// Version of the `diagnostics` object contract (see DIAGNOSTICS.md). Bump
+/// only when an existing field changes meaning or type.
+pub const DIAGNOSTICS_SCHEMA_VERSION: u32 = 1;
+
+/// Builder for block diagnostics. Always carries `schemaVersion`, `plugin`
+/// and `code`; `detail`, `field` and `offsets` are optional documented
+/// members and plugins may attach further keys via `with`.
+#[derive(Clone, Debug)]
+pub struct Diagnostics {
+    map: serde_json::Map<String, serde_json::Value>,
+    level: DiagnosticsLevel,
+}
+
+impl Diagnostics {
+    pub fn new(plugin: &str, code: &str) -> Self {
+        let mut map = serde_json::Map::new();
+        map.insert(
+            "schemaVersion".into(),
+            serde_json::Value::from(DIAGNOSTICS_SCHEMA_VERSION),
+        );
+        map.insert("plugin".into(), serde_json::Value::from(plugin));
+        map.insert("code".into(), serde_json::Value::from(code));
+        Self {
+            map,
+            level: DiagnosticsLevel::default(),
+        }
+    }
+
+    /// Diagnostics shaped by `cfg.diagnostics_level`; what plugins use to
+    /// build their blocks.
+    pub fn for_config(plugin: &str, code: &str, cfg: &PluginConfig) -> Self {
+        Self::new(plugin, code).level(cfg.diagnostics_level)
+    }
+
+    /// Drop members above `level` from here on (see `DiagnosticsLevel`).
+    pub fn level(mut self, level: DiagnosticsLevel) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Short contextual snippet (may be truncated by the plugin). Omitted at
+    /// `minimal`.
+    pub fn detail(self, detail: impl Into<String>) -> Self {
+        self.with("detail", serde_json::Value::String(detail.into()))
+    }
+
+    /// Input field (argument name) the match was found in. Only at
+    /// `verbose`.
+    pub fn field(self, field: impl Into<String>) -> Self {
+        if self.level != DiagnosticsLevel::Verbose {
+            return self;
+        }
+        self.with("field", serde_json::Value::String(field.into()))
+    }
+
+    /// Byte offsets `[start, end)` of the match within the scanned text.
+    /// Only at `verbose`.
+    pub fn offsets(self, start: usize, end: usize) -> Self {
+        if self.level != DiagnosticsLevel::Verbose {
+            return self;
+        }
+        self.with("offsets", serde_json::json!([start, end]))
+    }
+
+    /// Plugin-specific extension member. Omitted at `minimal`.
+    pub fn with(mut self, key: &str, value: serde_json::Value) -> Self {
+        if self.level == DiagnosticsLevel::Minimal {
+            return self;
+        }
+        self.map.insert(key.into(), value);
+        self
+    }
+}
+
+impl From<Diagnostics> for serde_json::Value {
+    fn from(d: Diagnostics) -> Self {
+        serde_json::Value::Object(d.map)
+    }
+}
+
+/// How much plugins attach to block diagnostics (`SENTRA_DIAGNOSTICS_LEVEL`).
+/// Applied at construction, so telemetry and audit records match the
+/// response.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagnosticsLevel {
+    /// Only the contract members: `schemaVersion`, `plugin`, `code`.
+    #[serde(alias = "compact")]
+    Minimal,
+    /// `detail` and plugin members, but no `offsets` or `field`.
+    Standard,
+    /// Everything, including `offsets` and `field`.
+    #[default]
+    Verbose,
+}
+
+impl DiagnosticsLevel {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "minimal" => Some(DiagnosticsLevel::Minimal),
+            "standard" => Some(DiagnosticsLevel::Standard),
+            "verbose" => Some(DiagnosticsLevel::Verbose),
+            _ => None,
+        }
+    }
+
+    /// `SENTRA_DIAGNOSTICS_MODE` values: `compact` is `Minimal`, `verbose`
+    /// is `Verbose`.
+    pub fn parse_mode(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "compact" => Some(DiagnosticsLevel::Minimal),
+            "verbose" => Some(DiagnosticsLevel::Verbose),
+            _ => None,
+        }
+    }
+}
+
+/// The contract members (`schemaVersion`, `plugin`, `code`) of already built
+/// `diagnostics`; anything that is not an object yields an empty map.
+#[cfg(not(feature = "wasm"))]
+pub(crate) fn contract_members(
+    diagnostics: serde_json::Value,
+) -> serde_json::Map<String, serde_json::Value> {
+    match diagnostics {
+        serde_json::Value::Object(mut map) => {
+            map.retain(|k, _| matches!(k.as_str(), "schemaVersion" | "plugin" | "code"));
+            map
+        }
+        _ => serde_json::Map::new(),
+    }
+}
+
 /// Trait implemented by all plugins.  Given a request and evaluation
 /// context, return `Some(AnalyzeResponse)` to indicate a block or
 /// transformation.  Returning `None` means the plugin has no opinion and
@@ -73,6 +555,14 @@ fn default() -> Self {
 #[async_trait::async_trait]
 pub trait Plugin: Send + Sync {
     fn name(&self) -> &str;
+
+    /// Cheap synchronous pre-check. Returning false means the plugin has
+    /// nothing to do for this request; the pipeline then skips `eval` and
+    /// records a 0ms timing.
+    fn applies(&self, _req: &AnalyzeRequest, _ctx: &EvalContext) -> bool {
+        true
+    }
+
     async fn eval(
         &self,
         req: &AnalyzeRequest,
@@ -86,11 +576,120 @@ async fn eval(
 #[derive(Clone)]
 pub struct PluginPipeline {
     plugins: Vec<Arc<dyn Plugin>>,
+    /// Clean evaluations per monitored plugin, shared by clones.
+    monitor_evals: Arc<dashmap::DashMap<String, u64>>,
+}
+
+/// Outcome of one pipeline pass.
+#[derive(Debug)]
+pub struct Evaluation {
+    /// The decision: first block, otherwise allow.
+    pub response: AnalyzeResponse,
+    /// `(plugin, elapsed ms)` for every plugin that ran, in order.
+    pub timings: Vec<(String, u64)>,
+    /// What each plugin in `timings` did (same order and length).
+    pub outcomes: Vec<PluginOutcome>,
+    /// Every blocking response when not stopping at the first block.
+    pub signals: Vec<AnalyzeResponse>,
+    /// Plugins that returned an explicit non-blocking response (as opposed
+    /// to abstaining with `None`).
+    pub allowed: Vec<String>,
+    /// Plugins never run because the deadline expired first.
+    pub skipped: Vec<String>,
+    /// Plugins not run because the request was trusted (`PluginConfig::trust`);
+    /// also listed in `timings` with a `Skipped` outcome.
+    pub trusted_skips: Vec<String>,
+    /// Plugins whose `eval` panicked.
+    pub panicked: Vec<String>,
+    /// Plugins cut off by their `pluginTimeouts` limit.
+    pub timed_out: Vec<String>,
+    /// Blocks from plugins still in their monitor window; these never
+    /// decide.
+    pub monitored: Vec<AnalyzeResponse>,
+}
+
+impl Evaluation {
+    /// A pass in which no plugin ran: allow, nothing recorded.
+    pub fn allow() -> Self {
+        Self {
+            response: AnalyzeResponse {
+                block_action: false,
+                reason_code: None,
+                reason: None,
+                blocked_by: None,
+                diagnostics: None,
+            },
+            timings: Vec::new(),
+            outcomes: Vec::new(),
+            signals: Vec::new(),
+            allowed: Vec::new(),
+            skipped: Vec::new(),
+            trusted_skips: Vec::new(),
+            panicked: Vec::new(),
+            timed_out: Vec::new(),
+            monitored: Vec::new(),
+        }
+    }
+}
+
+/// What one plugin did during a pipeline pass.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PluginOutcome {
+    /// Ran without blocking (explicit allow or nothing found).
+    Allow,
+    /// Returned a blocking response.
+    Block,
+    /// Would have blocked, but the plugin is still in its monitor window
+    /// (see `Evaluation::monitored`).
+    Monitored,
+    /// `eval` panicked.
+    Error,
+    /// `eval` was cut off by the plugin's `pluginTimeouts` limit.
+    Timeout,
+    /// `eval` never ran: `applies` was false, or the request was trusted
+    /// (see `Evaluation::trusted_skips`).
+    Skipped,
+}
+
+/// Decision plus per-plugin timings, as returned by
+/// `PluginPipeline::evaluate_with_timings`. Serializes as
+/// `{"response": {...}, "timings": [{"name": "secrets", "ms": 3, "outcome": "block"}]}`.
+#[derive(Clone, Debug, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvaluationResult {
+    pub response: AnalyzeResponse,
+    /// Every plugin that ran, in order.
+    pub timings: Vec<PluginTiming>,
+}
+
+/// Time one plugin spent in `eval` (0 when it did not apply).
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize)]
+pub struct PluginTiming {
+    pub name: String,
+    pub ms: u64,
+    pub outcome: PluginOutcome,
+}
+
+impl From<Evaluation> for EvaluationResult {
+    fn from(eval: Evaluation) -> Self {
+        EvaluationResult {
+            response: eval.response,
+            timings: eval
+                .timings
+                .into_iter()
+                .zip(eval.outcomes)
+                .map(|((name, ms), outcome)| PluginTiming { name, ms, outcome })
+                .collect(),
+        }
+    }
 }
 
 struct PluginRun {
     response: Option<AnalyzeResponse>,
     elapsed_ms: u64,
+    panicked: bool,
+    timed_out: bool,
 }
 
 impl PluginPipeline {
@@ -98,77 +697,294 @@ pub fn new(order: &[String], cfg: &PluginConfig) -> Self {
         // Map string names to plugin implementations.  Unknown names are
         // silently ignored.
         let mut plugins: Vec<Arc<dyn Plugin>> = Vec::new();
+        // One limiter shared by every external plugin so the cap bounds total
+        // outbound fan-out rather than per-plugin concurrency.
+        #[cfg(feature = "external-http")]
+        let external_limiter = cfg
+            .external_max_concurrency
+            .filter(|n| *n > 0)
+            .map(|n| Arc::new(tokio::sync::Semaphore::new(n)));
+        let mut seen = std::collections::HashSet::new();
         for name in order {
+            if !seen.insert(name.as_str()) {
+                // Per-plugin metrics are keyed by name; a second instance
+                // would double-count evaluations under the same series.
+                tracing::warn!(plugin = %name, "duplicate plugin name in order, skipping");
+                continue;
+            }
             match name.as_str() {
-                "exfil" => plugins.push(Arc::new(ExfilPlugin {})),
+                "exfil" => plugins.push(Arc::new(ExfilPlugin::new(cfg))),
                 "secrets" => plugins.push(Arc::new(SecretsPlugin {})),
-                "pii" => plugins.push(Arc::new(PiiPlugin {})),
+                "pii" => plugins.push(Arc::new(PiiPlugin::new(cfg))),
                 "email_bcc" => plugins.push(Arc::new(EmailBccPlugin {})),
                 "domain_block" => plugins.push(Arc::new(DomainBlockPlugin {})),
+                "base64_decode" => plugins.push(Arc::new(Base64DecodePlugin::new(cfg))),
+                "recipient_limit" => plugins.push(Arc::new(RecipientLimitPlugin {})),
+                "tool_output_injection" => plugins.push(Arc::new(ToolOutputInjectionPlugin {})),
+                "role_injection" => plugins.push(Arc::new(RoleInjectionPlugin {})),
+                "output_sink" => plugins.push(Arc::new(OutputSinkPlugin::new(cfg))),
+                "tool_denylist" => {
+                    if !cfg.blocked_tools.is_empty() || !cfg.blocked_tool_patterns.is_empty() {
+                        plugins.push(Arc::new(ToolDenylistPlugin::new(cfg)));
+                    }
+                }
                 "policy_pack" => {
                     if !cfg.policies.is_empty() {
-                        plugins.push(Arc::new(PolicyPackPlugin::new(cfg.policies.clone())));
+                        plugins.push(Arc::new(PolicyPackPlugin::with_max_rules(
+                            cfg.policies.clone(),
+                            cfg.max_policy_rules,
+                        )));
                     }
                 }
+                #[cfg(feature = "external-http")]
                 name if name.starts_with("external_") => {
                     // Look up external http definition by exact name match
                     if let Some(def) = cfg.external_http.iter().find(|d| d.name == *name) {
-                        plugins.push(Arc::new(ExternalHttpPlugin::new(def.clone())));
+                        // A broken definition disables that plugin only; the
+                        // rest of the pipeline still starts.
+                        match ExternalHttpPlugin::new(def.clone()) {
+                            Ok(plugin) => {
+                                let mut plugin = plugin.with_default_template(
+                                    cfg.external_http_default_template.clone(),
+                                );
+                                if let Some(limiter) = &external_limiter {
+                                    plugin = plugin.with_limiter(limiter.clone());
+                                }
+                                plugins.push(Arc::new(plugin));
+                            }
+                            Err(err) => {
+                                tracing::error!(plugin = %name, error = %err, "failed to build external_http client, skipping");
+                            }
+                        }
                     } else {
                         tracing::warn!(plugin=%name, "external_http definition not found");
                     }
                 }
+                #[cfg(not(feature = "external-http"))]
+                name if name.starts_with("external_") => {
+                    tracing::warn!(plugin = %name, "built without the external-http feature, skipping");
+                }
                 _ => {
                     tracing::warn!(plugin = %name, "unknown plugin name, skipping");
                 }
             }
         }
-        Self { plugins }
+        Self::from_unique(plugins)
     }
 
-    /// Evaluate all plugins against the request and context.  Returns an
-    /// `AnalyzeResponse` where `blockAction` indicates whether the tool
-    /// invocation should be blocked.  Stops at first blocking plugin.
+    /// Pipeline over caller-supplied plugins, run in the given order. Lets
+    /// embedders and tests register their own `Plugin` implementations
+    /// without going through `SENTRA_PLUGINS`. Repeated names are dropped
+    /// as in `new`.
+    pub fn from_plugins(plugins: Vec<Arc<dyn Plugin>>) -> Self {
+        let mut seen = std::collections::HashSet::new();
+        let plugins = plugins
+            .into_iter()
+            .filter(|plugin| {
+                let fresh = seen.insert(plugin.name().to_string());
+                if !fresh {
+                    tracing::warn!(plugin = %plugin.name(), "duplicate plugin name, skipping");
+                }
+                fresh
+            })
+            .collect();
+        Self::from_unique(plugins)
+    }
+
+    fn from_unique(plugins: Vec<Arc<dyn Plugin>>) -> Self {
+        Self {
+            plugins,
+            monitor_evals: Arc::default(),
+        }
+    }
+
+    /// Evaluate all plugins against the request and context.  The result's
+    /// `response.blockAction` indicates whether the tool invocation should be
+    /// blocked.  Stops at first blocking plugin.
     pub async fn evaluate_with_timings(
         &self,
         req: &AnalyzeRequest,
         ctx: &EvalContext,
         cfg: &PluginConfig,
+    ) -> EvaluationResult {
+        self.evaluate(req, ctx, cfg, true).await.into()
+    }
+
+    /// `evaluate_with_timings` in its former tuple shape.
+    #[deprecated(note = "use `evaluate_with_timings`, which returns `EvaluationResult`")]
+    pub async fn evaluate_with_timings_tuple(
+        &self,
+        req: &AnalyzeRequest,
+        ctx: &EvalContext,
+        cfg: &PluginConfig,
     ) -> (AnalyzeResponse, Vec<(String, u64)>) {
+        let eval = self.evaluate(req, ctx, cfg, true).await;
+        (eval.response, eval.timings)
+    }
+
+    /// Like `evaluate_with_timings` but keeps running the remaining plugins
+    /// after a block (budget permitting). The first block is still the
+    /// decision; every blocking response, in order, is returned as well.
+    pub async fn evaluate_all_with_timings(
+        &self,
+        req: &AnalyzeRequest,
+        ctx: &EvalContext,
+        cfg: &PluginConfig,
+    ) -> (AnalyzeResponse, Vec<(String, u64)>, Vec<AnalyzeResponse>) {
+        let eval = self.evaluate(req, ctx, cfg, false).await;
+        (eval.response, eval.timings, eval.signals)
+    }
+
+    /// Full evaluation record; `stop_at_first` selects between the two
+    /// modes above.
+    pub async fn evaluate(
+        &self,
+        req: &AnalyzeRequest,
+        ctx: &EvalContext,
+        cfg: &PluginConfig,
+        stop_at_first: bool,
+    ) -> Evaluation {
         let mut timings: Vec<(String, u64)> = Vec::new();
-        for plugin in &self.plugins {
+        let mut outcomes: Vec<PluginOutcome> = Vec::new();
+        let mut signals: Vec<AnalyzeResponse> = Vec::new();
+        let mut allowed: Vec<String> = Vec::new();
+        let mut skipped: Vec<String> = Vec::new();
+        let mut trusted_skips: Vec<String> = Vec::new();
+        let mut panicked: Vec<String> = Vec::new();
+        let mut timed_out: Vec<String> = Vec::new();
+        let mut monitored: Vec<AnalyzeResponse> = Vec::new();
+        for (idx, plugin) in self.plugins.iter().enumerate() {
             let pname = plugin.name();
             if ctx.deadline.exceeded() {
+                skipped = self.plugins[idx..]
+                    .iter()
+                    .map(|p| p.name().to_string())
+                    .collect();
                 tracing::warn!(
                     plugin_count = self.plugins.len(),
+                    skipped = ?skipped,
                     "deadline exceeded, aborting further plugin checks"
                 );
                 break;
             }
+            if ctx.trusted && cfg.trust.skip_plugins.iter().any(|p| p == pname) {
+                tracing::debug!(plugin = %pname, "trusted request, skipping plugin");
+                timings.push((pname.to_string(), 0));
+                outcomes.push(PluginOutcome::Skipped);
+                trusted_skips.push(pname.to_string());
+                continue;
+            }
+            if !plugin.applies(req, ctx) {
+                tracing::trace!(plugin = %pname, "plugin does not apply, skipping");
+                timings.push((pname.to_string(), 0));
+                outcomes.push(PluginOutcome::Skipped);
+                continue;
+            }
             tracing::trace!(plugin = %pname, remaining_ms = ctx.deadline.remaining_ms(), "evaluating plugin");
             let run = Self::run_plugin(plugin, req, ctx, cfg, pname).await;
             timings.push((pname.to_string(), run.elapsed_ms));
+            let monitoring = self.record_monitor_eval(pname, cfg, run.panicked);
+            outcomes.push(if run.panicked {
+                PluginOutcome::Error
+            } else if run.timed_out {
+                PluginOutcome::Timeout
+            } else if run.response.as_ref().is_some_and(|r| r.block_action) {
+                if monitoring {
+                    PluginOutcome::Monitored
+                } else {
+                    PluginOutcome::Block
+                }
+            } else {
+                PluginOutcome::Allow
+            });
+            if run.panicked {
+                panicked.push(pname.to_string());
+            }
+            if run.timed_out {
+                timed_out.push(pname.to_string());
+            }
             if let Some(mut resp) = run.response {
+                sanitize_response(&mut resp);
                 if resp.block_action {
-                    tracing::info!(plugin = %pname, reason_code = ?resp.reason_code, "blocking");
                     if resp.blocked_by.is_none() {
                         resp.blocked_by = Some(pname.to_string());
                     }
-                    return (resp, timings);
+                    if monitoring {
+                        tracing::info!(plugin = %pname, reason_code = ?resp.reason_code, "monitor mode, would block");
+                        monitored.push(resp);
+                        continue;
+                    }
+                    tracing::info!(plugin = %pname, reason_code = ?resp.reason_code, "blocking");
+                    if stop_at_first {
+                        return Evaluation {
+                            response: resp,
+                            timings,
+                            outcomes,
+                            signals,
+                            allowed,
+                            skipped,
+                            trusted_skips,
+                            panicked,
+                            timed_out,
+                            monitored,
+                        };
+                    }
+                    signals.push(resp);
+                    continue;
                 }
                 tracing::debug!(plugin = %pname, "plugin allowed");
+                allowed.push(pname.to_string());
             }
         }
-        (
-            AnalyzeResponse {
-                block_action: false,
-                reason_code: None,
-                reason: None,
-                blocked_by: None,
-                diagnostics: None,
-            },
+        let response = signals.first().cloned().unwrap_or(AnalyzeResponse {
+            block_action: false,
+            reason_code: None,
+            reason: None,
+            blocked_by: None,
+            diagnostics: None,
+        });
+        Evaluation {
+            response,
             timings,
-        )
+            outcomes,
+            signals,
+            allowed,
+            skipped,
+            trusted_skips,
+            panicked,
+            timed_out,
+            monitored,
+        }
+    }
+
+    /// Count one evaluation of `name` against its monitor window and report
+    /// whether the plugin was still monitoring during it. Plugins not listed
+    /// in `cfg.monitor` always enforce.
+    fn record_monitor_eval(&self, name: &str, cfg: &PluginConfig, panicked: bool) -> bool {
+        if !cfg.monitor.plugins.iter().any(|p| p == name) {
+            return false;
+        }
+        let Some(limit) = cfg.monitor.promote_after else {
+            return true;
+        };
+        let mut evals = self.monitor_evals.entry(name.to_string()).or_insert(0);
+        if *evals >= limit {
+            return false;
+        }
+        if panicked {
+            *evals = 0;
+        } else {
+            *evals += 1;
+            if *evals == limit {
+                tracing::info!(plugin = %name, evaluations = limit, "monitor window complete, plugin now enforcing");
+            }
+        }
+        true
+    }
+
+    /// Names of the registered plugins, in evaluation order.
+    pub fn names(&self) -> Vec<&str> {
+        self.plugins.iter().map(|p| p.name()).collect()
     }
 
     /// Number of registered plugins.
@@ -189,8 +1005,51 @@ async fn run_plugin(
         name: &str,
     ) -> PluginRun {
         let start = std::time::Instant::now();
-        let response = plugin.eval(req, ctx, cfg).await;
+        // A panicking plugin must not take the request down with it.
+        let eval = AssertUnwindSafe(plugin.eval(req, ctx, cfg)).catch_unwind();
+        // Timers need a runtime; runtime-free local evaluation has no limits.
+        #[cfg(not(feature = "wasm"))]
+        let limit = cfg
+            .plugin_timeouts
+            .get(name)
+            .filter(|_| tokio::runtime::Handle::try_current().is_ok());
+        #[cfg(feature = "wasm")]
+        let limit: Option<&PluginTimeout> = None;
+        let outcome = match limit {
+            #[cfg(not(feature = "wasm"))]
+            Some(limit) => {
+                match tokio::time::timeout(std::time::Duration::from_millis(limit.ms), eval).await {
+                    Ok(outcome) => outcome,
+                    Err(_) => return Self::timed_out(name, limit, cfg, start),
+                }
+            }
+            _ => eval.await,
+        };
         let elapsed_ms = start.elapsed().as_millis() as u64;
+        let panicked = outcome.is_err();
+        let response = match outcome {
+            Ok(response) => response,
+            Err(payload) => {
+                let message = payload
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("non-string panic payload");
+                tracing::error!(
+                    plugin = %name,
+                    panic = %message,
+                    fail_closed = cfg.panic_fail_closed,
+                    "plugin panicked"
+                );
+                cfg.panic_fail_closed.then(|| AnalyzeResponse {
+                    block_action: true,
+                    reason_code: Some(900),
+                    reason: Some("Plugin failure (fail-closed)".into()),
+                    blocked_by: Some(name.to_string()),
+                    diagnostics: Some(Diagnostics::for_config(name, "panic", cfg).into()),
+                })
+            }
+        };
         if elapsed_ms > ctx.plugin_warn_ms {
             tracing::warn!(
                 plugin = %name,
@@ -202,13 +1061,50 @@ async fn run_plugin(
         PluginRun {
             response,
             elapsed_ms,
+            panicked,
+            timed_out: false,
+        }
+    }
+
+    /// Run record for a plugin cut off by its `pluginTimeouts` entry.
+    #[cfg(not(feature = "wasm"))]
+    fn timed_out(
+        name: &str,
+        limit: &PluginTimeout,
+        cfg: &PluginConfig,
+        start: std::time::Instant,
+    ) -> PluginRun {
+        let elapsed_ms = start.elapsed().as_millis() as u64;
+        tracing::warn!(
+            plugin = %name,
+            elapsed_ms,
+            timeout_ms = limit.ms,
+            fail_closed = limit.fail_closed,
+            "plugin timed out"
+        );
+        PluginRun {
+            response: limit.fail_closed.then(|| AnalyzeResponse {
+                block_action: true,
+                reason_code: Some(900),
+                reason: Some("Plugin failure (fail-closed)".into()),
+                blocked_by: Some(name.to_string()),
+                diagnostics: Some(
+                    Diagnostics::for_config(name, "timeout", cfg)
+                        .with("timeoutMs", limit.ms.into())
+                        .into(),
+                ),
+            }),
+            elapsed_ms,
+            panicked: false,
+            timed_out: true,
         }
     }
 }
 
 /// Helper used by `build_state_from_env` to parse the list of plugin
 /// identifiers from an environment variable.  If unset, a default list is
-/// returned.  Strings are trimmed and lower‑cased.
+/// returned.  Strings are trimmed and lower‑cased; repeated names are left
+/// for `PluginPipeline::new` to drop.
 pub fn parse_plugin_order() -> Vec<String> {
     if let Ok(var) = std::env::var("SENTRA_PLUGINS") {
         var.split(',')
@@ -216,13 +1112,132 @@ pub fn parse_plugin_order() -> Vec<String> {
             .filter(|s| !s.is_empty())
             .collect()
     } else {
-        vec![
-            "exfil".into(),
-            "secrets".into(),
-            "email_bcc".into(),
-            "pii".into(),
-            "domain_block".into(),
-            "policy_pack".into(),
-        ]
+        default_plugin_order()
+    }
+}
+
+/// Plugin order used when `SENTRA_PLUGINS` is unset.
+pub fn default_plugin_order() -> Vec<String> {
+    vec![
+        "tool_denylist".into(),
+        "exfil".into(),
+        "secrets".into(),
+        "email_bcc".into(),
+        "pii".into(),
+        "domain_block".into(),
+        "policy_pack".into(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// `email_bcc` with an eval call counter.
+    struct CountingBcc {
+        inner: EmailBccPlugin,
+        evals: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl Plugin for CountingBcc {
+        fn name(&self) -> &str {
+            self.inner.name()
+        }
+
+        fn applies(&self, req: &AnalyzeRequest, ctx: &EvalContext) -> bool {
+            self.inner.applies(req, ctx)
+        }
+
+        async fn eval(
+            &self,
+            req: &AnalyzeRequest,
+            ctx: &EvalContext,
+            cfg: &PluginConfig,
+        ) -> Option<AnalyzeResponse> {
+            self.evals.fetch_add(1, Ordering::SeqCst);
+            self.inner.eval(req, ctx, cfg).await
+        }
+    }
+
+    fn request(tool: &str) -> AnalyzeRequest {
+        serde_json::from_value(serde_json::json!({
+            "plannerContext": { "userMessage": "hi" },
+            "toolDefinition": { "name": tool },
+            "inputValues": { "bcc": "someone@elsewhere.com" }
+        }))
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn pipeline_skips_eval_when_plugin_does_not_apply() {
+        let cfg = PluginConfig::default();
+        let plugin = Arc::new(CountingBcc {
+            inner: EmailBccPlugin,
+            evals: AtomicUsize::new(0),
+        });
+        let pipeline = PluginPipeline::from_unique(vec![plugin.clone()]);
+
+        let req = request("GetWeather");
+        let ctx = EvalContext::from_request(&req, &cfg, 900, 200);
+        assert!(!EmailBccPlugin.applies(&req, &ctx));
+        let result = pipeline.evaluate_with_timings(&req, &ctx, &cfg).await;
+        assert!(!result.response.block_action);
+        assert_eq!(
+            result.timings,
+            vec![PluginTiming {
+                name: "email_bcc".to_string(),
+                ms: 0,
+                outcome: PluginOutcome::Skipped,
+            }]
+        );
+        assert_eq!(plugin.evals.load(Ordering::SeqCst), 0);
+
+        let req = request("SendEmail");
+        let ctx = EvalContext::from_request(&req, &cfg, 900, 200);
+        let resp = pipeline
+            .evaluate_with_timings(&req, &ctx, &cfg)
+            .await
+            .response;
+        assert!(resp.block_action);
+        assert_eq!(plugin.evals.load(Ordering::SeqCst), 1);
+    }
+
+    struct PanickingPlugin;
+
+    #[async_trait::async_trait]
+    impl Plugin for PanickingPlugin {
+        fn name(&self) -> &str {
+            "panicky"
+        }
+
+        async fn eval(
+            &self,
+            _req: &AnalyzeRequest,
+            _ctx: &EvalContext,
+            _cfg: &PluginConfig,
+        ) -> Option<AnalyzeResponse> {
+            panic!("boom");
+        }
+    }
+
+    #[tokio::test]
+    async fn panicking_plugin_fails_open_or_closed_per_config() {
+        let pipeline =
+            PluginPipeline::from_unique(vec![Arc::new(PanickingPlugin), Arc::new(EmailBccPlugin)]);
+        let req = request("SendEmail");
+        let mut cfg = PluginConfig::default();
+        let ctx = EvalContext::from_request(&req, &cfg, 900, 200);
+
+        let eval = pipeline.evaluate(&req, &ctx, &cfg, true).await;
+        assert_eq!(eval.panicked, vec!["panicky".to_string()]);
+        assert_eq!(eval.response.blocked_by.as_deref(), Some("email_bcc"));
+
+        cfg.panic_fail_closed = true;
+        let eval = pipeline.evaluate(&req, &ctx, &cfg, true).await;
+        assert_eq!(eval.panicked, vec!["panicky".to_string()]);
+        assert_eq!(eval.response.blocked_by.as_deref(), Some("panicky"));
+        assert_eq!(eval.response.reason_code, Some(900));
     }
 }
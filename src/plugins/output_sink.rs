@@ -0,0 +1,113 @@
+use super::{Diagnostics, Plugin, PluginConfig};
+use crate::util::EvalContext;
+use crate::{AnalyzeRequest, AnalyzeResponse, ToolParam};
+
+/// Blocks tools whose declared `outputParameters` look like an exfil sink:
+/// a parameter name or type containing one of `outputSinkIndicators`
+/// (e.g. `external_url`, `webhook`). Names are compared folded to lowercase
+/// alphanumerics, so `webhookUrl`, `Webhook-URL` and `webhook_url` all match
+/// `webhook`.
+pub struct OutputSinkPlugin {
+    /// Indicator as configured and its folded form.
+    indicators: Vec<(String, String)>,
+}
+
+/// Lowercase alphanumerics only.
+fn fold(text: &str) -> String {
+    text.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Strings describing a parameter: its name, plus its type when that is a
+/// string or an object of strings (e.g. `{"$kind": "WebhookUrl"}`).
+fn descriptors(param: &ToolParam) -> Vec<&str> {
+    let mut out = vec![param.name.as_str()];
+    match &param.param_type {
+        Some(serde_json::Value::String(ty)) => out.push(ty),
+        Some(serde_json::Value::Object(map)) => {
+            out.extend(map.values().filter_map(|v| v.as_str()));
+        }
+        _ => {}
+    }
+    out
+}
+
+impl OutputSinkPlugin {
+    pub fn new(cfg: &PluginConfig) -> Self {
+        let indicators = cfg
+            .output_sink_indicators
+            .iter()
+            .map(|i| (i.clone(), fold(i)))
+            .filter(|(_, folded)| !folded.is_empty())
+            .collect();
+        Self { indicators }
+    }
+
+    /// First declared output parameter matching an indicator, with the
+    /// indicator as configured.
+    fn find<'a>(&'a self, params: &'a [ToolParam]) -> Option<(&'a ToolParam, &'a str)> {
+        params.iter().find_map(|param| {
+            let folded: Vec<String> = descriptors(param).into_iter().map(fold).collect();
+            self.indicators
+                .iter()
+                .find(|(_, ind)| folded.iter().any(|d| d.contains(ind.as_str())))
+                .map(|(source, _)| (param, source.as_str()))
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for OutputSinkPlugin {
+    fn name(&self) -> &str {
+        "output_sink"
+    }
+
+    fn applies(&self, req: &AnalyzeRequest, _ctx: &EvalContext) -> bool {
+        !req.tool_definition.output_parameters.is_empty()
+    }
+
+    async fn eval(
+        &self,
+        req: &AnalyzeRequest,
+        _ctx: &EvalContext,
+        cfg: &PluginConfig,
+    ) -> Option<AnalyzeResponse> {
+        let (param, indicator) = self.find(&req.tool_definition.output_parameters)?;
+        let mut diag = Diagnostics::for_config("output_sink", "output_sink", cfg)
+            .detail(indicator)
+            .with("parameter", param.name.as_str().into());
+        if let Some(tool) = req.tool_definition.name.as_deref() {
+            diag = diag.with("tool", tool.into());
+        }
+        Some(AnalyzeResponse {
+            block_action: true,
+            reason_code: Some(119),
+            reason: Some("Tool output is sent to an external destination.".into()),
+            blocked_by: Some("output_sink".into()),
+            diagnostics: Some(diag.into()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn param(name: &str, ty: serde_json::Value) -> ToolParam {
+        serde_json::from_value(json!({ "name": name, "type": ty })).unwrap()
+    }
+
+    #[test]
+    fn matches_folded_names_and_types() {
+        let plugin = OutputSinkPlugin::new(&PluginConfig::default());
+        let hit = [param("Webhook-URL", json!("string"))];
+        assert_eq!(plugin.find(&hit).map(|(_, i)| i), Some("webhook"));
+        let typed = [param("target", json!({ "$kind": "ExternalUrl" }))];
+        assert_eq!(plugin.find(&typed).map(|(_, i)| i), Some("external_url"));
+        let plain = [param("summary", json!("string")), param("url", json!(null))];
+        assert!(plugin.find(&plain).is_none());
+    }
+}
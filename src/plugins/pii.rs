@@ -1,18 +1,22 @@
-use super::{Plugin, PluginConfig};
-use crate::util::{ac_for, EvalContext};
+use super::{Diagnostics, Plugin, PluginConfig};
+use crate::util::{ac_for, request_texts, EvalContext};
 use crate::{AnalyzeRequest, AnalyzeResponse};
 use once_cell::sync::Lazy;
 use regex::Regex;
 
 /// Detects personally identifiable information such as email addresses, IBANs
 /// and phone numbers.  Additional keywords can be configured via
-/// `pii_keywords` in `PluginConfig`.  If any match is found the action is
-/// blocked.
+/// `pii_keywords` (literals) and `pii_keyword_regex` (patterns) in
+/// `PluginConfig`.  If any match is found the action is blocked.
 #[derive(Default)]
-pub struct PiiPlugin;
+pub struct PiiPlugin {
+    keyword_regexes: Vec<Regex>,
+}
 
 static EMAIL_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"[a-zA-Z0-9_.+-]+@[a-zA-Z0-9-]+\.[a-zA-Z0-9-.]+").unwrap());
+// Case-sensitive on purpose: IBANs are written upper-case, and matching the
+// lower-cased `Precomputed` text would flag ordinary words of the same shape.
 static IBAN_RE: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"\b[A-Z]{2}\d{2}[A-Z0-9]{10,30}\b").unwrap());
 static PHONE_RE: Lazy<Regex> = Lazy::new(|| {
@@ -20,19 +24,79 @@
 });
 
 impl PiiPlugin {
+    /// Compile `pii_keyword_regex` once (case-insensitive, as scanned text is
+    /// lower-cased). Invalid patterns are logged and skipped.
+    pub fn new(cfg: &PluginConfig) -> Self {
+        let keyword_regexes = cfg
+            .pii_keyword_regex
+            .iter()
+            .filter_map(|pat| match Regex::new(&format!("(?i){}", pat)) {
+                Ok(re) => Some(re),
+                Err(err) => {
+                    tracing::warn!(pattern = %pat, error = ?err, "failed to compile pii keyword regex, ignoring");
+                    None
+                }
+            })
+            .collect();
+        PiiPlugin { keyword_regexes }
+    }
+
+    /// Configured literal or regex keyword present in `text`.
+    fn contains_keyword(&self, text: &str, cfg: &PluginConfig) -> bool {
+        (!cfg.pii_keywords.is_empty() && ac_for(&cfg.pii_keywords).is_match(text))
+            || self.keyword_regexes.iter().any(|re| re.is_match(text))
+    }
+
     /// Check if text contains email addresses that are NOT from the company domain
     fn contains_non_company_pii(&self, text: &str, cfg: &PluginConfig) -> bool {
-        let domain_pattern = format!("@{}", cfg.company_domain);
-        for m in EMAIL_RE.find_iter(text) {
-            let email = m.as_str().to_lowercase();
-            if !email.ends_with(&domain_pattern) {
-                return true;
-            }
-        }
-        false
+        EMAIL_RE
+            .find_iter(text)
+            .any(|m| !cfg.is_company_email(m.as_str()))
+    }
+
+    /// Check for IBAN-shaped tokens. With `iban_checksum` enabled (default)
+    /// a candidate must also pass the ISO 7064 mod-97 check.
+    fn contains_iban(&self, text: &str, cfg: &PluginConfig) -> bool {
+        IBAN_RE
+            .find_iter(text)
+            .any(|m| !cfg.iban_checksum || iban_checksum_valid(m.as_str()))
     }
 }
 
+/// `text` with built-in PII (emails, IBAN-shaped tokens, phone numbers)
+/// replaced by `[redacted]`, for previews that must not leak it. Company
+/// addresses are masked too.
+pub fn redact(text: &str) -> String {
+    const MASK: &str = "[redacted]";
+    let out = EMAIL_RE.replace_all(text, MASK);
+    let out = IBAN_RE.replace_all(&out, MASK);
+    PHONE_RE.replace_all(&out, MASK).into_owned()
+}
+
+/// ISO 13616 / ISO 7064 mod-97-10 validation: move the country code and check
+/// digits to the end, map letters to 10..35 and require remainder 1.
+fn iban_checksum_valid(candidate: &str) -> bool {
+    let iban = candidate.as_bytes();
+    if iban.len() < 15 || iban.len() > 34 {
+        return false;
+    }
+    let mut remainder: u32 = 0;
+    for &b in iban[4..].iter().chain(&iban[..4]) {
+        let value = match b {
+            b'0'..=b'9' => (b - b'0') as u32,
+            b'a'..=b'z' => (b - b'a') as u32 + 10,
+            b'A'..=b'Z' => (b - b'A') as u32 + 10,
+            _ => return false,
+        };
+        remainder = if value >= 10 {
+            (remainder * 100 + value) % 97
+        } else {
+            (remainder * 10 + value) % 97
+        };
+    }
+    remainder == 1
+}
+
 #[async_trait::async_trait]
 impl Plugin for PiiPlugin {
     fn name(&self) -> &str {
@@ -41,66 +105,99 @@ fn name(&self) -> &str {
 
     async fn eval(
         &self,
-        _req: &AnalyzeRequest,
+        req: &AnalyzeRequest,
         ctx: &EvalContext,
         cfg: &PluginConfig,
     ) -> Option<AnalyzeResponse> {
-        // Check built‑in patterns on the flattened text
+        // Check built‑in patterns on the flattened text; IBANs against the
+        // original-case strings, since the pattern is case-sensitive.
         let hay = &ctx.pre.full_text_lower;
         if self.contains_non_company_pii(hay, cfg)
-            || IBAN_RE.is_match(hay)
             || PHONE_RE.is_match(hay)
+            || request_texts(req)
+                .into_iter()
+                .any(|text| self.contains_iban(text, cfg))
         {
-            let diag = serde_json::json!({"plugin":"pii","code":"builtin"});
+            let diag = Diagnostics::for_config("pii", "builtin", cfg);
             return Some(AnalyzeResponse {
                 block_action: true,
                 reason_code: Some(202),
                 reason: Some("Detected potential PII in content.".into()),
                 blocked_by: Some("pii".into()),
-                diagnostics: Some(diag),
+                diagnostics: Some(diag.into()),
             });
         }
-        // Check AC keyword list if configured
-        if !cfg.pii_keywords.is_empty() {
-            let ac = ac_for(&cfg.pii_keywords);
-            if ac.is_match(hay) {
-                let diag = serde_json::json!({"plugin":"pii","code":"keyword"});
+        // Check configured keywords (AC literals and regexes)
+        if self.contains_keyword(hay, cfg) {
+            let diag = Diagnostics::for_config("pii", "keyword", cfg);
+            return Some(AnalyzeResponse {
+                block_action: true,
+                reason_code: Some(202),
+                reason: Some("Detected potential PII in content.".into()),
+                blocked_by: Some("pii".into()),
+                diagnostics: Some(diag.into()),
+            });
+        }
+        // Check each input string individually for PII patterns
+        for s in &ctx.pre.strings {
+            if self.contains_non_company_pii(s, cfg) || PHONE_RE.is_match(s) {
+                let diag = Diagnostics::for_config("pii", "input", cfg);
                 return Some(AnalyzeResponse {
                     block_action: true,
                     reason_code: Some(202),
                     reason: Some("Detected potential PII in content.".into()),
                     blocked_by: Some("pii".into()),
-                    diagnostics: Some(diag),
+                    diagnostics: Some(diag.into()),
                 });
             }
-        }
-        // Check each input string individually for PII patterns
-        for s in &ctx.pre.strings {
-            if self.contains_non_company_pii(s, cfg) || IBAN_RE.is_match(s) || PHONE_RE.is_match(s)
-            {
-                let diag = serde_json::json!({"plugin":"pii","code":"input"});
+            if self.contains_keyword(s, cfg) {
+                let diag = Diagnostics::for_config("pii", "keyword", cfg);
                 return Some(AnalyzeResponse {
                     block_action: true,
                     reason_code: Some(202),
                     reason: Some("Detected potential PII in content.".into()),
                     blocked_by: Some("pii".into()),
-                    diagnostics: Some(diag),
+                    diagnostics: Some(diag.into()),
                 });
             }
-            if !cfg.pii_keywords.is_empty() {
-                let ac = ac_for(&cfg.pii_keywords);
-                if ac.is_match(s) {
-                    let diag = serde_json::json!({"plugin":"pii","code":"keyword"});
-                    return Some(AnalyzeResponse {
-                        block_action: true,
-                        reason_code: Some(202),
-                        reason: Some("Detected potential PII in content.".into()),
-                        blocked_by: Some("pii".into()),
-                        diagnostics: Some(diag),
-                    });
-                }
-            }
         }
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validates_iban_checksum() {
+        assert!(iban_checksum_valid("GB82WEST12345698765432"));
+        assert!(iban_checksum_valid("gb82west12345698765432"));
+        assert!(!iban_checksum_valid("GB00WEST12345698765432"));
+    }
+
+    #[test]
+    fn strict_mode_rejects_same_shape_invalid_iban() {
+        let plugin = PiiPlugin::default();
+        let strict = PluginConfig::default();
+        assert!(plugin.contains_iban("iban GB82WEST12345698765432", &strict));
+        assert!(!plugin.contains_iban("iban GB00WEST12345698765432", &strict));
+
+        let shape_only = PluginConfig {
+            iban_checksum: false,
+            ..PluginConfig::default()
+        };
+        assert!(plugin.contains_iban("iban GB00WEST12345698765432", &shape_only));
+    }
+
+    #[test]
+    fn lowercase_words_are_not_iban_shaped() {
+        let plugin = PiiPlugin::default();
+        let shape_only = PluginConfig {
+            iban_checksum: false,
+            ..PluginConfig::default()
+        };
+        assert!(!plugin.contains_iban("see ab12configuration0 docs", &shape_only));
+        assert!(!plugin.contains_iban("iban gb82west12345698765432", &shape_only));
+    }
+}
@@ -1,5 +1,5 @@
-use super::{Plugin, PluginConfig};
-use crate::util::EvalContext;
+use super::{Diagnostics, Plugin, PluginConfig, DEFAULT_MAX_POLICY_RULES};
+use crate::util::{EvalContext, Precomputed};
 use crate::{AnalyzeRequest, AnalyzeResponse};
 use regex::Regex;
 use serde::Deserialize;
@@ -16,8 +16,20 @@ pub struct PolicyRule {
     pub tool: Option<String>,
     /// Optional argument key.  If set, the rule operates on the specified
     /// input field.  If not set, conditions are evaluated against the
-    /// concatenated text and all inputs.
+    /// concatenated text and all inputs.  A value starting with `/` is a
+    /// JSON pointer into `inputValues` (e.g. `/recipients/bcc`); every string
+    /// under the value it points to is checked.
     pub arg: Option<String>,
+    /// Optional agent publication state.  `false` limits the rule to
+    /// unpublished (draft) agents, `true` to published ones, per
+    /// `conversationMetadata.agent.isPublished`.  A request that does not
+    /// state it never matches a rule that sets this.
+    pub agent_published: Option<bool>,
+    /// What the conditions are matched against. `tool_meta` checks the
+    /// tool name, description and parameter descriptions instead of the
+    /// request text and inputs; `arg` does not apply there.
+    #[serde(default)]
+    pub scope: PolicyScope,
     /// A list of substrings.  All entries are lower‑cased.  If any
     /// substring occurs in the target, the rule triggers.
     #[serde(default)]
@@ -32,12 +44,70 @@ pub struct PolicyRule {
     pub reason: Option<String>,
 }
 
+/// Text a policy rule is evaluated against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PolicyScope {
+    /// The user message, chat history and input values (or `arg`).
+    #[default]
+    Request,
+    /// `toolDefinition` name, description and parameter descriptions.
+    #[serde(alias = "toolMeta")]
+    ToolMeta,
+}
+
+/// Most regexes kept per rule (and per `blockedToolPatterns` list).
+pub(crate) const MAX_PATTERNS: usize = 50;
+/// Longest regex source accepted; longer patterns are dropped.
+pub(crate) const MAX_PATTERN_LEN: usize = 500;
+
+/// Apply the pattern limits above to `patterns` (truncate the list, drop
+/// oversized entries) and compile the rest case-insensitively, with
+/// `wrap` turning each source into the final expression. Patterns that
+/// fail to compile are logged and skipped. Returns `(source, regex)` pairs.
+pub(crate) fn compile_patterns(
+    patterns: &[String],
+    wrap: impl Fn(&str) -> String,
+) -> Vec<(String, Regex)> {
+    if patterns.len() > MAX_PATTERNS {
+        tracing::warn!(
+            pattern_count = patterns.len(),
+            limit = MAX_PATTERNS,
+            "regex list truncated"
+        );
+    }
+    patterns
+        .iter()
+        .take(MAX_PATTERNS)
+        .filter(|p| {
+            if p.len() > MAX_PATTERN_LEN {
+                tracing::warn!(
+                    len = p.len(),
+                    limit = MAX_PATTERN_LEN,
+                    "dropping oversized regex pattern"
+                );
+                return false;
+            }
+            true
+        })
+        .filter_map(|p| match Regex::new(&format!("(?i){}", wrap(p))) {
+            Ok(re) => Some((p.clone(), re)),
+            Err(err) => {
+                tracing::warn!(pattern = %p, error = ?err, "failed to compile regex, ignoring");
+                None
+            }
+        })
+        .collect()
+}
+
 /// A compiled rule for efficient evaluation.  Conditions are stored
 /// lower‑cased and regexes compiled once.
 #[derive(Clone)]
 struct CompiledRule {
     tool: Option<String>,
     arg: Option<String>,
+    agent_published: Option<bool>,
+    scope: PolicyScope,
     contains: Vec<String>,
     regexes: Vec<Regex>,
     reason_code: i32,
@@ -46,20 +116,23 @@ struct CompiledRule {
 
 impl From<&PolicyRule> for CompiledRule {
     fn from(r: &PolicyRule) -> Self {
-        let mut regexes = Vec::new();
-        for pat in &r.patterns {
-            // Compile case‑insensitive.  We avoid untrusted regex complexity by
-            // falling back to a literal if compilation fails.
-            match Regex::new(&format!("(?i){}", pat)) {
-                Ok(re) => regexes.push(re),
-                Err(err) => {
-                    tracing::warn!(pattern = %pat, error = ?err, "failed to compile regex in policy pack, ignoring");
-                }
-            }
-        }
+        let regexes = compile_patterns(&r.patterns, str::to_string)
+            .into_iter()
+            .map(|(_, re)| re)
+            .collect();
         CompiledRule {
             tool: r.tool.as_ref().map(|s| s.to_lowercase()),
-            arg: r.arg.as_ref().map(|s| s.to_lowercase()),
+            // Pointers address keys exactly; plain keys keep the historic
+            // lower-casing.
+            arg: r.arg.as_ref().map(|s| {
+                if s.starts_with('/') {
+                    s.clone()
+                } else {
+                    s.to_lowercase()
+                }
+            }),
+            agent_published: r.agent_published,
+            scope: r.scope,
             contains: r.contains.iter().map(|s| s.to_lowercase()).collect(),
             regexes,
             reason_code: r.reason_code.unwrap_or(700),
@@ -75,36 +148,65 @@ pub struct PolicyPackPlugin {
 }
 
 impl PolicyPackPlugin {
+    /// Compile `rules`, keeping at most `DEFAULT_MAX_POLICY_RULES` of them.
     pub fn new(rules: Vec<PolicyRule>) -> Self {
-        // Safeguards: limit regex/pattern complexity per rule.
-        const MAX_PATTERNS: usize = 50;
-        const MAX_PATTERN_LEN: usize = 500;
-        let mut filtered: Vec<CompiledRule> = Vec::new();
-        for r in &rules {
-            let mut safe = r.clone();
-            if safe.patterns.len() > MAX_PATTERNS {
-                tracing::warn!(
-                    pattern_count = safe.patterns.len(),
-                    limit = MAX_PATTERNS,
-                    "policy rule regex list truncated"
-                );
-                safe.patterns.truncate(MAX_PATTERNS);
-            }
-            safe.patterns.retain(|p| {
-                if p.len() > MAX_PATTERN_LEN {
-                    tracing::warn!(
-                        len = p.len(),
-                        limit = MAX_PATTERN_LEN,
-                        "dropping oversized policy regex pattern"
-                    );
-                    return false;
-                }
-                true
-            });
-            filtered.push(CompiledRule::from(&safe));
+        Self::with_max_rules(rules, DEFAULT_MAX_POLICY_RULES)
+    }
+
+    /// Compile `rules`, keeping at most `max_rules` of them (in order).
+    pub fn with_max_rules(rules: Vec<PolicyRule>, max_rules: usize) -> Self {
+        // Safeguards: limit the rule count here; per-rule pattern limits are
+        // applied by `compile_patterns`.
+        if rules.len() > max_rules {
+            tracing::warn!(
+                rule_count = rules.len(),
+                limit = max_rules,
+                "policy rule list truncated (maxPolicyRules)"
+            );
         }
+        let filtered = rules
+            .iter()
+            .take(max_rules)
+            .map(CompiledRule::from)
+            .collect();
         Self { rules: filtered }
     }
+
+    /// Number of rules kept after the `max_rules` cap.
+    pub fn rule_count(&self) -> usize {
+        self.rules.len()
+    }
+}
+
+/// Rules a `PolicyPackPlugin` built from `cfg` loads (the
+/// `sentra_policy_rules_loaded` gauge).
+pub fn loaded_rule_count(cfg: &PluginConfig) -> usize {
+    cfg.policies.len().min(cfg.max_policy_rules)
+}
+
+/// Resolve a JSON pointer against `inputValues` without cloning the map
+/// into a `Value`: the first segment selects the top-level key.
+fn resolve_pointer<'a>(
+    inputs: &'a serde_json::Map<String, serde_json::Value>,
+    pointer: &str,
+) -> Option<&'a serde_json::Value> {
+    let rest = pointer.strip_prefix('/')?;
+    let (first, tail) = match rest.find('/') {
+        Some(i) => (&rest[..i], &rest[i..]),
+        None => (rest, ""),
+    };
+    let key = first.replace("~1", "/").replace("~0", "~");
+    inputs.get(&key)?.pointer(tail)
+}
+
+/// Non-blank string leaves of `val` (see `Precomputed::is_blank`).
+fn collect_strings<'a>(val: &'a serde_json::Value, out: &mut Vec<&'a str>) {
+    match val {
+        serde_json::Value::String(s) if !Precomputed::is_blank(s) => out.push(s),
+        serde_json::Value::Array(arr) => arr.iter().for_each(|v| collect_strings(v, out)),
+        serde_json::Value::Object(map) => map.values().for_each(|v| collect_strings(v, out)),
+        _ => {}
+    }
 }
 
 #[async_trait::async_trait]
@@ -117,7 +219,7 @@ async fn eval(
         &self,
         req: &AnalyzeRequest,
         ctx: &EvalContext,
-        _cfg: &PluginConfig,
+        cfg: &PluginConfig,
     ) -> Option<AnalyzeResponse> {
         // Evaluate each rule.  Return the first block.
         for rule in &self.rules {
@@ -133,14 +235,37 @@ async fn eval(
                     continue;
                 }
             }
+            if let Some(published) = rule.agent_published {
+                let is_published = req
+                    .conversation_metadata
+                    .as_ref()
+                    .and_then(|m| m.agent.as_ref())
+                    .and_then(|a| a.is_published);
+                if is_published != Some(published) {
+                    continue;
+                }
+            }
             // Determine the target string to test: either a specific argument
             // value or the concatenated input plus chat messages.
             let mut targets: Vec<&str> = Vec::new();
-            if let Some(ref arg_name) = rule.arg {
-                if let Some(val) = req.input_values.get(arg_name) {
-                    if let Some(s) = val.as_str() {
-                        targets.push(s);
+            let tool_meta;
+            if rule.scope == PolicyScope::ToolMeta {
+                let tool = &req.tool_definition;
+                tool_meta = tool
+                    .name
+                    .as_deref()
+                    .into_iter()
+                    .chain(tool.descriptions())
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                targets.push(&tool_meta);
+            } else if let Some(ref arg_name) = rule.arg {
+                if arg_name.starts_with('/') {
+                    if let Some(val) = resolve_pointer(&req.input_values, arg_name) {
+                        collect_strings(val, &mut targets);
                     }
+                } else if let Some(s) = Precomputed::input_str(&req.input_values, arg_name) {
+                    targets.push(s);
                 }
             } else {
                 targets.push(&ctx.pre.full_text_lower);
@@ -175,6 +300,16 @@ async fn eval(
                 }
             }
             if matched {
+                let mut diag = Diagnostics::for_config("policy_pack", "policy", cfg)
+                    .with("ruleReasonCode", rule.reason_code.into());
+                if rule.scope == PolicyScope::ToolMeta {
+                    diag = diag.with("scope", "tool_meta".into());
+                } else if let Some(ref arg_name) = rule.arg {
+                    // `arg` predates `field`; kept for schemaVersion 1 consumers.
+                    diag = diag
+                        .with("arg", arg_name.as_str().into())
+                        .field(arg_name.clone());
+                }
                 return Some(AnalyzeResponse {
                     block_action: true,
                     reason_code: Some(rule.reason_code),
@@ -184,7 +319,7 @@ async fn eval(
                             .unwrap_or_else(|| "Policy rule triggered".into()),
                     ),
                     blocked_by: Some("policy_pack".into()),
-                    diagnostics: Some(serde_json::json!({"plugin":"policy_pack","code":"policy"})),
+                    diagnostics: Some(diag.into()),
                 });
             }
         }
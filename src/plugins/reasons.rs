@@ -0,0 +1,64 @@
+//! Catalog of reason codes Sentra can emit, served by `GET /reason-codes`.
+
+use std::collections::BTreeMap;
+
+use super::PluginConfig;
+
+/// Built-in reason codes and their human-readable meaning. Keep in sync with
+/// the legend in DIAGNOSTICS.md.
+pub const BUILTIN_REASONS: &[(i32, &str)] = &[
+    (111, "Potential data exfiltration / prompt injection phrase"),
+    (112, "Noncompliant BCC domain"),
+    (113, "Blocked domain"),
+    (115, "Too many mail recipients"),
+    (116, "Injection phrase in a previous tool output"),
+    (117, "Tool is on the denylist"),
+    (118, "Role impersonation in conversation content"),
+    (119, "Tool output declared to an external sink"),
+    (201, "AWS key or other secret detected"),
+    (202, "PII detected"),
+    (700, "Policy rule triggered"),
+    (801, "External policy block"),
+    (900, "Plugin failure (fail-closed)"),
+];
+
+/// Built-in codes merged with the codes configured in `cfg`: policy rules,
+/// external HTTP definitions and custom exfil patterns. A configured
+/// `reason` replaces the built-in text for its code; configured codes
+/// without one fall back to the plugin's default wording.
+pub fn catalog(cfg: &PluginConfig) -> BTreeMap<i32, String> {
+    let mut out: BTreeMap<i32, String> = BUILTIN_REASONS
+        .iter()
+        .map(|(code, text)| (*code, text.to_string()))
+        .collect();
+    let mut add = |code: i32, reason: Option<&String>, fallback: &str| match reason {
+        Some(reason) => {
+            out.insert(code, reason.clone());
+        }
+        None => {
+            out.entry(code).or_insert_with(|| fallback.to_string());
+        }
+    };
+    for rule in &cfg.policies {
+        add(
+            rule.reason_code.unwrap_or(700),
+            rule.reason.as_ref(),
+            "Policy rule triggered",
+        );
+    }
+    for def in &cfg.external_http {
+        add(
+            def.reason_code,
+            def.reason.as_ref(),
+            "External policy block",
+        );
+    }
+    for pattern in &cfg.exfil_patterns {
+        if let super::exfil::ExfilPattern::Detailed(spec) = pattern {
+            if let Some(code) = spec.reason_code {
+                add(code, None, "Custom exfiltration pattern");
+            }
+        }
+    }
+    out
+}
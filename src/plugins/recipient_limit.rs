@@ -0,0 +1,80 @@
+use super::{is_mail_tool, Diagnostics, Plugin, PluginConfig};
+use crate::util::EvalContext;
+use crate::{AnalyzeRequest, AnalyzeResponse};
+use serde_json::Value;
+
+/// Blocks mail tools addressed to more than `max_recipients` recipients
+/// across the `to`, `cc` and `bcc` fields.
+#[derive(Default)]
+pub struct RecipientLimitPlugin;
+
+const RECIPIENT_FIELDS: [&str; 3] = ["to", "cc", "bcc"];
+
+/// Count addresses in a recipient field. Strings may hold several addresses
+/// separated by `,` or `;`; arrays are counted element by element.
+fn count_recipients(val: &Value) -> usize {
+    match val {
+        Value::String(s) => s
+            .split([',', ';'])
+            .filter(|part| !part.trim().is_empty())
+            .count(),
+        Value::Array(items) => items.iter().map(count_recipients).sum(),
+        _ => 0,
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for RecipientLimitPlugin {
+    fn name(&self) -> &str {
+        "recipient_limit"
+    }
+
+    fn applies(&self, req: &AnalyzeRequest, _ctx: &EvalContext) -> bool {
+        is_mail_tool(req)
+    }
+
+    async fn eval(
+        &self,
+        req: &AnalyzeRequest,
+        _ctx: &EvalContext,
+        cfg: &PluginConfig,
+    ) -> Option<AnalyzeResponse> {
+        // Same tool scoping as email_bcc.
+        if !is_mail_tool(req) {
+            return None;
+        }
+        let count: usize = RECIPIENT_FIELDS
+            .iter()
+            .filter_map(|f| req.input_values.get(*f))
+            .map(count_recipients)
+            .sum();
+        if count > cfg.max_recipients {
+            let diag = Diagnostics::for_config("recipient_limit", "too_many_recipients", cfg)
+                .with("count", count.into())
+                .with("limit", cfg.max_recipients.into());
+            return Some(AnalyzeResponse {
+                block_action: true,
+                reason_code: Some(115),
+                reason: Some("Too many recipients.".into()),
+                blocked_by: Some("recipient_limit".into()),
+                diagnostics: Some(diag.into()),
+            });
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::count_recipients;
+    use serde_json::json;
+
+    #[test]
+    fn counts_delimited_strings_and_arrays() {
+        assert_eq!(count_recipients(&json!("a@x.com")), 1);
+        assert_eq!(count_recipients(&json!("a@x.com, b@x.com;c@x.com,")), 3);
+        assert_eq!(count_recipients(&json!(["a@x.com", "b@x.com, c@x.com"])), 3);
+        assert_eq!(count_recipients(&json!("  ")), 0);
+        assert_eq!(count_recipients(&json!(null)), 0);
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+
+use regex::Regex;
+
+use super::policy_pack::compile_patterns;
+use super::{Diagnostics, Plugin, PluginConfig};
+use crate::util::EvalContext;
+use crate::{AnalyzeRequest, AnalyzeResponse};
+
+/// Blocks tools whose `toolDefinition.name` is on `blockedTools` (compared
+/// case-insensitively) or fully matches one of `blockedToolPatterns`. Built
+/// only when either list is non-empty.
+pub struct ToolDenylistPlugin {
+    /// Lowercased name -> entry as configured, reported in diagnostics.
+    names: HashMap<String, String>,
+    /// Pattern as configured and its anchored, case-insensitive regex.
+    patterns: Vec<(String, Regex)>,
+}
+
+/// What put a tool on the denylist.
+#[derive(Debug, PartialEq, Eq)]
+enum Match<'a> {
+    Name(&'a str),
+    Pattern(&'a str),
+}
+
+impl ToolDenylistPlugin {
+    pub fn new(cfg: &PluginConfig) -> Self {
+        let names = cfg
+            .blocked_tools
+            .iter()
+            .map(|name| name.trim())
+            .filter(|name| !name.is_empty())
+            .map(|name| (name.to_lowercase(), name.to_string()))
+            .collect();
+        // Anchored so `Delete.*` does not catch `UndeleteFile`.
+        let patterns = compile_patterns(&cfg.blocked_tool_patterns, |p| format!("^(?:{})$", p));
+        Self { names, patterns }
+    }
+
+    /// Exact entry first, then the first matching pattern in config order.
+    fn find(&self, tool: &str) -> Option<Match<'_>> {
+        let tool = tool.trim();
+        if let Some(entry) = self.names.get(&tool.to_lowercase()) {
+            return Some(Match::Name(entry));
+        }
+        self.patterns
+            .iter()
+            .find(|(_, re)| re.is_match(tool))
+            .map(|(source, _)| Match::Pattern(source))
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for ToolDenylistPlugin {
+    fn name(&self) -> &str {
+        "tool_denylist"
+    }
+
+    fn applies(&self, req: &AnalyzeRequest, _ctx: &EvalContext) -> bool {
+        req.tool_definition.name.is_some()
+    }
+
+    async fn eval(
+        &self,
+        req: &AnalyzeRequest,
+        _ctx: &EvalContext,
+        cfg: &PluginConfig,
+    ) -> Option<AnalyzeResponse> {
+        let tool = req.tool_definition.name.as_deref()?;
+        let diag = match self.find(tool)? {
+            Match::Name(entry) => Diagnostics::for_config("tool_denylist", "blocked_tool", cfg)
+                .with("tool", tool.into())
+                .with("entry", entry.into()),
+            Match::Pattern(pattern) => {
+                Diagnostics::for_config("tool_denylist", "blocked_tool_pattern", cfg)
+                    .with("tool", tool.into())
+                    .with("pattern", pattern.into())
+            }
+        };
+        Some(AnalyzeResponse {
+            block_action: true,
+            reason_code: Some(117),
+            reason: Some("Tool is not permitted.".into()),
+            blocked_by: Some("tool_denylist".into()),
+            diagnostics: Some(diag.into()),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_names_case_insensitively() {
+        let cfg = PluginConfig {
+            blocked_tools: vec!["ExecuteShell".into(), " ".into()],
+            ..Default::default()
+        };
+        let plugin = ToolDenylistPlugin::new(&cfg);
+        assert_eq!(
+            plugin.find("executeshell"),
+            Some(Match::Name("ExecuteShell"))
+        );
+        assert_eq!(
+            plugin.find(" EXECUTESHELL "),
+            Some(Match::Name("ExecuteShell"))
+        );
+        assert_eq!(plugin.find("ExecuteShellSafe"), None);
+        assert_eq!(plugin.find(""), None);
+    }
+
+    #[test]
+    fn patterns_match_whole_names_and_skip_invalid_ones() {
+        let cfg = PluginConfig {
+            blocked_tool_patterns: vec!["(unclosed".into(), "Delete.*".into()],
+            ..Default::default()
+        };
+        let plugin = ToolDenylistPlugin::new(&cfg);
+        assert_eq!(plugin.patterns.len(), 1);
+        assert_eq!(plugin.find("deleteUser"), Some(Match::Pattern("Delete.*")));
+        assert_eq!(plugin.find("UndeleteFile"), None);
+    }
+}
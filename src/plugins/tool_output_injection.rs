@@ -0,0 +1,81 @@
+use super::{Diagnostics, Plugin, PluginConfig};
+use crate::util::EvalContext;
+use crate::{AnalyzeRequest, AnalyzeResponse};
+use serde_json::Value;
+
+/// Detects instructions planted in `previousToolOutputs` (tool-output
+/// poisoning). Only prior tool outputs are scanned; the user message is the
+/// `exfil` plugin's concern.
+#[derive(Default)]
+pub struct ToolOutputInjectionPlugin;
+
+/// Used when `tool_output_patterns` is empty. Lower case.
+const DEFAULT_PATTERNS: &[&str] = &[
+    "ignore the user",
+    "ignore previous instructions",
+    "disregard the user",
+    "always approve",
+    "do not tell the user",
+    "send to",
+];
+
+fn collect_lower(val: &Value, out: &mut Vec<String>) {
+    match val {
+        Value::String(s) => out.push(s.to_lowercase()),
+        Value::Array(items) => items.iter().for_each(|v| collect_lower(v, out)),
+        Value::Object(map) => map.values().for_each(|v| collect_lower(v, out)),
+        _ => {}
+    }
+}
+
+#[async_trait::async_trait]
+impl Plugin for ToolOutputInjectionPlugin {
+    fn name(&self) -> &str {
+        "tool_output_injection"
+    }
+
+    async fn eval(
+        &self,
+        req: &AnalyzeRequest,
+        _ctx: &EvalContext,
+        cfg: &PluginConfig,
+    ) -> Option<AnalyzeResponse> {
+        let outputs = req.planner_context.previous_tool_outputs.as_ref()?;
+        let configured: Vec<String>;
+        let patterns: Vec<&str> = if cfg.tool_output_patterns.is_empty() {
+            DEFAULT_PATTERNS.to_vec()
+        } else {
+            configured = cfg
+                .tool_output_patterns
+                .iter()
+                .map(|p| p.to_lowercase())
+                .collect();
+            configured.iter().map(String::as_str).collect()
+        };
+        for output in outputs {
+            let Some(val) = output.outputs.as_ref() else {
+                continue;
+            };
+            let mut texts = Vec::new();
+            collect_lower(val, &mut texts);
+            let hit = patterns
+                .iter()
+                .find(|p| !p.is_empty() && texts.iter().any(|t| t.contains(**p)));
+            if let Some(pat) = hit {
+                let mut diag = Diagnostics::for_config("tool_output_injection", "tool_output", cfg)
+                    .detail(pat.to_string());
+                if let Some(tool) = &output.tool_name {
+                    diag = diag.with("toolName", tool.as_str().into());
+                }
+                return Some(AnalyzeResponse {
+                    block_action: true,
+                    reason_code: Some(116),
+                    reason: Some("Detected instructions injected via tool output".into()),
+                    blocked_by: Some("tool_output_injection".into()),
+                    diagnostics: Some(diag.into()),
+                });
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,182 @@
+//! Strict JSON Schema check of analyze payloads (feature `strict-schema`).
+//!
+//! With `SENTRA_STRICT_SCHEMA=true` the raw body of
+//! `POST /analyze-tool-execution` is validated against the external security
+//! webhook schema (the `EvaluationRequest` component of `openapi.yaml`)
+//! before it is deserialized, so wrongly typed fields and unknown chat roles
+//! are rejected with a 400 listing every violation. Unknown properties are
+//! still accepted, as in lenient mode.
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use serde_json::{json, Value};
+
+/// Violations reported per request; the rest are dropped.
+const MAX_VIOLATIONS: usize = 20;
+
+/// Chat roles accepted in `plannerContext.chatHistory[].role`.
+pub const CHAT_ROLES: [&str; 3] = ["user", "assistant", "system"];
+
+/// One schema violation: where it is in the payload and what is wrong.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct SchemaViolation {
+    /// JSON pointer into the request body (`""` for the root).
+    pub path: String,
+    pub message: String,
+}
+
+static VALIDATOR: Lazy<jsonschema::Validator> = Lazy::new(|| {
+    jsonschema::validator_for(&analyze_request_schema()).expect("analyze request schema is valid")
+});
+
+/// The `AnalyzeRequest` schema, mirroring `openapi.yaml`.
+pub fn analyze_request_schema() -> Value {
+    let string = json!({ "type": "string" });
+    let tool_param = json!({
+        "type": "object",
+        "required": ["name"],
+        "properties": {
+            "name": string,
+            "description": string,
+            "type": { "type": "object" }
+        }
+    });
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "type": "object",
+        "required": ["plannerContext", "toolDefinition", "inputValues"],
+        "properties": {
+            "plannerContext": {
+                "type": "object",
+                "required": ["userMessage"],
+                "properties": {
+                    "userMessage": string,
+                    "thought": string,
+                    "chatHistory": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["id", "role", "content"],
+                            "properties": {
+                                "id": string,
+                                "role": { "type": "string", "enum": CHAT_ROLES },
+                                "content": string,
+                                "timestamp": string
+                            }
+                        }
+                    },
+                    "previousToolOutputs": {
+                        "type": "array",
+                        "items": {
+                            "type": "object",
+                            "required": ["toolId", "toolName", "outputs"],
+                            "properties": {
+                                "toolId": string,
+                                "toolName": string,
+                                "outputs": { "type": "object" },
+                                "timestamp": string
+                            }
+                        }
+                    }
+                }
+            },
+            "toolDefinition": {
+                "type": "object",
+                "required": ["name"],
+                "properties": {
+                    "id": string,
+                    "type": string,
+                    "name": string,
+                    "description": string,
+                    "inputParameters": { "type": "array", "items": tool_param },
+                    "outputParameters": { "type": "array", "items": tool_param }
+                }
+            },
+            "inputValues": { "type": "object" },
+            "conversationMetadata": {
+                "type": "object",
+                "properties": {
+                    "agent": {
+                        "type": "object",
+                        "properties": {
+                            "id": string,
+                            "tenantId": string,
+                            "environmentId": string,
+                            "isPublished": { "type": "boolean" }
+                        }
+                    },
+                    "user": {
+                        "type": "object",
+                        "properties": { "id": string, "tenantId": string }
+                    },
+                    "trigger": {
+                        "type": "object",
+                        "properties": { "id": string, "schemaName": string }
+                    },
+                    "conversationId": string,
+                    "planId": string,
+                    "planStepId": string
+                }
+            }
+        }
+    })
+}
+
+/// Validate a raw analyze body. Returns at most `MAX_VIOLATIONS` entries.
+pub fn validate(body: &Value) -> Result<(), Vec<SchemaViolation>> {
+    let violations: Vec<SchemaViolation> = VALIDATOR
+        .iter_errors(body)
+        .take(MAX_VIOLATIONS)
+        .map(|err| SchemaViolation {
+            path: err.instance_path.to_string(),
+            message: err.to_string(),
+        })
+        .collect();
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(violations)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn minimal_payload_is_valid() {
+        let body = json!({
+            "plannerContext": { "userMessage": "hi" },
+            "toolDefinition": { "name": "SendEmail" },
+            "inputValues": {},
+            "extra": true
+        });
+        assert_eq!(validate(&body), Ok(()));
+    }
+
+    #[test]
+    fn reports_each_violation_with_its_path() {
+        let body = json!({
+            "plannerContext": {
+                "userMessage": "hi",
+                "chatHistory": [{ "id": "1", "role": "robot", "content": "x" }]
+            },
+            "toolDefinition": { "name": 7 },
+            "inputValues": []
+        });
+        let mut paths: Vec<String> = validate(&body)
+            .unwrap_err()
+            .into_iter()
+            .map(|v| v.path)
+            .collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            [
+                "/inputValues",
+                "/plannerContext/chatHistory/0/role",
+                "/toolDefinition/name"
+            ]
+        );
+    }
+}
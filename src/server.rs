@@ -0,0 +1,145 @@
+//! Connection handling for the server binary: HTTP/1.1 and optional HTTP/2
+//! on a plain or TLS listener, tuned by `ServerOptions`, with graceful
+//! shutdown that lets in-flight requests finish (within
+//! `shutdown_timeout_ms`, when set).
+
+use std::future::Future;
+use std::time::Duration;
+
+use axum::Router;
+use hyper_util::rt::{TokioExecutor, TokioIo, TokioTimer};
+use hyper_util::server::conn::auto;
+use hyper_util::server::graceful::GracefulShutdown;
+use hyper_util::service::TowerToHyperService;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::JoinSet;
+
+use crate::ServerOptions;
+
+/// Serve `app` over plain TCP until `shutdown` resolves. With
+/// `options.http2`, clients may speak HTTP/2 with prior knowledge (h2c).
+pub async fn serve(
+    listener: TcpListener,
+    app: Router,
+    options: &ServerOptions,
+    shutdown: impl Future<Output = ()>,
+) {
+    run(listener, app, options, shutdown, |stream| async move {
+        Ok::<_, std::io::Error>(stream)
+    })
+    .await
+}
+
+/// Serve `app` over TLS until `shutdown` resolves. With `options.http2`,
+/// `h2` is offered via ALPN ahead of `http/1.1`. A failed handshake only
+/// drops that connection.
+#[cfg(feature = "tls")]
+pub async fn serve_tls(
+    listener: TcpListener,
+    app: Router,
+    options: &ServerOptions,
+    config: std::sync::Arc<tokio_rustls::rustls::ServerConfig>,
+    shutdown: impl Future<Output = ()>,
+) {
+    let mut config = (*config).clone();
+    config.alpn_protocols = if options.http2 {
+        vec![b"h2".to_vec(), b"http/1.1".to_vec()]
+    } else {
+        vec![b"http/1.1".to_vec()]
+    };
+    let acceptor = tokio_rustls::TlsAcceptor::from(std::sync::Arc::new(config));
+    run(listener, app, options, shutdown, move |stream| {
+        let acceptor = acceptor.clone();
+        async move { acceptor.accept(stream).await }
+    })
+    .await
+}
+
+fn builder(options: &ServerOptions) -> auto::Builder<TokioExecutor> {
+    let mut builder = auto::Builder::new(TokioExecutor::new());
+    builder
+        .http1()
+        .keep_alive(options.keep_alive)
+        .timer(TokioTimer::new())
+        .header_read_timeout(Duration::from_millis(options.header_read_timeout_ms));
+    builder
+        .http2()
+        .timer(TokioTimer::new())
+        .keep_alive_interval(
+            options
+                .http2_keep_alive_interval_ms
+                .map(Duration::from_millis),
+        )
+        .keep_alive_timeout(Duration::from_millis(options.http2_keep_alive_timeout_ms))
+        .max_concurrent_streams(options.http2_max_concurrent_streams);
+    if options.http2 {
+        builder
+    } else {
+        builder.http1_only()
+    }
+}
+
+async fn run<H, F, S>(
+    listener: TcpListener,
+    app: Router,
+    options: &ServerOptions,
+    shutdown: impl Future<Output = ()>,
+    handshake: H,
+) where
+    H: Fn(TcpStream) -> F,
+    F: Future<Output = std::io::Result<S>> + Send + 'static,
+    S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+{
+    let builder = builder(options);
+    let graceful = GracefulShutdown::new();
+    let mut connections = JoinSet::new();
+    tokio::pin!(shutdown);
+    loop {
+        let (stream, peer) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(conn) => conn,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to accept connection");
+                    continue;
+                }
+            },
+            _ = &mut shutdown => break,
+        };
+        while connections.try_join_next().is_some() {}
+        let handshake = handshake(stream);
+        let builder = builder.clone();
+        let watcher = graceful.watcher();
+        let service = TowerToHyperService::new(app.clone());
+        connections.spawn(async move {
+            let stream = match handshake.await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    tracing::debug!(peer = %peer, error = %e, "handshake failed");
+                    return;
+                }
+            };
+            let conn = builder
+                .serve_connection(TokioIo::new(stream), service)
+                .into_owned();
+            if let Err(e) = watcher.watch(conn).await {
+                tracing::debug!(peer = %peer, error = %e, "connection closed with error");
+            }
+        });
+    }
+    drop(listener);
+    let Some(timeout_ms) = options.shutdown_timeout_ms else {
+        graceful.shutdown().await;
+        return;
+    };
+    let drain = tokio::time::timeout(Duration::from_millis(timeout_ms), graceful.shutdown());
+    if drain.await.is_err() {
+        while connections.try_join_next().is_some() {}
+        tracing::warn!(
+            dropped = connections.len(),
+            timeout_ms,
+            "shutdown timeout expired, dropping open connections"
+        );
+        connections.shutdown().await;
+    }
+}
@@ -0,0 +1,125 @@
+//! Tamper evidence for telemetry and audit logs.
+//!
+//! When `SENTRA_TELEMETRY_HMAC_KEY` is set, every JSONL line gets a trailing
+//! `"mac"` member: the hex HMAC-SHA256 of the line as it was before the
+//! member was appended. Verification strips the member and recomputes the
+//! MAC over the exact original bytes, so no JSON re-serialization is needed.
+//! Each line is signed on its own, so deleting whole lines is not detected.
+
+use ring::hmac;
+use std::fmt;
+use std::io::BufRead;
+use std::path::Path;
+
+const MAC_SUFFIX_START: &str = ",\"mac\":\"";
+const MAC_HEX_LEN: usize = 64;
+
+pub fn signing_key(secret: &[u8]) -> hmac::Key {
+    hmac::Key::new(hmac::HMAC_SHA256, secret)
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(hex.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+/// Append the `mac` member to a serialized JSON object.
+pub fn sign_line(key: &hmac::Key, line: &str) -> String {
+    let tag = to_hex(hmac::sign(key, line.as_bytes()).as_ref());
+    let body = line.strip_suffix('}').unwrap_or(line);
+    if body.trim_end() == "{" {
+        format!("{{\"mac\":\"{}\"}}", tag)
+    } else {
+        format!("{}{}{}\"}}", body, MAC_SUFFIX_START, tag)
+    }
+}
+
+/// Check a line produced by [`sign_line`].
+pub fn verify_line(key: &hmac::Key, line: &str) -> bool {
+    let Some(rest) = line.strip_suffix("\"}") else {
+        return false;
+    };
+    if rest.len() < MAC_HEX_LEN {
+        return false;
+    }
+    let (head, hex) = rest.split_at(rest.len() - MAC_HEX_LEN);
+    let original = if let Some(body) = head.strip_suffix(MAC_SUFFIX_START) {
+        format!("{}}}", body)
+    } else if head == "{\"mac\":\"" {
+        "{}".to_string()
+    } else {
+        return false;
+    };
+    match from_hex(hex) {
+        Some(tag) => hmac::verify(key, original.as_bytes(), &tag).is_ok(),
+        None => false,
+    }
+}
+
+#[derive(Debug)]
+pub enum VerifyError {
+    Io(std::io::Error),
+    /// 1-based number of the first line whose MAC does not match.
+    Tampered {
+        line: usize,
+    },
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VerifyError::Io(e) => write!(f, "failed to read log: {}", e),
+            VerifyError::Tampered { line } => write!(f, "MAC mismatch on line {}", line),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
+
+/// Verify every non-empty line of a signed JSONL file. Returns the number of
+/// lines checked.
+pub fn verify_file(path: &Path, secret: &[u8]) -> Result<usize, VerifyError> {
+    let key = signing_key(secret);
+    let file = std::fs::File::open(path).map_err(VerifyError::Io)?;
+    let mut checked = 0;
+    for (idx, line) in std::io::BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(VerifyError::Io)?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        if !verify_line(&key, &line) {
+            return Err(VerifyError::Tampered { line: idx + 1 });
+        }
+        checked += 1;
+    }
+    Ok(checked)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_line_is_valid_json_and_verifies() {
+        let key = signing_key(b"k");
+        let signed = sign_line(&key, r#"{"blockAction":true,"reasonCode":201}"#);
+        let parsed: serde_json::Value = serde_json::from_str(&signed).unwrap();
+        assert_eq!(parsed["mac"].as_str().unwrap().len(), MAC_HEX_LEN);
+        assert!(verify_line(&key, &signed));
+        assert!(!verify_line(&signing_key(b"other"), &signed));
+        assert!(!verify_line(&key, &signed.replace("201", "202")));
+
+        let empty = sign_line(&key, "{}");
+        assert!(verify_line(&key, &empty));
+        assert!(!verify_line(&key, r#"{"blockAction":true}"#));
+    }
+}
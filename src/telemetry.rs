@@ -0,0 +1,331 @@
+//! Typed telemetry and audit records (one JSONL line each).
+//!
+//! Schema history (changes are additive; consumers must ignore unknown keys):
+//! * `1`: `ts`, `correlationId`, `blockAction`, `reasonCode`, `blockedBy`,
+//!   `latencyMs`, `diagnostics`, `auditSuppressed`, `pluginTimings`.
+//! * `2`: adds `decision`, `severity` and `configHash`. Every v1 key keeps its
+//!   name, type and meaning. Optional `pluginDiagnostics` and
+//!   `skippedPlugins` appear only when they have content. `pluginTimings`
+//!   entries carry `outcome` (`allow`, `block`, `monitored`, `error`,
+//!   `timeout`, `skipped`), and `skipReason: "trusted"` for plugins a trusted
+//!   request bypassed. `monitored` lists the would-block responses of
+//!   plugins still in their monitor window, when there are any.
+//!   `requestId` carries the server-generated id also returned in the
+//!   `x-sentra-request-id` header.
+//!
+//! Audit records are a separate stream and keep their own `schemaVersion`
+//! (`AUDIT_SCHEMA_VERSION`). Besides audit-only blocks they record each
+//! monitor-mode would-block, marked `monitored: true`.
+
+use serde::Serialize;
+
+use crate::{AnalyzeRequest, AnalyzeResponse};
+
+pub const TELEMETRY_SCHEMA_VERSION: u32 = 2;
+pub const AUDIT_SCHEMA_VERSION: u32 = 1;
+
+/// What happened to the tool call, including blocks hidden by audit-only mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    Allow,
+    Block,
+    /// A plugin blocked but audit-only mode returned allow.
+    WouldBlock,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    Info,
+    Warning,
+    Critical,
+}
+
+impl Decision {
+    pub fn severity(self) -> Severity {
+        match self {
+            Decision::Allow => Severity::Info,
+            Decision::WouldBlock => Severity::Warning,
+            Decision::Block => Severity::Critical,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct PluginTiming<'a> {
+    pub plugin: &'a str,
+    pub ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<crate::plugins::PluginOutcome>,
+    /// Why a `skipped` plugin did not run, when not simply inapplicable
+    /// (`trusted`).
+    #[serde(rename = "skipReason", skip_serializing_if = "Option::is_none")]
+    pub skip_reason: Option<&'static str>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TelemetryEvent<'a> {
+    pub schema_version: u32,
+    pub ts: String,
+    pub correlation_id: &'a str,
+    /// Server-generated id of the request (see `util::request_id`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<&'a str>,
+    pub decision: Decision,
+    pub severity: Severity,
+    pub block_action: bool,
+    pub reason_code: Option<i32>,
+    pub blocked_by: Option<&'a str>,
+    pub latency_ms: u128,
+    pub diagnostics: Option<&'a serde_json::Value>,
+    /// `true` when audit-only hid a block, otherwise `null` (as in v1).
+    pub audit_suppressed: Option<bool>,
+    pub plugin_timings: Vec<PluginTiming<'a>>,
+    /// Fingerprint of the effective plugin order and config (see `config_hash`).
+    pub config_hash: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub plugin_diagnostics: Option<serde_json::Value>,
+    /// Plugins cut off by the deadline; omitted when every plugin ran.
+    #[serde(skip_serializing_if = "<[String]>::is_empty")]
+    pub skipped_plugins: &'a [String],
+    /// `true` when the caller is on a bypass list and no plugin ran.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bypassed: Option<bool>,
+    /// Would-block responses from plugins in their monitor window.
+    #[serde(skip_serializing_if = "<[AnalyzeResponse]>::is_empty")]
+    pub monitored: &'a [AnalyzeResponse],
+}
+
+impl<'a> TelemetryEvent<'a> {
+    /// Event for the outward `response`; `audit_suppressed` marks a block
+    /// that audit-only mode turned into an allow.
+    pub fn new(
+        response: &'a AnalyzeResponse,
+        audit_suppressed: bool,
+        correlation_id: &'a str,
+        latency_ms: u128,
+        plugin_timings: &'a [(String, u64)],
+        config_hash: &'a str,
+    ) -> Self {
+        let decision = if audit_suppressed {
+            Decision::WouldBlock
+        } else if response.block_action {
+            Decision::Block
+        } else {
+            Decision::Allow
+        };
+        TelemetryEvent {
+            schema_version: TELEMETRY_SCHEMA_VERSION,
+            ts: chrono::Utc::now().to_rfc3339(),
+            correlation_id,
+            request_id: None,
+            decision,
+            severity: decision.severity(),
+            block_action: response.block_action,
+            reason_code: response.reason_code,
+            blocked_by: response.blocked_by.as_deref(),
+            latency_ms,
+            diagnostics: response.diagnostics.as_ref(),
+            audit_suppressed: audit_suppressed.then_some(true),
+            plugin_timings: plugin_timings
+                .iter()
+                .map(|(plugin, ms)| PluginTiming {
+                    plugin,
+                    ms: *ms,
+                    outcome: None,
+                    skip_reason: None,
+                })
+                .collect(),
+            config_hash,
+            plugin_diagnostics: None,
+            skipped_plugins: &[],
+            bypassed: None,
+            monitored: &[],
+        }
+    }
+
+    /// Attach per-plugin outcomes, aligned with `plugin_timings`.
+    pub fn with_outcomes(mut self, outcomes: &[crate::plugins::PluginOutcome]) -> Self {
+        for (timing, outcome) in self.plugin_timings.iter_mut().zip(outcomes) {
+            timing.outcome = Some(*outcome);
+        }
+        self
+    }
+
+    /// Mark `plugins`, skipped because the request was trusted, with
+    /// `skipReason: "trusted"` so audits can tell a check was bypassed.
+    pub fn with_trusted_skips(mut self, plugins: &[String]) -> Self {
+        for timing in &mut self.plugin_timings {
+            if plugins.iter().any(|p| p == timing.plugin) {
+                timing.skip_reason = Some("trusted");
+            }
+        }
+        self
+    }
+}
+
+/// Record of a block that was suppressed in the outward response, by
+/// audit-only mode or (`monitored`) by the plugin's monitor window.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AuditEvent<'a> {
+    pub schema_version: u32,
+    pub ts: String,
+    pub correlation_id: &'a str,
+    /// Same id as the matching telemetry line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_id: Option<&'a str>,
+    pub audit_only: bool,
+    pub would_block: bool,
+    pub would_response: &'a AnalyzeResponse,
+    /// Redacted excerpt of the flagged text.
+    pub preview: String,
+    /// Full request, only with `SENTRA_AUDIT_FULL_REQUEST`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request: Option<&'a AnalyzeRequest>,
+    /// `true` for a would-block from a plugin in its monitor window.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub monitored: Option<bool>,
+}
+
+impl<'a> AuditEvent<'a> {
+    pub fn new(
+        correlation_id: &'a str,
+        would_response: &'a AnalyzeResponse,
+        preview: String,
+    ) -> Self {
+        AuditEvent {
+            schema_version: AUDIT_SCHEMA_VERSION,
+            ts: chrono::Utc::now().to_rfc3339(),
+            correlation_id,
+            request_id: None,
+            audit_only: true,
+            would_block: would_response.block_action,
+            would_response,
+            preview,
+            request: None,
+            monitored: None,
+        }
+    }
+
+    /// Record of `would_response` from a plugin in its monitor window.
+    pub fn monitored(
+        correlation_id: &'a str,
+        would_response: &'a AnalyzeResponse,
+        preview: String,
+    ) -> Self {
+        AuditEvent {
+            audit_only: false,
+            monitored: Some(true),
+            ..Self::new(correlation_id, would_response, preview)
+        }
+    }
+}
+
+/// Short SHA-256 fingerprint of the effective configuration, so telemetry
+/// lines can be grouped by the config that produced them. Stable for a given
+/// build and config; not comparable across Sentra versions.
+pub fn config_hash(
+    plugin_order: &[String],
+    plugin_config: &crate::plugins::PluginConfig,
+) -> String {
+    let material = format!("{:?}|{:?}", plugin_order, plugin_config);
+    let digest = ring::digest::digest(&ring::digest::SHA256, material.as_bytes());
+    digest.as_ref()[..8]
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializes_v2_schema_with_all_keys() {
+        let response = AnalyzeResponse {
+            block_action: true,
+            reason_code: Some(201),
+            reason: Some("Detected AWS key".into()),
+            blocked_by: Some("secrets".into()),
+            diagnostics: Some(serde_json::json!({"code": "aws_key"})),
+        };
+        let timings = vec![("secrets".to_string(), 3)];
+        let event = TelemetryEvent::new(&response, false, "corr-1", 7, &timings, "abcd");
+        let v = serde_json::to_value(&event).unwrap();
+        assert_eq!(v["schemaVersion"], 2);
+        let mut keys: Vec<&str> = v.as_object().unwrap().keys().map(|k| k.as_str()).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            vec![
+                "auditSuppressed",
+                "blockAction",
+                "blockedBy",
+                "configHash",
+                "correlationId",
+                "decision",
+                "diagnostics",
+                "latencyMs",
+                "pluginTimings",
+                "reasonCode",
+                "schemaVersion",
+                "severity",
+                "ts",
+            ]
+        );
+        assert_eq!(v["decision"], "block");
+        assert_eq!(v["severity"], "critical");
+        assert_eq!(v["auditSuppressed"], serde_json::Value::Null);
+        assert_eq!(v["pluginTimings"][0]["plugin"], "secrets");
+
+        let allow = AnalyzeResponse {
+            block_action: false,
+            reason_code: None,
+            reason: None,
+            blocked_by: None,
+            diagnostics: None,
+        };
+        let event = TelemetryEvent::new(&allow, true, "", 1, &[], "abcd");
+        assert_eq!(event.decision, Decision::WouldBlock);
+        assert_eq!(event.severity, Severity::Warning);
+    }
+
+    #[test]
+    fn audit_event_matches_v1_shape() {
+        let response = AnalyzeResponse {
+            block_action: true,
+            reason_code: Some(112),
+            reason: Some("Noncompliant BCC domain.".into()),
+            blocked_by: Some("email_bcc".into()),
+            diagnostics: None,
+        };
+        let event = AuditEvent::new("corr-2", &response, "bcc [redacted]".into());
+        let v = serde_json::to_value(&event).unwrap();
+        let mut keys: Vec<&str> = v.as_object().unwrap().keys().map(|k| k.as_str()).collect();
+        keys.sort_unstable();
+        assert_eq!(
+            keys,
+            vec![
+                "auditOnly",
+                "correlationId",
+                "preview",
+                "schemaVersion",
+                "ts",
+                "wouldBlock",
+                "wouldResponse",
+            ]
+        );
+        assert_eq!(v["schemaVersion"], 1);
+        assert_eq!(v["wouldResponse"]["blockedBy"], "email_bcc");
+
+        let req = AnalyzeRequest::default();
+        let full = AuditEvent {
+            request: Some(&req),
+            ..AuditEvent::new("corr-2", &response, String::new())
+        };
+        assert!(serde_json::to_value(&full).unwrap()["request"].is_object());
+    }
+}
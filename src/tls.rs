@@ -0,0 +1,42 @@
+//! HTTPS for the server binary (feature `tls`).
+//!
+//! `main` serves through `server::serve_tls` when `SENTRA_TLS_CERT` and
+//! `SENTRA_TLS_KEY` are both set, and keeps the plain listener otherwise.
+
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+use std::sync::Arc;
+
+use anyhow::{anyhow, Context};
+use tokio_rustls::rustls::{self, ServerConfig};
+
+/// Load a PEM certificate chain and private key (PKCS#8, PKCS#1 or SEC1)
+/// into a server config. Errors name the offending file.
+pub fn load_config(cert_path: &Path, key_path: &Path) -> anyhow::Result<Arc<ServerConfig>> {
+    let cert_file = File::open(cert_path)
+        .with_context(|| format!("cannot open TLS certificate {}", cert_path.display()))?;
+    let certs = rustls_pemfile::certs(&mut BufReader::new(cert_file))
+        .collect::<Result<Vec<_>, _>>()
+        .with_context(|| format!("invalid PEM in TLS certificate {}", cert_path.display()))?;
+    if certs.is_empty() {
+        return Err(anyhow!(
+            "TLS certificate {} contains no certificates",
+            cert_path.display()
+        ));
+    }
+    let key_file = File::open(key_path)
+        .with_context(|| format!("cannot open TLS key {}", key_path.display()))?;
+    let key = rustls_pemfile::private_key(&mut BufReader::new(key_file))
+        .with_context(|| format!("invalid PEM in TLS key {}", key_path.display()))?
+        .ok_or_else(|| anyhow!("TLS key {} contains no private key", key_path.display()))?;
+
+    let provider = Arc::new(rustls::crypto::ring::default_provider());
+    let config = ServerConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()
+        .context("TLS provider rejected the default protocol versions")?
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("TLS certificate and key do not match")?;
+    Ok(Arc::new(config))
+}
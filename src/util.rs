@@ -10,9 +10,11 @@
 use aho_corasick::{AhoCorasick, AhoCorasickBuilder};
 use dashmap::DashMap;
 use once_cell::sync::Lazy;
+use regex::Regex;
 use serde_json::Value;
 use std::hash::{Hash, Hasher};
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 
 /// A small structure storing fields extracted from the incoming request to
@@ -96,6 +98,160 @@ fn collect(val: &Value, strings: &mut Vec<String>, urls: &mut Vec<String>) {
             urls_lower,
         }
     }
+
+    /// Whether an input value counts as absent: empty or whitespace-only.
+    /// Plugins that inspect a named argument skip such values, so a rule
+    /// never matches on emptiness.
+    pub fn is_blank(value: &str) -> bool {
+        value.trim().is_empty()
+    }
+
+    /// String value of input `name`, or `None` when it is missing, not a
+    /// string, or blank (see `is_blank`).
+    pub fn input_str<'a>(
+        input_values: &'a serde_json::Map<String, Value>,
+        name: &str,
+    ) -> Option<&'a str> {
+        input_values
+            .get(name)
+            .and_then(Value::as_str)
+            .filter(|s| !Self::is_blank(s))
+    }
+
+    /// Append the tool's description and its input/output parameter
+    /// descriptions to `full_text_lower`, so text-scanning plugins see
+    /// instructions hidden in tool metadata.
+    pub fn add_tool_metadata(&mut self, tool: &crate::ToolDefinition) {
+        for text in tool.descriptions() {
+            self.full_text_lower.push_str(&text.to_lowercase());
+            self.full_text_lower.push(' ');
+        }
+    }
+
+    /// Canonicalize `full_text_lower` and `strings` against obfuscation:
+    /// zero-width characters are stripped and, with the
+    /// `unicode-normalization` feature, text is NFKC-folded (fullwidth and
+    /// other compatibility forms become their plain equivalents).
+    pub fn normalize_unicode(&mut self) {
+        self.full_text_lower = canonicalize(&self.full_text_lower);
+        for s in &mut self.strings {
+            *s = canonicalize(s);
+        }
+    }
+}
+
+/// Invisible characters used to split words past substring matching.
+const ZERO_WIDTH: &[char] = &[
+    '\u{00ad}', '\u{180e}', '\u{200b}', '\u{200c}', '\u{200d}', '\u{2060}', '\u{feff}',
+];
+
+fn canonicalize(text: &str) -> String {
+    #[cfg(feature = "unicode-normalization")]
+    let folded: String = unicode_normalization::UnicodeNormalization::nfkc(text)
+        .filter(|c| !ZERO_WIDTH.contains(c))
+        .collect::<String>()
+        .to_lowercase();
+    #[cfg(not(feature = "unicode-normalization"))]
+    let folded: String = text.chars().filter(|c| !ZERO_WIDTH.contains(c)).collect();
+    folded
+}
+
+/// Original-case text of a request: the user message, chat history contents
+/// and every string leaf of `inputValues`. Use this instead of `Precomputed`
+/// when case matters.
+pub fn request_texts(req: &crate::AnalyzeRequest) -> Vec<&str> {
+    fn collect<'a>(val: &'a Value, out: &mut Vec<&'a str>) {
+        match val {
+            Value::String(s) => out.push(s),
+            Value::Array(arr) => arr.iter().for_each(|v| collect(v, out)),
+            Value::Object(map) => map.values().for_each(|v| collect(v, out)),
+            _ => {}
+        }
+    }
+    let mut out = Vec::new();
+    if let Some(msg) = req.planner_context.user_message.as_deref() {
+        out.push(msg);
+    }
+    for item in req.planner_context.chat_history.iter().flatten() {
+        if let Some(content) = item.get("content").and_then(|v| v.as_str()) {
+            out.push(content);
+        }
+    }
+    for v in req.input_values.values() {
+        collect(v, &mut out);
+    }
+    out
+}
+
+/// Split free text into URL candidates: each whitespace-separated token
+/// containing `http://` or `https://`, starting at the scheme and stripped
+/// of trailing punctuation commonly adjacent to links in prose.
+pub fn extract_urls(text: &str) -> Vec<&str> {
+    text.split_whitespace()
+        .filter_map(|token| {
+            let start = token.find("http://").or_else(|| token.find("https://"))?;
+            let mut candidate = token[start..].trim_end_matches(|c: char| {
+                matches!(c, ')' | ']' | '>' | ',' | '.' | ';' | '"' | '\'')
+            });
+            // Keep the closing bracket of an IPv6 host (`http://[::1]`).
+            let rest = &token[start + candidate.len()..];
+            if candidate.contains('[') && !candidate.contains(']') && rest.starts_with(']') {
+                candidate = &token[start..start + candidate.len() + 1];
+            }
+            Some(candidate)
+        })
+        .collect()
+}
+
+/// Source of the current time for `Deadline`. Production code uses
+/// `SystemClock`; tests substitute `ManualClock` to control elapsed time
+/// without sleeping.
+pub trait Clock: Send + Sync + std::fmt::Debug {
+    fn now(&self) -> Instant;
+}
+
+/// The real monotonic clock (`Instant::now`).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// A clock that stands still until `advance` is called.
+#[derive(Debug)]
+pub struct ManualClock {
+    base: Instant,
+    offset_ns: AtomicU64,
+}
+
+impl ManualClock {
+    pub fn new() -> Self {
+        ManualClock {
+            base: Instant::now(),
+            offset_ns: AtomicU64::new(0),
+        }
+    }
+
+    /// Move the clock forward by `by`.
+    pub fn advance(&self, by: Duration) {
+        self.offset_ns
+            .fetch_add(by.as_nanos() as u64, Ordering::Relaxed);
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.base + Duration::from_nanos(self.offset_ns.load(Ordering::Relaxed))
+    }
 }
 
 /// Deadline structure for budgeting plugin execution time.  Calls to
@@ -106,6 +262,7 @@ fn collect(val: &Value, strings: &mut Vec<String>, urls: &mut Vec<String>) {
 pub struct Deadline {
     start: Instant,
     budget: Duration,
+    clock: Arc<dyn Clock>,
 }
 
 impl Deadline {
@@ -113,53 +270,267 @@ impl Deadline {
     /// available compute time.  A 100ms safety margin should be left by
     /// callers to allow for network overhead and serialization.
     pub fn new_ms(ms: u64) -> Self {
+        Self::with_clock(ms, Arc::new(SystemClock))
+    }
+
+    /// Like `new_ms`, but measuring elapsed time on `clock`.
+    pub fn with_clock(ms: u64, clock: Arc<dyn Clock>) -> Self {
         Deadline {
-            start: Instant::now(),
+            start: clock.now(),
             budget: Duration::from_millis(ms),
+            clock,
         }
     }
 
+    fn elapsed(&self) -> Duration {
+        self.clock.now().saturating_duration_since(self.start)
+    }
+
     /// Returns true if the budget has already been exhausted.
     pub fn exceeded(&self) -> bool {
-        self.start.elapsed() >= self.budget
+        self.elapsed() >= self.budget
     }
 
     /// Returns the remaining budget in milliseconds.
     pub fn remaining_ms(&self) -> u64 {
-        self.budget.saturating_sub(self.start.elapsed()).as_millis() as u64
+        self.budget.saturating_sub(self.elapsed()).as_millis() as u64
     }
 }
 
+/// Maximum number of compiled automata kept in `AC_CACHE`. Lists are
+/// typically few and long-lived; the bound only matters when keyword or
+/// policy lists change repeatedly (e.g. across config reloads).
+pub const AC_CACHE_CAPACITY: usize = 64;
+
 /// A memoising wrapper around `AhoCorasick::new` to avoid recompiling
-/// automata for repeated lists.  The cache key is a hash of the pattern list.
-static AC_CACHE: Lazy<DashMap<u64, Arc<AhoCorasick>>> = Lazy::new(DashMap::new);
+/// automata for repeated lists.  The cache key is a hash of the pattern list;
+/// each entry records a last-use tick so the least recently used automaton is
+/// evicted once the capacity is reached.
+struct AcCache {
+    entries: DashMap<u64, (Arc<AhoCorasick>, AtomicU64)>,
+    tick: AtomicU64,
+    /// Held across the capacity check, eviction and insert, so concurrent
+    /// misses cannot push the cache past `capacity`.
+    insert_lock: Mutex<()>,
+    capacity: usize,
+}
+
+impl AcCache {
+    fn new(capacity: usize) -> Self {
+        AcCache {
+            entries: DashMap::new(),
+            tick: AtomicU64::new(0),
+            insert_lock: Mutex::new(()),
+            capacity,
+        }
+    }
+
+    fn get_or_build(&self, list: &[String]) -> Arc<AhoCorasick> {
+        let key = list_key(list);
+        let tick = self.tick.fetch_add(1, Ordering::Relaxed);
+        if let Some(existing) = self.entries.get(&key) {
+            existing.1.store(tick, Ordering::Relaxed);
+            return existing.0.clone();
+        }
+        // Build AC: case insensitive by lower‑casing patterns
+        let mut lower = Vec::with_capacity(list.len());
+        for p in list {
+            lower.push(p.to_lowercase());
+        }
+        let ac = AhoCorasickBuilder::new()
+            .ascii_case_insensitive(true)
+            .build(lower)
+            .unwrap();
+        let arc = Arc::new(ac);
+        let _guard = self
+            .insert_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        // Another miss for the same list may have won the race.
+        if let Some(existing) = self.entries.get(&key) {
+            existing.1.store(tick, Ordering::Relaxed);
+            return existing.0.clone();
+        }
+        while self.entries.len() >= self.capacity {
+            let oldest = self
+                .entries
+                .iter()
+                .min_by_key(|e| e.value().1.load(Ordering::Relaxed))
+                .map(|e| *e.key());
+            match oldest {
+                Some(k) => {
+                    self.entries.remove(&k);
+                }
+                None => break,
+            }
+        }
+        self.entries
+            .insert(key, (arc.clone(), AtomicU64::new(tick)));
+        arc
+    }
+}
+
+/// A stable hash of the pattern list, used as the cache key.
+fn list_key(list: &[String]) -> u64 {
+    let mut hasher = AHasher::default();
+    for pat in list {
+        pat.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+static AC_CACHE: Lazy<AcCache> = Lazy::new(|| AcCache::new(AC_CACHE_CAPACITY));
 
 /// Given a list of literal patterns, return a shared `AhoCorasick` matcher.
 /// If a matcher for the list already exists in the cache, a cloned Arc is
 /// returned.  Otherwise a new matcher is constructed and inserted.  The
 /// caller must ensure that the pattern set does not change between calls.
 pub fn ac_for(list: &[String]) -> Arc<AhoCorasick> {
-    // Compute a stable hash of the pattern list.
-    let mut hasher = AHasher::default();
-    for pat in list {
-        pat.hash(&mut hasher);
+    AC_CACHE.get_or_build(list)
+}
+
+// Tokens masked in audit previews: email addresses, long digit sequences
+// (phones, IBAN/card numbers) possibly split by separators, and long
+// secret-like alphanumeric runs containing a digit.
+static REDACT_RE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"[A-Za-z0-9_.+-]+@[A-Za-z0-9-]+\.[A-Za-z0-9-.]+|\+?\d[\d\s.-]{6,}\d|\b[A-Za-z0-9+/_=-]*\d[A-Za-z0-9+/_=-]*\b",
+    )
+    .unwrap()
+});
+
+/// Replace sensitive-looking tokens (emails, phone/account numbers, long
+/// secret-like runs) in `text` with `[redacted]`.
+pub fn redact(text: &str) -> String {
+    REDACT_RE
+        .replace_all(text, |caps: &regex::Captures<'_>| {
+            let m = &caps[0];
+            if m.contains('@')
+                || m.len() >= 16
+                || m.chars().filter(|c| c.is_ascii_digit()).count() >= 7
+            {
+                "[redacted]".to_string()
+            } else {
+                m.to_string()
+            }
+        })
+        .into_owned()
+}
+
+/// `redact` applied to every string in a JSON value; object keys are kept.
+pub fn redact_json(value: &serde_json::Value) -> serde_json::Value {
+    use serde_json::Value;
+    match value {
+        Value::String(s) => Value::String(redact(s)),
+        Value::Array(items) => Value::Array(items.iter().map(redact_json).collect()),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), redact_json(v)))
+                .collect(),
+        ),
+        other => other.clone(),
     }
-    let key = hasher.finish();
-    if let Some(existing) = AC_CACHE.get(&key) {
-        return existing.clone();
+}
+
+/// Produce a short preview of `text` for audit records: sensitive-looking
+/// tokens are replaced with `[redacted]` first, then the result is cut to at
+/// most `max_chars` characters so a truncated secret can never leak.
+pub fn redacted_preview(text: &str, max_chars: usize) -> String {
+    let redacted = redact(text);
+    let mut preview: String = redacted.chars().take(max_chars).collect();
+    if redacted.chars().nth(max_chars).is_some() {
+        preview.push('…');
     }
-    // Build AC: case insensitive by lower‑casing patterns
-    let mut lower = Vec::with_capacity(list.len());
-    for p in list {
-        lower.push(p.to_lowercase());
+    preview
+}
+
+/// Longest plugin-supplied `reason` passed on to callers and logs.
+pub const MAX_REASON_CHARS: usize = 256;
+
+/// `text` made safe for single-line logs: control characters and Unicode
+/// line/paragraph separators become spaces, and anything past `max_chars`
+/// characters is replaced by `…`.
+pub fn sanitize_text(text: &str, max_chars: usize) -> String {
+    let unsafe_char = |c: char| c.is_control() || matches!(c, '\u{2028}' | '\u{2029}');
+    let mut out: String = text
+        .chars()
+        .take(max_chars)
+        .map(|c| if unsafe_char(c) { ' ' } else { c })
+        .collect();
+    if text.chars().nth(max_chars).is_some() {
+        out.push('…');
     }
-    let ac = AhoCorasickBuilder::new()
-        .ascii_case_insensitive(true)
-        .build(lower)
-        .unwrap();
-    let arc = Arc::new(ac);
-    AC_CACHE.insert(key, arc.clone());
-    arc
+    out
+}
+
+/// Fresh random (version 4) UUID in its hyphenated lowercase form, used to
+/// tag each request in responses, telemetry and audit records.
+pub fn request_id() -> String {
+    use ring::rand::{SecureRandom, SystemRandom};
+    let mut bytes = [0u8; 16];
+    if SystemRandom::new().fill(&mut bytes).is_err() {
+        // Never expected; fall back to something still unique per process.
+        static FALLBACK: AtomicU64 = AtomicU64::new(0);
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos() as u64;
+        bytes[..8].copy_from_slice(&nanos.to_be_bytes());
+        bytes[8..].copy_from_slice(&FALLBACK.fetch_add(1, Ordering::Relaxed).to_be_bytes());
+    }
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// Histogram bucket upper bounds as `bucket_index` needs them: ascending,
+/// without duplicates.
+pub fn histogram_buckets(mut bounds: Vec<u64>) -> Vec<u64> {
+    bounds.sort_unstable();
+    bounds.dedup();
+    bounds
+}
+
+/// Index of the first bucket whose upper bound is at least `value`, or
+/// `None` when `value` exceeds every bound (it then only counts towards
+/// `+Inf`, `_sum` and `_count`). `bounds` must come from
+/// `histogram_buckets`.
+pub fn bucket_index(bounds: &[u64], value: u64) -> Option<usize> {
+    debug_assert!(
+        bounds.windows(2).all(|w| w[0] < w[1]),
+        "histogram bounds must be strictly ascending"
+    );
+    let idx = bounds.partition_point(|ub| *ub < value);
+    (idx < bounds.len()).then_some(idx)
+}
+
+/// Providers whose mailboxes ignore dots in the local part.
+const DOT_INSENSITIVE_EMAIL_DOMAINS: [&str; 2] = ["gmail.com", "googlemail.com"];
+
+/// Canonical form of an email address for comparisons: trimmed and
+/// lower-cased, `+tag` suffixes removed from the local part, a trailing dot
+/// dropped from the domain, and for Gmail addresses dots removed from the
+/// local part and `googlemail.com` folded into `gmail.com`. Strings without
+/// `@` are only trimmed and lower-cased.
+pub fn canonical_email(email: &str) -> String {
+    let email = email.trim().to_lowercase();
+    let Some((local, domain)) = email.rsplit_once('@') else {
+        return email;
+    };
+    let domain = domain.strip_suffix('.').unwrap_or(domain);
+    let local = local.split_once('+').map_or(local, |(base, _)| base);
+    if DOT_INSENSITIVE_EMAIL_DOMAINS.contains(&domain) {
+        return format!("{}@gmail.com", local.replace('.', ""));
+    }
+    format!("{}@{}", local, domain)
 }
 
 /// Evaluation context provided to each plugin.  Contains immutable
@@ -173,6 +544,9 @@ pub struct EvalContext {
     pub deadline: Deadline,
     /// Per-plugin warn threshold (ms) for logging slow plugins.
     pub plugin_warn_ms: u64,
+    /// Request met `PluginConfig::trust`; the pipeline skips the plugins it
+    /// lists. Set by the handler, which knows the caller's token.
+    pub trusted: bool,
 }
 
 impl EvalContext {
@@ -182,22 +556,141 @@ impl EvalContext {
     /// derived from environment variables.
     pub fn from_request(
         req: &crate::AnalyzeRequest,
-        _plugin_config: &crate::plugins::PluginConfig,
+        plugin_config: &crate::plugins::PluginConfig,
         plugin_budget_ms: u64,
         plugin_warn_ms: u64,
     ) -> Self {
         // Build precomputed fields from user message, chat history and input values.
-        let pre = Precomputed::from_request_message(
+        let mut pre = Precomputed::from_request_message(
             req.planner_context.user_message.as_deref(),
             req.planner_context.chat_history.as_deref(),
             &req.input_values,
         );
+        if plugin_config.scan_tool_metadata {
+            pre.add_tool_metadata(&req.tool_definition);
+        }
+        if plugin_config.normalize_unicode {
+            pre.normalize_unicode();
+        }
         // Use provided budget (default configured as 900ms) leaving headroom for IO.
         let deadline = Deadline::new_ms(plugin_budget_ms);
         EvalContext {
             pre: Arc::new(pre),
             deadline,
             plugin_warn_ms,
+            trusted: false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadline_follows_its_clock() {
+        let clock = Arc::new(ManualClock::new());
+        let deadline = Deadline::with_clock(100, clock.clone());
+        assert!(!deadline.exceeded());
+        assert_eq!(deadline.remaining_ms(), 100);
+        clock.advance(Duration::from_millis(60));
+        assert_eq!(deadline.remaining_ms(), 40);
+        clock.advance(Duration::from_millis(40));
+        assert!(deadline.exceeded());
+        assert_eq!(deadline.remaining_ms(), 0);
+    }
+
+    #[test]
+    fn ac_cache_stays_bounded_and_keeps_hot_entries() {
+        // A private cache, so other tests' lists cannot evict or crowd it.
+        let cache = AcCache::new(4);
+        let hot = vec!["hot-keyword".to_string()];
+        let first = cache.get_or_build(&hot);
+        let cold: Vec<Vec<String>> = (0..12)
+            .map(|i| vec![format!("ac-cache-test-{}", i)])
+            .collect();
+        for list in &cold {
+            cache.get_or_build(list);
+            // Touching the hot list keeps it most recently used.
+            cache.get_or_build(&hot);
+            assert!(cache.entries.len() <= 4);
+        }
+        assert!(Arc::ptr_eq(&first, &cache.get_or_build(&hot)));
+        assert_eq!(cache.entries.len(), 4);
+        assert!(!cache.entries.contains_key(&list_key(&cold[0])));
+    }
+
+    #[test]
+    fn ac_cache_bound_holds_under_concurrent_misses() {
+        let cache = Arc::new(AcCache::new(4));
+        let workers: Vec<_> = (0..8)
+            .map(|t| {
+                let cache = cache.clone();
+                std::thread::spawn(move || {
+                    for i in 0..50 {
+                        cache.get_or_build(&[format!("ac-cache-race-{}-{}", t, i)]);
+                        assert!(cache.entries.len() <= 4);
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(cache.entries.len(), 4);
+    }
+
+    #[test]
+    fn sanitize_text_flattens_control_characters_and_caps_length() {
+        assert_eq!(
+            sanitize_text("no\n{\"fake\":true}\r\u{1b}[2J\u{2028}", 100),
+            "no {\"fake\":true}  [2J "
+        );
+        assert_eq!(sanitize_text("abcdef", 3), "abc…");
+        assert_eq!(sanitize_text("日本語", 3), "日本語");
+    }
+
+    #[test]
+    fn request_id_is_a_v4_uuid() {
+        let id = request_id();
+        let groups: Vec<&str> = id.split('-').collect();
+        assert_eq!(
+            groups.iter().map(|g| g.len()).collect::<Vec<_>>(),
+            [8, 4, 4, 4, 12]
+        );
+        assert!(id
+            .chars()
+            .all(|c| c == '-' || matches!(c, '0'..='9' | 'a'..='f')));
+        assert!(groups[2].starts_with('4'));
+        assert!(matches!(groups[3].as_bytes()[0], b'8' | b'9' | b'a' | b'b'));
+        assert_ne!(id, request_id());
+    }
+
+    #[test]
+    fn bucket_index_picks_first_bound_at_or_above_value() {
+        let bounds = histogram_buckets(vec![10, 1, 5, 5]);
+        assert_eq!(bounds, [1, 5, 10]);
+        assert_eq!(bucket_index(&bounds, 0), Some(0));
+        assert_eq!(bucket_index(&bounds, 1), Some(0));
+        assert_eq!(bucket_index(&bounds, 2), Some(1));
+        assert_eq!(bucket_index(&bounds, 5), Some(1));
+        assert_eq!(bucket_index(&bounds, 10), Some(2));
+        assert_eq!(bucket_index(&bounds, 11), None);
+        assert_eq!(bucket_index(&bounds, u64::MAX), None);
+        assert_eq!(bucket_index(&[], 0), None);
+    }
+
+    #[test]
+    fn canonical_email_folds_tags_dots_and_case() {
+        assert_eq!(
+            canonical_email(" Alice+Test@YourCompany.com. "),
+            "alice@yourcompany.com"
+        );
+        assert_eq!(
+            canonical_email("a.li.ce+x@googlemail.com"),
+            "alice@gmail.com"
+        );
+        assert_eq!(canonical_email("a.lice@example.com"), "a.lice@example.com");
+        assert_eq!(canonical_email("Not An Email"), "not an email");
+    }
+}
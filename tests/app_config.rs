@@ -1,5 +1,7 @@
+#![cfg(not(feature = "wasm"))]
+
 use once_cell::sync::Lazy;
-use sentra::AppConfig;
+use sentra::{AppConfig, ServerOptions, UserMessageOverflow};
 use std::sync::Mutex;
 
 static ENV_MUTEX: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
@@ -16,3 +18,218 @@ fn rejects_invalid_boolean_env_values() {
     assert!(format!("{}", err).contains("LOG_ROTATE_COMPRESS"));
     std::env::remove_var("LOG_ROTATE_COMPRESS");
 }
+
+#[test]
+fn server_options_read_http_tuning_env() {
+    let _guard = ENV_MUTEX.lock().unwrap();
+    std::env::set_var("SENTRA_HTTP2", "true");
+    std::env::set_var("SENTRA_HTTP_KEEPALIVE", "false");
+    std::env::set_var("SENTRA_HTTP2_KEEPALIVE_INTERVAL_MS", "15000");
+    let options = ServerOptions::from_env().unwrap();
+    assert!(options.http2);
+    assert!(!options.keep_alive);
+    assert_eq!(options.http2_keep_alive_interval_ms, Some(15000));
+    assert_eq!(options.header_read_timeout_ms, 30_000);
+
+    std::env::set_var("SENTRA_HTTP2_MAX_STREAMS", "99999999999");
+    let err = ServerOptions::from_env().expect_err("stream cap must fit u32");
+    assert!(format!("{}", err).contains("SENTRA_HTTP2_MAX_STREAMS"));
+    for var in [
+        "SENTRA_HTTP2",
+        "SENTRA_HTTP_KEEPALIVE",
+        "SENTRA_HTTP2_KEEPALIVE_INTERVAL_MS",
+        "SENTRA_HTTP2_MAX_STREAMS",
+    ] {
+        std::env::remove_var(var);
+    }
+}
+
+#[test]
+fn rejects_malformed_policies_file() {
+    let _guard = ENV_MUTEX.lock().unwrap();
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    use std::io::Write;
+    write!(file, "{{not json").unwrap();
+    std::env::set_var("SENTRA_POLICIES_FILE", file.path());
+    let err = AppConfig::from_env().expect_err("malformed policies file should error");
+    assert!(format!("{}", err).contains("SENTRA_POLICIES_FILE"));
+    std::env::remove_var("SENTRA_POLICIES_FILE");
+}
+
+#[tokio::test]
+async fn policies_file_rules_fire_like_inline_rules() {
+    use sentra::plugins::PluginPipeline;
+    use sentra::util::EvalContext;
+    use std::io::Write;
+
+    let rule = serde_json::json!({
+        "tool": "sendemail",
+        "arg": "subject",
+        "contains": ["confidential"],
+        "reasonCode": 742,
+        "reason": "Policy: confidential subject"
+    });
+    let mut inline_file = tempfile::NamedTempFile::new().unwrap();
+    write!(inline_file, "{}", serde_json::json!({ "policies": [rule] })).unwrap();
+    let mut rules_file = tempfile::NamedTempFile::new().unwrap();
+    write!(rules_file, "{}", serde_json::json!([rule])).unwrap();
+
+    let (inline_cfg, file_cfg) = {
+        let _guard = ENV_MUTEX.lock().unwrap();
+        std::env::remove_var("SENTRA_POLICIES_FILE");
+        std::env::set_var("SENTRA_PLUGIN_CONFIG", inline_file.path());
+        let inline_cfg = AppConfig::from_env().unwrap();
+        std::env::remove_var("SENTRA_PLUGIN_CONFIG");
+        std::env::set_var("SENTRA_POLICIES_FILE", rules_file.path());
+        let file_cfg = AppConfig::from_env().unwrap();
+        std::env::remove_var("SENTRA_POLICIES_FILE");
+        (inline_cfg, file_cfg)
+    };
+    assert_eq!(file_cfg.plugin_config.policies.len(), 1);
+
+    let req: sentra::AnalyzeRequest = serde_json::from_value(serde_json::json!({
+        "plannerContext": { "userMessage": "Send it" },
+        "toolDefinition": { "name": "SendEmail" },
+        "inputValues": { "subject": "confidential Q4" }
+    }))
+    .unwrap();
+    let order = vec!["policy_pack".to_string()];
+    let mut decisions = Vec::new();
+    for cfg in [&inline_cfg.plugin_config, &file_cfg.plugin_config] {
+        let pipeline = PluginPipeline::new(&order, cfg);
+        let ctx = EvalContext::from_request(&req, cfg, 900, 200);
+        let resp = pipeline
+            .evaluate_with_timings(&req, &ctx, cfg)
+            .await
+            .response;
+        decisions.push(serde_json::to_value(&resp).unwrap());
+    }
+    assert_eq!(decisions[0], decisions[1]);
+    assert_eq!(decisions[0]["reasonCode"], serde_json::json!(742));
+}
+
+#[tokio::test]
+async fn layered_plugin_configs_merge_in_order() {
+    use sentra::plugins::PluginPipeline;
+    use sentra::util::EvalContext;
+    use std::io::Write;
+
+    let mut base = tempfile::NamedTempFile::new().unwrap();
+    write!(
+        base,
+        "{}",
+        serde_json::json!({ "domainBlocklist": ["evil.com"], "company_domain": "base.example" })
+    )
+    .unwrap();
+    let mut overlay = tempfile::NamedTempFile::new().unwrap();
+    write!(
+        overlay,
+        "{}",
+        serde_json::json!({ "domain_blocklist": ["leaky.example"], "company_domain": "corp.example" })
+    )
+    .unwrap();
+
+    let cfg = {
+        let _guard = ENV_MUTEX.lock().unwrap();
+        std::env::remove_var("SENTRA_POLICIES_FILE");
+        std::env::set_var(
+            "SENTRA_PLUGIN_CONFIG",
+            format!("{}, {}", base.path().display(), overlay.path().display()),
+        );
+        let cfg = AppConfig::from_env();
+        std::env::remove_var("SENTRA_PLUGIN_CONFIG");
+        cfg.unwrap().plugin_config
+    };
+    assert_eq!(cfg.company_domain, "corp.example");
+    assert_eq!(cfg.domain_blocklist, vec!["evil.com", "leaky.example"]);
+
+    let pipeline = PluginPipeline::new(&["domain_block".to_string()], &cfg);
+    for url in ["https://evil.com/x", "https://leaky.example/y"] {
+        let req: sentra::AnalyzeRequest = serde_json::from_value(serde_json::json!({
+            "plannerContext": { "userMessage": "fetch" },
+            "toolDefinition": { "name": "Fetch" },
+            "inputValues": { "url": url }
+        }))
+        .unwrap();
+        let ctx = EvalContext::from_request(&req, &cfg, 900, 200);
+        let resp = pipeline
+            .evaluate_with_timings(&req, &ctx, &cfg)
+            .await
+            .response;
+        assert_eq!(resp.reason_code, Some(113), "{url} should be blocked");
+    }
+}
+
+#[test]
+fn user_message_overflow_env_is_validated() {
+    let _guard = ENV_MUTEX.lock().unwrap();
+    std::env::set_var("SENTRA_MAX_USER_MESSAGE_CHARS", "2000");
+    std::env::set_var("SENTRA_USER_MESSAGE_OVERFLOW", "Reject");
+    let cfg = AppConfig::from_env().unwrap();
+    assert_eq!(cfg.max_user_message_chars, Some(2000));
+    assert_eq!(cfg.user_message_overflow, UserMessageOverflow::Reject);
+
+    std::env::set_var("SENTRA_USER_MESSAGE_OVERFLOW", "drop");
+    let err = AppConfig::from_env().expect_err("unknown overflow mode");
+    assert!(err.to_string().contains("SENTRA_USER_MESSAGE_OVERFLOW"));
+    std::env::remove_var("SENTRA_MAX_USER_MESSAGE_CHARS");
+    std::env::remove_var("SENTRA_USER_MESSAGE_OVERFLOW");
+}
+
+#[tokio::test]
+async fn company_domain_env_overrides_plugin_config() {
+    use sentra::plugins::PluginPipeline;
+    use sentra::util::EvalContext;
+    use std::io::Write;
+
+    let mut file = tempfile::NamedTempFile::new().unwrap();
+    write!(
+        file,
+        "{}",
+        serde_json::json!({ "company_domain": "file.example" })
+    )
+    .unwrap();
+    let cfg = {
+        let _guard = ENV_MUTEX.lock().unwrap();
+        std::env::remove_var("SENTRA_POLICIES_FILE");
+        std::env::set_var("SENTRA_PLUGIN_CONFIG", file.path());
+        std::env::set_var("SENTRA_COMPANY_DOMAIN", " corp.example ");
+        std::env::set_var(
+            "SENTRA_COMPANY_DOMAINS",
+            "subsidiary.example, ,partner.example",
+        );
+        let cfg = AppConfig::from_env();
+        for var in [
+            "SENTRA_PLUGIN_CONFIG",
+            "SENTRA_COMPANY_DOMAIN",
+            "SENTRA_COMPANY_DOMAINS",
+        ] {
+            std::env::remove_var(var);
+        }
+        cfg.unwrap().plugin_config
+    };
+    assert_eq!(cfg.company_domain, "corp.example");
+    assert_eq!(
+        cfg.company_domains,
+        vec!["subsidiary.example", "partner.example"]
+    );
+
+    let pipeline = PluginPipeline::new(&["email_bcc".to_string()], &cfg);
+    for (bcc, blocked) in [
+        ("ops@corp.example", false),
+        ("ops@partner.example", false),
+        ("ops@file.example", true),
+    ] {
+        let req = sentra::AnalyzeRequest::builder()
+            .user_message("Send it")
+            .tool("SendEmail")
+            .input("bcc", bcc)
+            .build();
+        let ctx = EvalContext::from_request(&req, &cfg, 900, 200);
+        let resp = pipeline
+            .evaluate_with_timings(&req, &ctx, &cfg)
+            .await
+            .response;
+        assert_eq!(resp.block_action, blocked, "bcc {bcc}");
+    }
+}
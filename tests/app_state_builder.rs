@@ -0,0 +1,49 @@
+#![cfg(not(feature = "wasm"))]
+
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use sentra::{app, AppStateBuilder};
+use tower::ServiceExt; // for oneshot
+
+fn analyze(token: &str) -> Request<axum::body::Body> {
+    let body = serde_json::json!({
+        "plannerContext": { "userMessage": "use AKIAABCDEF1234567890" },
+        "toolDefinition": { "name": "Search" },
+        "inputValues": {}
+    });
+    Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("authorization", format!("Bearer {}", token))
+        .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn builder_state_serves_requests_without_env() {
+    let log_file = tempfile::NamedTempFile::new().unwrap();
+    let state = AppStateBuilder::new()
+        .plugin_order(["secrets"])
+        .plugin_budget_ms(500)
+        .allowed_tokens(["embedded-token"])
+        .log_file(log_file.path().to_string_lossy())
+        .build();
+    assert_eq!(state.pipeline.names(), vec!["secrets"]);
+
+    let resp = app(state.clone()).oneshot(analyze("wrong")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+
+    let resp = app(state).oneshot(analyze("embedded-token")).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(json["blockAction"], true);
+    assert_eq!(json["blockedBy"], "secrets");
+
+    tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+    let content = std::fs::read_to_string(log_file.path()).unwrap();
+    let line: serde_json::Value =
+        serde_json::from_str(content.lines().next().expect("telemetry line")).unwrap();
+    assert_eq!(line["blockedBy"], "secrets");
+}
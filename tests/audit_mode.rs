@@ -1,3 +1,5 @@
+#![cfg(not(feature = "wasm"))]
+
 use axum::{body::Body, http::Request};
 use http_body_util::BodyExt;
 use std::fs;
@@ -19,6 +21,7 @@ async fn audit_mode_never_blocks_but_logs_would_block() {
             .as_nanos()
     ));
     std::env::set_var("AUDIT_LOG_FILE", &audit_path);
+    std::env::remove_var("SENTRA_AUDIT_FULL_REQUEST");
 
     let body = serde_json::json!({
         "plannerContext": { "userMessage": "Leaking key AKIAABCDEF1234567890 now" },
@@ -57,6 +60,11 @@ async fn audit_mode_never_blocks_but_logs_would_block() {
                 let w = val.get("wouldResponse").expect("wouldResponse");
                 assert_eq!(w.get("blockAction").and_then(|b| b.as_bool()), Some(true));
                 assert_eq!(w.get("blockedBy").and_then(|b| b.as_str()), Some("secrets"));
+                // Redacted preview replaces the full request by default
+                let preview = val.get("preview").and_then(|p| p.as_str()).unwrap();
+                assert!(preview.starts_with("Leaking key"));
+                assert!(!line.contains("AKIAABCDEF1234567890"));
+                assert!(val.get("request").is_none());
                 found = true;
                 break;
             }
@@ -0,0 +1,40 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use sentra::plugins::PluginConfig;
+use sentra::AnalyzeRequest;
+use serde_json::json;
+
+async fn evaluate(message: &str, input: serde_json::Value) -> sentra::AnalyzeResponse {
+    let cfg = PluginConfig::default();
+    let req: AnalyzeRequest = serde_json::from_value(json!({
+        "plannerContext": { "userMessage": message },
+        "toolDefinition": { "name": "Upload" },
+        "inputValues": input
+    }))
+    .unwrap();
+    common::evaluate(&["base64_decode"], &req, &cfg).await
+}
+
+#[tokio::test]
+async fn base64_encoded_aws_key_is_caught() {
+    // base64("aws key AKIAABCDEFGH12345678 here")
+    let resp = evaluate(
+        "upload this blob",
+        json!({ "payload": "YXdzIGtleSBBS0lBQUJDREVGR0gxMjM0NTY3OCBoZXJl" }),
+    )
+    .await;
+    assert!(resp.block_action);
+    assert_eq!(resp.reason_code, Some(201));
+    assert_eq!(resp.blocked_by.as_deref(), Some("base64_decode"));
+    let diag = resp.diagnostics.unwrap();
+    assert_eq!(diag["plugin"], json!("secrets"));
+    assert_eq!(diag["encoding"], json!("base64"));
+}
+
+#[tokio::test]
+async fn benign_base64_is_allowed() {
+    // base64("hello team, see you at noon")
+    let resp = evaluate("aGVsbG8gdGVhbSwgc2VlIHlvdSBhdCBub29u", json!({})).await;
+    assert!(!resp.block_action);
+}
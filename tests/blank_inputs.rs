@@ -0,0 +1,60 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use sentra::plugins::PluginConfig;
+use sentra::{AnalyzeRequest, AnalyzeResponse};
+use serde_json::{json, Value};
+
+async fn evaluate(plugin: &str, cfg: Value, tool: &str, input: Value) -> AnalyzeResponse {
+    let cfg: PluginConfig = serde_json::from_value(cfg).unwrap();
+    let req: AnalyzeRequest = serde_json::from_value(json!({
+        "plannerContext": { "userMessage": "send the summary" },
+        "toolDefinition": { "name": tool },
+        "inputValues": input
+    }))
+    .unwrap();
+    common::evaluate(&[plugin], &req, &cfg).await
+}
+
+#[tokio::test]
+async fn blank_bcc_is_ignored() {
+    let cfg = json!({ "companyDomain": "contoso.com" });
+    for bcc in ["", "   ", "\t\n"] {
+        let resp = evaluate("email_bcc", cfg.clone(), "SendEmail", json!({ "bcc": bcc })).await;
+        assert!(!resp.block_action, "bcc {bcc:?}");
+    }
+    let resp = evaluate(
+        "email_bcc",
+        cfg,
+        "SendEmail",
+        json!({ "bcc": "x@evil.com" }),
+    )
+    .await;
+    assert_eq!(resp.blocked_by.as_deref(), Some("email_bcc"));
+}
+
+#[tokio::test]
+async fn policy_arg_does_not_match_blank_values() {
+    let cfg = json!({
+        "policies": [
+            { "arg": "note", "contains": [""], "reasonCode": 751 },
+            { "arg": "/meta/tags", "contains": [""], "reasonCode": 752 }
+        ]
+    });
+    let blank = json!({ "note": "  ", "meta": { "tags": ["", " "] } });
+    let resp = evaluate("policy_pack", cfg.clone(), "SaveNote", blank).await;
+    assert!(!resp.block_action);
+
+    let resp = evaluate(
+        "policy_pack",
+        cfg.clone(),
+        "SaveNote",
+        json!({ "note": "hi" }),
+    )
+    .await;
+    assert_eq!(resp.reason_code, Some(751));
+
+    let tagged = json!({ "note": "", "meta": { "tags": ["", "urgent"] } });
+    let resp = evaluate("policy_pack", cfg, "SaveNote", tagged).await;
+    assert_eq!(resp.reason_code, Some(752));
+}
@@ -1,5 +1,12 @@
+// Each test crate uses only part of these helpers.
+#![allow(dead_code)]
+
 use std::collections::HashMap;
 
+use sentra::plugins::{PluginConfig, PluginPipeline};
+use sentra::util::EvalContext;
+use sentra::{AnalyzeRequest, AnalyzeResponse};
+
 /// Tracks environment variable mutations and restores originals on drop.
 pub struct EnvGuard {
     originals: HashMap<String, Option<String>>,
@@ -17,14 +24,12 @@ pub fn set(&mut self, key: &str, value: &str) {
         std::env::set_var(key, value);
     }
 
-    #[allow(dead_code)]
     pub fn set_many(&mut self, entries: &[(&str, &str)]) {
         for (key, value) in entries {
             self.set(key, value);
         }
     }
 
-    #[allow(dead_code)]
     pub fn remove(&mut self, key: &str) {
         self.capture(key);
         std::env::remove_var(key);
@@ -49,3 +54,30 @@ fn drop(&mut self) {
         }
     }
 }
+
+/// Run `req` through a pipeline of `plugins` built from `cfg`, within the
+/// usual 900 ms budget, and return the decision.
+pub async fn evaluate(
+    plugins: &[impl AsRef<str>],
+    req: &AnalyzeRequest,
+    cfg: &PluginConfig,
+) -> AnalyzeResponse {
+    evaluate_within(plugins, req, cfg, 900, 200).await
+}
+
+/// `evaluate` with an explicit plugin budget and warn threshold.
+pub async fn evaluate_within(
+    plugins: &[impl AsRef<str>],
+    req: &AnalyzeRequest,
+    cfg: &PluginConfig,
+    budget_ms: u64,
+    warn_ms: u64,
+) -> AnalyzeResponse {
+    let order: Vec<String> = plugins.iter().map(|p| p.as_ref().to_string()).collect();
+    let pipeline = PluginPipeline::new(&order, cfg);
+    let ctx = EvalContext::from_request(req, cfg, budget_ms, warn_ms);
+    pipeline
+        .evaluate_with_timings(req, &ctx, cfg)
+        .await
+        .response
+}
@@ -0,0 +1,79 @@
+use sentra::plugins::{PluginConfig, PluginPipeline};
+use sentra::util::EvalContext;
+use sentra::AnalyzeRequest;
+use serde_json::json;
+
+fn bcc_request(bcc: &str) -> AnalyzeRequest {
+    serde_json::from_value(json!({
+        "plannerContext": { "userMessage": "Send the report" },
+        "toolDefinition": { "name": "SendEmail" },
+        "inputValues": { "bcc": bcc }
+    }))
+    .unwrap()
+}
+
+async fn blocks(plugin: &str, cfg: &PluginConfig, req: &AnalyzeRequest) -> bool {
+    let pipeline = PluginPipeline::new(&[plugin.to_string()], cfg);
+    let ctx = EvalContext::from_request(req, cfg, 900, 200);
+    pipeline
+        .evaluate_with_timings(req, &ctx, cfg)
+        .await
+        .response
+        .block_action
+}
+
+fn config(match_subdomains: bool) -> PluginConfig {
+    serde_json::from_value(json!({
+        "company_domain": "company.com",
+        "companyDomainMatchSubdomains": match_subdomains
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn subdomain_addresses_are_internal_when_enabled() {
+    let cfg = config(true);
+    for plugin in ["email_bcc", "pii"] {
+        assert!(!blocks(plugin, &cfg, &bcc_request("ops@eu.company.com")).await);
+        assert!(!blocks(plugin, &cfg, &bcc_request("ops@company.com")).await);
+        // Suffix must fall on a label boundary.
+        assert!(blocks(plugin, &cfg, &bcc_request("ops@evilcompany.com")).await);
+    }
+}
+
+#[tokio::test]
+async fn subdomain_addresses_are_external_when_disabled() {
+    let cfg = config(false);
+    for plugin in ["email_bcc", "pii"] {
+        assert!(blocks(plugin, &cfg, &bcc_request("ops@eu.company.com")).await);
+        assert!(!blocks(plugin, &cfg, &bcc_request("ops@company.com")).await);
+    }
+}
+
+#[tokio::test]
+async fn default_company_domain_honours_subdomain_flag() {
+    let mut cfg = PluginConfig::default();
+    let req = bcc_request("user@mail.yourcompany.com");
+    assert!(
+        blocks("email_bcc", &cfg, &req).await,
+        "exact match by default"
+    );
+    cfg.company_domain_match_subdomains = true;
+    assert!(!blocks("email_bcc", &cfg, &req).await);
+}
+
+#[tokio::test]
+async fn canonicalized_addresses_compare_like_their_plain_form() {
+    let mut cfg = PluginConfig::default();
+    for plugin in ["email_bcc", "pii"] {
+        assert!(!blocks(plugin, &cfg, &bcc_request("alice@yourcompany.com")).await);
+        assert!(!blocks(plugin, &cfg, &bcc_request("alice+test@yourcompany.com")).await);
+        assert!(blocks(plugin, &cfg, &bcc_request("alice@yourcompany.com.")).await);
+    }
+    cfg.canonicalize_emails = true;
+    for plugin in ["email_bcc", "pii"] {
+        assert!(!blocks(plugin, &cfg, &bcc_request("alice+test@yourcompany.com")).await);
+        assert!(!blocks(plugin, &cfg, &bcc_request("Alice@YourCompany.com.")).await);
+        assert!(blocks(plugin, &cfg, &bcc_request("alice+yourcompany.com@evil.com")).await);
+    }
+}
@@ -0,0 +1,70 @@
+use std::sync::Arc;
+
+use sentra::plugins::{Plugin, PluginConfig, PluginPipeline};
+use sentra::util::EvalContext;
+use sentra::{AnalyzeRequest, AnalyzeResponse};
+use serde_json::json;
+
+/// Blocks any tool whose name is on a deny list.
+struct DenyTool(&'static str);
+
+#[async_trait::async_trait]
+impl Plugin for DenyTool {
+    fn name(&self) -> &str {
+        "deny_tool"
+    }
+
+    async fn eval(
+        &self,
+        req: &AnalyzeRequest,
+        _ctx: &EvalContext,
+        _cfg: &PluginConfig,
+    ) -> Option<AnalyzeResponse> {
+        let tool = req.tool_definition.name.as_deref()?;
+        (tool == self.0).then(|| AnalyzeResponse {
+            block_action: true,
+            reason_code: Some(950),
+            reason: Some(format!("{tool} is not allowed")),
+            blocked_by: None,
+            diagnostics: None,
+        })
+    }
+}
+
+fn request(tool: &str) -> AnalyzeRequest {
+    serde_json::from_value(json!({
+        "plannerContext": { "userMessage": "tidy up" },
+        "toolDefinition": { "name": tool },
+        "inputValues": {}
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn custom_plugin_runs_through_pipeline() {
+    let cfg = PluginConfig::default();
+    let pipeline = PluginPipeline::from_plugins(vec![
+        Arc::new(DenyTool("DeleteRepo")),
+        Arc::new(DenyTool("Ignored")),
+    ]);
+    assert_eq!(pipeline.names(), vec!["deny_tool"], "repeats are dropped");
+
+    let req = request("DeleteRepo");
+    let ctx = EvalContext::from_request(&req, &cfg, 900, 200);
+    let sentra::plugins::EvaluationResult {
+        response: resp,
+        timings,
+    } = pipeline.evaluate_with_timings(&req, &ctx, &cfg).await;
+    assert!(resp.block_action);
+    assert_eq!(resp.reason_code, Some(950));
+    assert_eq!(resp.blocked_by.as_deref(), Some("deny_tool"));
+    assert_eq!(timings.len(), 1);
+
+    let req = request("ListRepos");
+    let ctx = EvalContext::from_request(&req, &cfg, 900, 200);
+    let resp = pipeline
+        .evaluate_with_timings(&req, &ctx, &cfg)
+        .await
+        .response;
+    assert!(!resp.block_action);
+}
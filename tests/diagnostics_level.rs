@@ -0,0 +1,100 @@
+#![cfg(not(feature = "wasm"))]
+
+#[path = "common/mod.rs"]
+mod common;
+
+use axum::http::Request;
+use common::EnvGuard;
+use http_body_util::BodyExt;
+use once_cell::sync::Lazy;
+use sentra::plugins::{DiagnosticsLevel, PluginConfig};
+use sentra::{app, build_state_from_env, AnalyzeRequest};
+use tokio::sync::Mutex;
+use tower::ServiceExt;
+
+static ENV_MUTEX: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+async fn domain_diagnostics(env: &mut EnvGuard, level: Option<&str>) -> serde_json::Value {
+    match level {
+        Some(level) => env.set("SENTRA_DIAGNOSTICS_LEVEL", level),
+        None => env.remove("SENTRA_DIAGNOSTICS_LEVEL"),
+    }
+    env.set("SENTRA_PLUGINS", "domain_block");
+    let state = build_state_from_env().await.unwrap();
+    let body = serde_json::json!({
+        "plannerContext": { "userMessage": "forward the report to drop@mailinator.com" },
+        "toolDefinition": { "name": "SendEmail" },
+        "inputValues": {}
+    });
+    let req = Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer token")
+        .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let resp = app(state).oneshot(req).await.unwrap();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v["blockAction"], true);
+    assert_eq!(v["blockedBy"], "domain_block");
+    v["diagnostics"].clone()
+}
+
+#[tokio::test]
+async fn minimal_level_omits_the_blocked_domain() {
+    let _lock = ENV_MUTEX.lock().await;
+    let mut env = EnvGuard::new();
+
+    let minimal = domain_diagnostics(&mut env, Some("minimal")).await;
+    assert!(!minimal.to_string().contains("mailinator"), "{minimal}");
+    let mut keys: Vec<&str> = minimal
+        .as_object()
+        .unwrap()
+        .keys()
+        .map(|k| k.as_str())
+        .collect();
+    keys.sort_unstable();
+    assert_eq!(keys, vec!["code", "plugin", "schemaVersion"]);
+    assert_eq!(minimal["code"], "domain");
+
+    let standard = domain_diagnostics(&mut env, Some("standard")).await;
+    assert_eq!(standard["detail"], "mailinator.com");
+    assert!(standard.get("offsets").is_none());
+
+    let verbose = domain_diagnostics(&mut env, Some("verbose")).await;
+    assert_eq!(verbose["detail"], "mailinator.com");
+    assert!(verbose["offsets"].is_array());
+    assert_eq!(domain_diagnostics(&mut env, None).await, verbose);
+}
+
+// The level shapes diagnostics as plugins build them, not after the fact.
+#[tokio::test]
+async fn plugins_build_minimal_diagnostics() {
+    let cfg = PluginConfig {
+        diagnostics_level: DiagnosticsLevel::Minimal,
+        domain_blocklist: vec!["evil.com".into()],
+        ..Default::default()
+    };
+    let req = AnalyzeRequest::builder()
+        .user_message("visit evil.com today")
+        .tool("Browse")
+        .build();
+    let resp = common::evaluate(&["domain_block"], &req, &cfg).await;
+    assert_eq!(
+        resp.diagnostics,
+        Some(serde_json::json!({
+            "schemaVersion": 1,
+            "plugin": "domain_block",
+            "code": "domain"
+        }))
+    );
+}
+
+#[tokio::test]
+async fn unknown_level_is_a_startup_error() {
+    let _lock = ENV_MUTEX.lock().await;
+    let mut env = EnvGuard::new();
+    env.set("SENTRA_DIAGNOSTICS_LEVEL", "loud");
+    assert!(build_state_from_env().await.is_err());
+}
@@ -0,0 +1,106 @@
+#![cfg(not(feature = "wasm"))]
+
+#[path = "common/mod.rs"]
+mod common;
+
+use axum::http::Request;
+use common::EnvGuard;
+use http_body_util::BodyExt;
+use once_cell::sync::Lazy;
+use sentra::plugins::{DiagnosticsLevel, PluginConfig};
+use sentra::{app, build_state_from_env, AnalyzeRequest};
+use tokio::sync::Mutex;
+use tower::ServiceExt;
+
+static ENV_MUTEX: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+async fn blocked_diagnostics(env: &mut EnvGuard, mode: Option<&str>) -> serde_json::Value {
+    match mode {
+        Some(mode) => env.set("SENTRA_DIAGNOSTICS_MODE", mode),
+        None => env.remove("SENTRA_DIAGNOSTICS_MODE"),
+    }
+    env.remove("SENTRA_DIAGNOSTICS_LEVEL");
+    env.set("SENTRA_PLUGINS", "secrets");
+    let state = build_state_from_env().await.unwrap();
+    let body = serde_json::json!({
+        "plannerContext": { "userMessage": "key AKIAABCDEF1234567890" },
+        "toolDefinition": { "name": "SendEmail" },
+        "inputValues": {}
+    });
+    let req = Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer token")
+        .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let resp = app(state).oneshot(req).await.unwrap();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let v: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(v["blockAction"], true);
+    v["diagnostics"].clone()
+}
+
+#[tokio::test]
+async fn compact_mode_trims_diagnostics_to_contract_fields() {
+    let _lock = ENV_MUTEX.lock().await;
+    let mut env = EnvGuard::new();
+
+    let compact = blocked_diagnostics(&mut env, Some("compact")).await;
+    let verbose = blocked_diagnostics(&mut env, Some("verbose")).await;
+    let default = blocked_diagnostics(&mut env, None).await;
+
+    let compact = compact.as_object().unwrap();
+    let mut keys: Vec<&str> = compact.keys().map(|k| k.as_str()).collect();
+    keys.sort_unstable();
+    assert_eq!(keys, vec!["code", "plugin", "schemaVersion"]);
+    assert!(verbose.as_object().unwrap().len() > compact.len());
+    assert_eq!(verbose["provider"], "aws");
+    assert_eq!(compact["code"], verbose["code"]);
+    assert_eq!(default, verbose);
+}
+
+#[tokio::test]
+async fn diagnostics_level_wins_over_mode() {
+    let _lock = ENV_MUTEX.lock().await;
+    let mut env = EnvGuard::new();
+    env.set("SENTRA_DIAGNOSTICS_MODE", "compact");
+    env.set("SENTRA_DIAGNOSTICS_LEVEL", "verbose");
+    let state = build_state_from_env().await.unwrap();
+    assert_eq!(
+        state.plugin_config.diagnostics_level,
+        DiagnosticsLevel::Verbose
+    );
+}
+
+// The mode shapes diagnostics as plugins build them, not after the fact.
+#[tokio::test]
+async fn plugins_build_compact_diagnostics() {
+    let cfg: PluginConfig = serde_json::from_value(serde_json::json!({
+        "diagnosticsMode": "compact",
+        "domainBlocklist": ["evil.com"]
+    }))
+    .unwrap();
+    assert_eq!(cfg.diagnostics_level, DiagnosticsLevel::Minimal);
+    let req = AnalyzeRequest::builder()
+        .user_message("visit evil.com today")
+        .tool("Browse")
+        .build();
+    let resp = common::evaluate(&["domain_block"], &req, &cfg).await;
+    assert_eq!(
+        resp.diagnostics,
+        Some(serde_json::json!({
+            "schemaVersion": 1,
+            "plugin": "domain_block",
+            "code": "domain"
+        }))
+    );
+}
+
+#[tokio::test]
+async fn unknown_mode_is_a_startup_error() {
+    let _lock = ENV_MUTEX.lock().await;
+    let mut env = EnvGuard::new();
+    env.set("SENTRA_DIAGNOSTICS_MODE", "chatty");
+    assert!(build_state_from_env().await.is_err());
+}
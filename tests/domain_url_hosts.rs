@@ -0,0 +1,52 @@
+use sentra::plugins::{PluginConfig, PluginPipeline};
+use sentra::util::EvalContext;
+use sentra::AnalyzeRequest;
+
+async fn decide(url: &str, cfg: &PluginConfig) -> sentra::AnalyzeResponse {
+    let pipeline = PluginPipeline::new(&["domain_block".to_string()], cfg);
+    let req = AnalyzeRequest::builder()
+        .user_message("fetch this")
+        .tool("Fetch")
+        .input("url", url)
+        .build();
+    let ctx = EvalContext::from_request(&req, cfg, 900, 200);
+    pipeline
+        .evaluate_with_timings(&req, &ctx, cfg)
+        .await
+        .response
+}
+
+#[tokio::test]
+async fn ipv6_and_mixed_case_hosts_match_parsed_host() {
+    let cfg = PluginConfig {
+        domain_blocklist: vec!["Evil.COM".into(), "2001:db8::1".into()],
+        domain_host_only: true,
+        ..Default::default()
+    };
+    for url in [
+        "http://[2001:db8::1]/",
+        "http://[2001:DB8:0::1]:8080",
+        "https://API.Evil.Com:8443/v1",
+        "https://user@evil.com/x",
+    ] {
+        let resp = decide(url, &cfg).await;
+        assert_eq!(resp.reason_code, Some(113), "{url} should be blocked");
+    }
+}
+
+#[tokio::test]
+async fn blocked_domain_in_path_does_not_match_host() {
+    let cfg = PluginConfig {
+        domain_blocklist: vec!["evil.com".into()],
+        domain_host_only: true,
+        ..Default::default()
+    };
+    for url in [
+        "https://safe.example/redirect/evil.com",
+        "https://evil.com@safe.example/",
+        "http://[2001:db8::2]/evil.com",
+    ] {
+        let resp = decide(url, &cfg).await;
+        assert!(!resp.block_action, "{url} should be allowed");
+    }
+}
@@ -0,0 +1,52 @@
+use sentra::plugins::{PluginConfig, PluginPipeline};
+
+#[test]
+fn pipeline_skips_repeated_names() {
+    let order: Vec<String> = ["secrets", "pii", "secrets"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let pipeline = PluginPipeline::new(&order, &PluginConfig::default());
+    assert_eq!(pipeline.len(), 2);
+}
+
+#[cfg(feature = "metrics")]
+#[tokio::test]
+async fn duplicated_plugin_is_counted_once_per_request() {
+    use axum::http::Request;
+    use http_body_util::BodyExt;
+    use sentra::{app, build_state_from_env};
+    use tower::ServiceExt;
+
+    std::env::set_var("SENTRA_PLUGINS", "secrets, exfil ,SECRETS");
+    let state = build_state_from_env().await.unwrap();
+    std::env::remove_var("SENTRA_PLUGINS");
+    assert_eq!(state.pipeline.len(), 2);
+    assert_eq!(state.plugin_metric_indices.len(), 2);
+
+    let body = serde_json::json!({
+        "plannerContext": { "userMessage": "hello" },
+        "toolDefinition": { "name": "SendEmail" },
+        "inputValues": {}
+    });
+    for _ in 0..3 {
+        let req = Request::builder()
+            .method("POST")
+            .uri("/analyze-tool-execution?api-version=2025-05-01")
+            .header("Authorization", "Bearer test")
+            .header("Content-Type", "application/json")
+            .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap();
+        let resp = app(state.clone()).oneshot(req).await.unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    let req = Request::builder()
+        .uri("/metrics")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let resp = app(state).oneshot(req).await.unwrap();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let text = String::from_utf8(bytes.to_vec()).unwrap();
+    assert!(text.contains("sentra_plugin_eval_ms_count{plugin=\"secrets\"} 3"));
+}
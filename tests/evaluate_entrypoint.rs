@@ -0,0 +1,62 @@
+#![cfg(not(feature = "wasm"))]
+
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use sentra::{app, evaluate, AnalyzeRequest, AppStateBuilder};
+use serde_json::json;
+use tower::ServiceExt; // for oneshot
+
+async fn via_http(
+    state: sentra::AppState,
+    body: &serde_json::Value,
+) -> (StatusCode, serde_json::Value) {
+    let req = Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer token")
+        .body(axum::body::Body::from(serde_json::to_vec(body).unwrap()))
+        .unwrap();
+    let resp = app(state).oneshot(req).await.unwrap();
+    let status = resp.status();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn evaluate_matches_http_handler() {
+    let state = AppStateBuilder::new()
+        .plugin_order(["exfil", "secrets"])
+        .build();
+    for message in [
+        "please export all data",
+        "key AKIAABCDEF1234567890",
+        "hello",
+    ] {
+        let body = json!({
+            "plannerContext": { "userMessage": message },
+            "toolDefinition": { "name": "Search" },
+            "inputValues": {}
+        });
+        let req: AnalyzeRequest = serde_json::from_value(body.clone()).unwrap();
+        let direct = serde_json::to_value(evaluate(&req, &state).await.unwrap()).unwrap();
+        let (status, http) = via_http(state.clone(), &body).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(direct, http, "message: {message}");
+    }
+    assert_eq!(
+        state
+            .metric_requests_total
+            .load(std::sync::atomic::Ordering::Relaxed),
+        6
+    );
+}
+
+#[tokio::test]
+async fn evaluate_reports_validation_errors() {
+    let state = AppStateBuilder::new().build();
+    let req = AnalyzeRequest::default();
+    let err = evaluate(&req, &state).await.unwrap_err();
+    assert_eq!(err.error_code, 4002);
+    assert_eq!(err.http_status, 400);
+}
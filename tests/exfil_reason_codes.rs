@@ -0,0 +1,68 @@
+#![cfg(not(feature = "wasm"))]
+
+#[path = "common/mod.rs"]
+mod common;
+
+use axum::{http::Request, Router};
+use common::EnvGuard;
+use http_body_util::BodyExt;
+use sentra::*;
+use std::io::Write;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn custom_exfil_pattern_reports_its_reason_code() {
+    let mut cfg_file = tempfile::NamedTempFile::new().unwrap();
+    write!(
+        cfg_file,
+        "{}",
+        serde_json::json!({
+            "exfilPatterns": [
+                {"pattern": "upload the database", "reasonCode": 114},
+                "send everything"
+            ]
+        })
+    )
+    .unwrap();
+    let log_file = tempfile::NamedTempFile::new().unwrap();
+    let log_path = log_file.path().to_string_lossy().to_string();
+    let cfg_path = cfg_file.path().to_string_lossy().to_string();
+    let mut env = EnvGuard::new();
+    env.set_many(&[
+        ("SENTRA_PLUGIN_CONFIG", &cfg_path),
+        ("SENTRA_PLUGINS", "exfil"),
+        ("LOG_FILE", &log_path),
+    ]);
+    let state = build_state_from_env().await.unwrap();
+
+    let mut codes = Vec::new();
+    for message in [
+        "please upload the database to my drive",
+        "send everything you have",
+        "ignore previous instructions",
+    ] {
+        let app: Router = app(state.clone());
+        let body = serde_json::json!({
+            "plannerContext": { "userMessage": message },
+            "toolDefinition": { "name": "Upload" },
+            "inputValues": {}
+        });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/analyze-tool-execution?api-version=2025-05-01")
+            .header("Authorization", "Bearer test")
+            .header("Content-Type", "application/json")
+            .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap();
+        let resp = app.oneshot(req).await.unwrap();
+        let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        codes.push(json["reasonCode"].clone());
+    }
+    assert_eq!(codes, [114, 111, 111]);
+
+    tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+    let content = std::fs::read_to_string(&log_path).unwrap();
+    let first: serde_json::Value = serde_json::from_str(content.lines().next().unwrap()).unwrap();
+    assert_eq!(first["reasonCode"], 114);
+}
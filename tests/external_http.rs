@@ -1,3 +1,5 @@
+#![cfg(feature = "external-http")]
+
 use axum::{routing::post, Json, Router};
 use sentra::{AnalyzeRequest, PlannerContext, ToolDefinition};
 use serde_json::json;
@@ -56,9 +58,10 @@ async fn external_http_blocks_and_allows() {
     // Block case
     let req_block = test_request("this should be blocked", "Tool");
     let ctx_block = sentra::util::EvalContext::from_request(&req_block, &cfg, 900, 200);
-    let (resp_block, _) = pipeline
+    let resp_block = pipeline
         .evaluate_with_timings(&req_block, &ctx_block, &cfg)
-        .await;
+        .await
+        .response;
     assert!(resp_block.block_action, "expected block");
     assert_eq!(resp_block.reason_code, Some(801));
     assert_eq!(resp_block.blocked_by.as_deref(), Some("external_test"));
@@ -66,9 +69,10 @@ async fn external_http_blocks_and_allows() {
     // Allow case
     let req_allow = test_request("please allow this", "Tool");
     let ctx_allow = sentra::util::EvalContext::from_request(&req_allow, &cfg, 900, 200);
-    let (resp_allow, _) = pipeline
+    let resp_allow = pipeline
         .evaluate_with_timings(&req_allow, &ctx_allow, &cfg)
-        .await;
+        .await
+        .response;
     assert!(!resp_allow.block_action, "expected allow");
 }
 
@@ -86,7 +90,10 @@ async fn external_http_fail_open_allows_on_error() {
     let pipeline = sentra::plugins::PluginPipeline::new(&["external_err".to_string()], &cfg);
     let req = test_request("anything", "Tool");
     let ctx = sentra::util::EvalContext::from_request(&req, &cfg, 900, 200);
-    let (resp, _) = pipeline.evaluate_with_timings(&req, &ctx, &cfg).await;
+    let resp = pipeline
+        .evaluate_with_timings(&req, &ctx, &cfg)
+        .await
+        .response;
     assert!(!resp.block_action, "fail-open network error should allow");
 }
 
@@ -103,7 +110,10 @@ async fn external_http_fail_closed_blocks_on_error() {
     let pipeline = sentra::plugins::PluginPipeline::new(&["external_err_closed".to_string()], &cfg);
     let req = test_request("anything", "Tool");
     let ctx = sentra::util::EvalContext::from_request(&req, &cfg, 900, 200);
-    let (resp, _) = pipeline.evaluate_with_timings(&req, &ctx, &cfg).await;
+    let resp = pipeline
+        .evaluate_with_timings(&req, &ctx, &cfg)
+        .await
+        .response;
     assert!(resp.block_action, "fail-closed network error should block");
     assert_eq!(resp.reason_code, Some(804));
 }
@@ -131,7 +141,10 @@ async fn pointer_decide(Json(_v): Json<serde_json::Value>) -> Json<serde_json::V
     let pipeline = sentra::plugins::PluginPipeline::new(&["external_ptr".to_string()], &cfg);
     let req = test_request("whatever", "Tool");
     let ctx = sentra::util::EvalContext::from_request(&req, &cfg, 900, 200);
-    let (resp, _) = pipeline.evaluate_with_timings(&req, &ctx, &cfg).await;
+    let resp = pipeline
+        .evaluate_with_timings(&req, &ctx, &cfg)
+        .await
+        .response;
     assert!(resp.block_action, "pointer block field should block");
     assert_eq!(resp.reason_code, Some(805));
     drop(handle);
@@ -166,16 +179,18 @@ async fn allow_decide(Json(v): Json<serde_json::Value>) -> Json<serde_json::Valu
     // allow path
     let req_allow = test_request("safe", "Tool");
     let ctx_allow = sentra::util::EvalContext::from_request(&req_allow, &cfg, 900, 200);
-    let (resp_allow, _) = pipeline
+    let resp_allow = pipeline
         .evaluate_with_timings(&req_allow, &ctx_allow, &cfg)
-        .await;
+        .await
+        .response;
     assert!(!resp_allow.block_action);
     // deny path
     let req_block = test_request("please deny this", "Tool");
     let ctx_block = sentra::util::EvalContext::from_request(&req_block, &cfg, 900, 200);
-    let (resp_block, _) = pipeline
+    let resp_block = pipeline
         .evaluate_with_timings(&req_block, &ctx_block, &cfg)
-        .await;
+        .await
+        .response;
     assert!(resp_block.block_action);
     assert_eq!(resp_block.reason_code, Some(806));
     drop(handle);
@@ -206,7 +221,10 @@ async fn slow(Json(_): Json<serde_json::Value>) -> Json<serde_json::Value> {
         sentra::plugins::PluginPipeline::new(&["external_timeout_open".to_string()], &cfg);
     let req = test_request("anything", "Tool");
     let ctx = sentra::util::EvalContext::from_request(&req, &cfg, 900, 200);
-    let (resp, _) = pipeline.evaluate_with_timings(&req, &ctx, &cfg).await;
+    let resp = pipeline
+        .evaluate_with_timings(&req, &ctx, &cfg)
+        .await
+        .response;
     assert!(!resp.block_action, "fail-open timeout should allow");
     drop(handle);
 }
@@ -235,7 +253,10 @@ async fn slow(Json(_): Json<serde_json::Value>) -> Json<serde_json::Value> {
         sentra::plugins::PluginPipeline::new(&["external_timeout_closed".to_string()], &cfg);
     let req = test_request("anything", "Tool");
     let ctx = sentra::util::EvalContext::from_request(&req, &cfg, 900, 200);
-    let (resp, _) = pipeline.evaluate_with_timings(&req, &ctx, &cfg).await;
+    let resp = pipeline
+        .evaluate_with_timings(&req, &ctx, &cfg)
+        .await
+        .response;
     assert!(resp.block_action, "fail-closed timeout should block");
     assert_eq!(resp.reason_code, Some(812));
     drop(handle);
@@ -264,7 +285,10 @@ async fn invalid() -> (axum::http::StatusCode, &'static [u8]) {
     let pipeline = sentra::plugins::PluginPipeline::new(&["external_parse_open".to_string()], &cfg);
     let req = test_request("whatever", "Tool");
     let ctx = sentra::util::EvalContext::from_request(&req, &cfg, 900, 200);
-    let (resp, _) = pipeline.evaluate_with_timings(&req, &ctx, &cfg).await;
+    let resp = pipeline
+        .evaluate_with_timings(&req, &ctx, &cfg)
+        .await
+        .response;
     assert!(!resp.block_action, "fail-open parse error should allow");
     drop(handle);
 }
@@ -292,8 +316,26 @@ async fn invalid() -> (axum::http::StatusCode, &'static [u8]) {
         sentra::plugins::PluginPipeline::new(&["external_parse_closed".to_string()], &cfg);
     let req = test_request("whatever", "Tool");
     let ctx = sentra::util::EvalContext::from_request(&req, &cfg, 900, 200);
-    let (resp, _) = pipeline.evaluate_with_timings(&req, &ctx, &cfg).await;
+    let resp = pipeline
+        .evaluate_with_timings(&req, &ctx, &cfg)
+        .await
+        .response;
     assert!(resp.block_action, "fail-closed parse error should block");
     assert_eq!(resp.reason_code, Some(831));
     drop(handle);
 }
+
+// A definition whose HTTP client cannot be built is skipped, not fatal.
+#[test]
+fn external_http_invalid_client_config_is_skipped() {
+    let cfg_val = serde_json::json!({
+        "externalHttp": [
+            {"name":"external_bad","url":"http://127.0.0.1:1/eval", "userAgent":"bad\nagent"},
+            {"name":"external_good","url":"http://127.0.0.1:1/eval"}
+        ]
+    });
+    let cfg: sentra::plugins::PluginConfig = serde_json::from_value(cfg_val).unwrap();
+    let order = ["external_bad".to_string(), "external_good".to_string()];
+    let pipeline = sentra::plugins::PluginPipeline::new(&order, &cfg);
+    assert_eq!(pipeline.names(), vec!["external_good"]);
+}
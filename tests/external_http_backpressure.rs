@@ -0,0 +1,66 @@
+#![cfg(feature = "external-http")]
+
+#[path = "common/mod.rs"]
+mod common;
+
+use axum::{http::StatusCode, response::IntoResponse, routing::post, Json, Router};
+use sentra::plugins::PluginConfig;
+use sentra::AnalyzeRequest;
+use serde_json::json;
+use std::net::SocketAddr;
+
+// Mock that sheds load: 429 with Retry-After and a body that would otherwise
+// parse as a block decision.
+async fn start_throttling_mock() -> SocketAddr {
+    async fn decide(Json(_v): Json<serde_json::Value>) -> impl IntoResponse {
+        (
+            StatusCode::TOO_MANY_REQUESTS,
+            [("retry-after", "2")],
+            Json(json!({"block": true})),
+        )
+    }
+    let app = Router::new().route("/eval", post(decide));
+    let listener = tokio::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, 0))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    addr
+}
+
+async fn evaluate(addr: SocketAddr, fail_open: bool) -> sentra::AnalyzeResponse {
+    let cfg: PluginConfig = serde_json::from_value(json!({
+        "externalHttp": [{
+            "name": "external_policy",
+            "url": format!("http://{}/eval", addr),
+            "timeoutMs": 1000,
+            "failOpen": fail_open
+        }]
+    }))
+    .unwrap();
+    let req: AnalyzeRequest = serde_json::from_value(json!({
+        "plannerContext": { "userMessage": "hello" },
+        "toolDefinition": { "name": "Tool" },
+        "inputValues": {}
+    }))
+    .unwrap();
+    common::evaluate_within(&["external_policy"], &req, &cfg, 5_000, 2_000).await
+}
+
+#[tokio::test]
+async fn retry_after_is_transient_failure_not_decision() {
+    let addr = start_throttling_mock().await;
+
+    let open = evaluate(addr, true).await;
+    assert!(!open.block_action, "fail-open should allow on 429");
+
+    let closed = evaluate(addr, false).await;
+    assert!(closed.block_action);
+    assert_eq!(closed.reason_code, Some(801));
+    let diag = closed.diagnostics.unwrap();
+    assert_eq!(diag["code"], json!("network_error"));
+    assert_eq!(diag["status"], json!(429));
+    assert_eq!(diag["retryAfter"], json!("2"));
+}
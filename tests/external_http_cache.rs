@@ -0,0 +1,95 @@
+#![cfg(feature = "external-http")]
+
+use axum::{routing::post, Json, Router};
+use sentra::plugins::{PluginConfig, PluginPipeline};
+use sentra::util::EvalContext;
+use sentra::AnalyzeRequest;
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+// Mock whose decision depends only on call order: the first call blocks,
+// every later call allows. Tenant identity never reaches it (default template).
+async fn start_mock(calls: Arc<AtomicUsize>) -> SocketAddr {
+    let app = Router::new().route(
+        "/eval",
+        post(move |Json(_v): Json<serde_json::Value>| {
+            let calls = calls.clone();
+            async move {
+                let n = calls.fetch_add(1, Ordering::SeqCst);
+                Json(json!({"block": n == 0}))
+            }
+        }),
+    );
+    let listener = tokio::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, 0))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    addr
+}
+
+fn request(tenant: &str) -> AnalyzeRequest {
+    serde_json::from_value(json!({
+        "plannerContext": { "userMessage": "same message" },
+        "toolDefinition": { "name": "SendEmail" },
+        "inputValues": {},
+        "conversationMetadata": { "agent": { "tenantId": tenant } }
+    }))
+    .unwrap()
+}
+
+async fn blocked(pipeline: &PluginPipeline, cfg: &PluginConfig, tenant: &str) -> bool {
+    let req = request(tenant);
+    let ctx = EvalContext::from_request(&req, cfg, 900, 200);
+    pipeline
+        .evaluate_with_timings(&req, &ctx, cfg)
+        .await
+        .response
+        .block_action
+}
+
+fn config(addr: SocketAddr, key_fields: serde_json::Value) -> PluginConfig {
+    serde_json::from_value(json!({
+        "externalHttp": [{
+            "name": "external_policy",
+            "url": format!("http://{}/eval", addr),
+            "timeoutMs": 1000,
+            "cacheTtlMs": 60000,
+            "cacheKeyFields": key_fields
+        }]
+    }))
+    .unwrap()
+}
+
+#[tokio::test]
+async fn tenant_key_field_separates_cache_entries() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let addr = start_mock(calls.clone()).await;
+    let cfg = config(addr, json!(["tenant_id", "tool_name"]));
+    let pipeline = PluginPipeline::new(&["external_policy".to_string()], &cfg);
+
+    assert!(blocked(&pipeline, &cfg, "tenant-a").await);
+    assert!(!blocked(&pipeline, &cfg, "tenant-b").await);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+    // Both decisions are now served from the cache.
+    assert!(blocked(&pipeline, &cfg, "tenant-a").await);
+    assert!(!blocked(&pipeline, &cfg, "tenant-b").await);
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
+
+#[tokio::test]
+async fn body_only_key_shares_decisions_across_tenants() {
+    let calls = Arc::new(AtomicUsize::new(0));
+    let addr = start_mock(calls.clone()).await;
+    let cfg = config(addr, json!([]));
+    let pipeline = PluginPipeline::new(&["external_policy".to_string()], &cfg);
+
+    assert!(blocked(&pipeline, &cfg, "tenant-a").await);
+    assert!(blocked(&pipeline, &cfg, "tenant-b").await);
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+}
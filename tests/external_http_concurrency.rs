@@ -0,0 +1,101 @@
+#![cfg(feature = "external-http")]
+
+use axum::{extract::State, routing::post, Json, Router};
+use sentra::{AnalyzeRequest, PlannerContext, ToolDefinition};
+use serde_json::json;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+#[derive(Clone, Default)]
+struct Gauge {
+    current: Arc<AtomicUsize>,
+    peak: Arc<AtomicUsize>,
+    calls: Arc<AtomicUsize>,
+}
+
+// Mock decision service that records how many requests are in flight at once.
+async fn start_counting_mock(gauge: Gauge) -> SocketAddr {
+    async fn decide(
+        State(g): State<Gauge>,
+        Json(_v): Json<serde_json::Value>,
+    ) -> Json<serde_json::Value> {
+        let now = g.current.fetch_add(1, Ordering::SeqCst) + 1;
+        g.peak.fetch_max(now, Ordering::SeqCst);
+        g.calls.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+        g.current.fetch_sub(1, Ordering::SeqCst);
+        Json(json!({"block": false}))
+    }
+    let app = Router::new().route("/eval", post(decide)).with_state(gauge);
+    let listener = tokio::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, 0))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    addr
+}
+
+fn test_request() -> AnalyzeRequest {
+    AnalyzeRequest {
+        planner_context: PlannerContext {
+            user_message: Some("hello".to_string()),
+            ..Default::default()
+        },
+        tool_definition: ToolDefinition {
+            name: Some("Tool".to_string()),
+            ..Default::default()
+        },
+        input_values: serde_json::Map::new(),
+        conversation_metadata: None,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn external_calls_respect_shared_concurrency_cap() {
+    let gauge = Gauge::default();
+    let addr = start_counting_mock(gauge.clone()).await;
+    let url = format!("http://{}/eval", addr);
+    let cfg = json!({
+        "externalMaxConcurrency": 2,
+        "externalHttp": [
+            {"name":"external_a", "url": url, "timeoutMs": 2000},
+            {"name":"external_b", "url": url, "timeoutMs": 2000},
+            {"name":"external_c", "url": url, "timeoutMs": 2000}
+        ]
+    });
+    let cfg: sentra::plugins::PluginConfig = serde_json::from_value(cfg).unwrap();
+    let order: Vec<String> = ["external_a", "external_b", "external_c"]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    let pipeline = sentra::plugins::PluginPipeline::new(&order, &cfg);
+
+    let mut handles = Vec::new();
+    for _ in 0..6 {
+        let pipeline = pipeline.clone();
+        let cfg = cfg.clone();
+        handles.push(tokio::spawn(async move {
+            let req = test_request();
+            let ctx = sentra::util::EvalContext::from_request(&req, &cfg, 10_000, 5_000);
+            let resp = pipeline
+                .evaluate_with_timings(&req, &ctx, &cfg)
+                .await
+                .response;
+            assert!(!resp.block_action);
+        }));
+    }
+    for h in handles {
+        h.await.unwrap();
+    }
+
+    assert_eq!(gauge.calls.load(Ordering::SeqCst), 18);
+    let peak = gauge.peak.load(Ordering::SeqCst);
+    assert!(
+        (1..=2).contains(&peak),
+        "peak concurrency {} exceeded cap",
+        peak
+    );
+}
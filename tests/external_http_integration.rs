@@ -1,3 +1,5 @@
+#![cfg(feature = "external-http")]
+
 use axum::{routing::post, Json, Router};
 use reqwest::Client;
 use sentra::{app, build_state_from_env};
@@ -1,3 +1,5 @@
+#![cfg(feature = "external-http")]
+
 use axum::{routing::post, Json, Router};
 use sentra::{AnalyzeRequest, PlannerContext, ToolDefinition};
 use serde_json::json;
@@ -46,7 +48,10 @@ async fn external_http_non_empty_root_array_blocks() {
     let pipeline = sentra::plugins::PluginPipeline::new(&["external_array".to_string()], &cfg);
     let req = test_request("some message");
     let ctx = sentra::util::EvalContext::from_request(&req, &cfg, 900, 200);
-    let (resp, _timings) = pipeline.evaluate_with_timings(&req, &ctx, &cfg).await;
+    let resp = pipeline
+        .evaluate_with_timings(&req, &ctx, &cfg)
+        .await
+        .response;
     assert!(
         resp.block_action,
         "Expected block due to non-empty root array"
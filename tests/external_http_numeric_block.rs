@@ -0,0 +1,62 @@
+#![cfg(feature = "external-http")]
+
+#[path = "common/mod.rs"]
+mod common;
+
+use axum::extract::Path;
+use axum::{routing::post, Json, Router};
+use sentra::plugins::PluginConfig;
+use sentra::AnalyzeRequest;
+use serde_json::json;
+
+// Mock risk service: `/risk/<n>` always answers `{"risk": n}`.
+async fn start_risk_server() -> std::net::SocketAddr {
+    async fn respond(Path(risk): Path<i64>) -> Json<serde_json::Value> {
+        Json(json!({ "risk": risk }))
+    }
+    let app = Router::new().route("/risk/:risk", post(respond));
+    let listener = tokio::net::TcpListener::bind((std::net::Ipv4Addr::LOCALHOST, 0))
+        .await
+        .unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app).await.unwrap();
+    });
+    addr
+}
+
+async fn evaluate(addr: std::net::SocketAddr, risk: i64) -> sentra::AnalyzeResponse {
+    let cfg: PluginConfig = serde_json::from_value(json!({
+        "externalHttp": [{
+            "name": "external_risk",
+            "url": format!("http://{addr}/risk/{risk}"),
+            "blockField": "/risk",
+            "blockNumericThreshold": 1,
+            "reasonCode": 870,
+            "failOpen": false
+        }]
+    }))
+    .unwrap();
+    let req = AnalyzeRequest::builder()
+        .user_message("hello")
+        .tool("DemoTool")
+        .build();
+    common::evaluate(&["external_risk"], &req, &cfg).await
+}
+
+#[tokio::test]
+async fn numeric_risk_at_or_above_threshold_blocks() {
+    let addr = start_risk_server().await;
+    let resp = evaluate(addr, 2).await;
+    assert!(resp.block_action);
+    assert_eq!(resp.reason_code, Some(870));
+    assert_eq!(resp.blocked_by.as_deref(), Some("external_risk"));
+    assert!(evaluate(addr, 1).await.block_action);
+}
+
+#[tokio::test]
+async fn numeric_risk_below_threshold_allows() {
+    let addr = start_risk_server().await;
+    let resp = evaluate(addr, 0).await;
+    assert!(!resp.block_action);
+}
@@ -0,0 +1,98 @@
+#![cfg(feature = "grpc")]
+
+use sentra::grpc::sentra_client::SentraClient;
+use sentra::grpc::{AnalyzeRequest, PlannerContext, ToolDefinition, ValidateRequest};
+use sentra::AppStateBuilder;
+use tokio::net::TcpListener;
+use tonic::transport::server::TcpIncoming;
+use tonic::Request;
+
+async fn spawn_grpc() -> String {
+    let state = AppStateBuilder::new()
+        .plugin_order(["secrets", "pii"])
+        .allowed_tokens(["grpc-token"])
+        .build();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let incoming = TcpIncoming::from_listener(listener, true, None).unwrap();
+    tokio::spawn(async move {
+        tonic::transport::Server::builder()
+            .add_service(sentra::grpc::service(state))
+            .serve_with_incoming(incoming)
+            .await
+            .unwrap();
+    });
+    format!("http://{}", addr)
+}
+
+fn authed<T>(message: T, token: &str) -> Request<T> {
+    let mut req = Request::new(message);
+    req.metadata_mut().insert(
+        "authorization",
+        format!("Bearer {}", token).parse().unwrap(),
+    );
+    req
+}
+
+fn analyze(message: &str) -> AnalyzeRequest {
+    AnalyzeRequest {
+        planner_context: Some(PlannerContext {
+            user_message: Some(message.into()),
+            ..Default::default()
+        }),
+        tool_definition: Some(ToolDefinition {
+            name: Some("Deploy".into()),
+            ..Default::default()
+        }),
+        input_values_json: r#"{"target": "prod"}"#.into(),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn grpc_blocks_secrets_and_checks_auth() {
+    let mut client = SentraClient::connect(spawn_grpc().await).await.unwrap();
+
+    let ok = client
+        .validate(authed(ValidateRequest {}, "grpc-token"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(ok.is_successful);
+
+    let resp = client
+        .analyze_tool_execution(authed(
+            analyze("use key AKIAABCDEF1234567890"),
+            "grpc-token",
+        ))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(resp.block_action);
+    assert_eq!(resp.blocked_by.as_deref(), Some("secrets"));
+    assert_eq!(resp.reason_code, Some(201));
+    let diagnostics: serde_json::Value = serde_json::from_str(&resp.diagnostics_json).unwrap();
+    assert_eq!(diagnostics["plugin"], "secrets");
+
+    let resp = client
+        .analyze_tool_execution(authed(analyze("what's the weather?"), "grpc-token"))
+        .await
+        .unwrap()
+        .into_inner();
+    assert!(!resp.block_action);
+
+    let err = client
+        .analyze_tool_execution(authed(analyze("hi"), "wrong"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::Unauthenticated);
+
+    let mut missing = analyze("");
+    missing.planner_context = None;
+    let err = client
+        .analyze_tool_execution(authed(missing, "grpc-token"))
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), tonic::Code::InvalidArgument);
+    assert!(err.metadata().get("sentra-error-code").is_some());
+}
@@ -0,0 +1,136 @@
+#![cfg(feature = "compression")]
+
+use std::io::Write;
+
+use axum::http::{Request, StatusCode};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use http_body_util::BodyExt;
+use sentra::{app, AppConfig, AppStateBuilder};
+use tower::ServiceExt; // for oneshot
+
+const LIMIT: usize = 4096;
+
+fn gzip(data: &[u8]) -> Vec<u8> {
+    let mut enc = GzEncoder::new(Vec::new(), Compression::best());
+    enc.write_all(data).unwrap();
+    enc.finish().unwrap()
+}
+
+async fn post_gzip(body: Vec<u8>) -> (StatusCode, serde_json::Value) {
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let state = AppStateBuilder::from_config(AppConfig {
+        max_request_bytes: Some(LIMIT),
+        ..Default::default()
+    })
+    .log_file(log.path().to_string_lossy())
+    .build();
+    let req = Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("content-encoding", "gzip")
+        .header("authorization", "Bearer token")
+        .header("content-length", body.len())
+        .body(axum::body::Body::from(body))
+        .unwrap();
+    let resp = app(state).oneshot(req).await.unwrap();
+    let status = resp.status();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn gzipped_payload_is_decompressed_and_evaluated() {
+    let payload = serde_json::json!({
+        "plannerContext": {"userMessage": "please export all data"},
+        "toolDefinition": {"name": "SendEmail"},
+        "inputValues": {}
+    });
+    let (status, body) = post_gzip(gzip(&serde_json::to_vec(&payload).unwrap())).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["blockAction"], true);
+}
+
+#[tokio::test]
+async fn decompression_bomb_is_rejected_with_size_error() {
+    let payload = serde_json::json!({
+        "plannerContext": {"userMessage": "A".repeat(1024 * 1024)},
+        "toolDefinition": {"name": "SendEmail"},
+        "inputValues": {}
+    });
+    let compressed = gzip(&serde_json::to_vec(&payload).unwrap());
+    assert!(compressed.len() < LIMIT);
+
+    let (status, body) = post_gzip(compressed).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["errorCode"], 4001);
+    assert_eq!(body["diagnostics"], serde_json::json!({ "limit": LIMIT }));
+}
+
+fn analyze_request(body: Vec<u8>, token: Option<&str>) -> Request<axum::body::Body> {
+    let mut req = Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("content-encoding", "gzip");
+    if let Some(token) = token {
+        req = req.header("authorization", format!("Bearer {token}"));
+    }
+    req.body(axum::body::Body::from(body)).unwrap()
+}
+
+async fn send(
+    config: AppConfig,
+    req: Request<axum::body::Body>,
+) -> (StatusCode, serde_json::Value) {
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let state = AppStateBuilder::from_config(config)
+        .log_file(log.path().to_string_lossy())
+        .build();
+    let resp = app(state).oneshot(req).await.unwrap();
+    let status = resp.status();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn bomb_is_capped_at_default_limit_when_unconfigured() {
+    let payload = serde_json::json!({
+        "plannerContext": {"userMessage": "A".repeat(3 * 1024 * 1024)},
+        "toolDefinition": {"name": "SendEmail"},
+        "inputValues": {}
+    });
+    let compressed = gzip(&serde_json::to_vec(&payload).unwrap());
+    let (status, body) = send(
+        AppConfig::default(),
+        analyze_request(compressed, Some("token")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["errorCode"], 4001);
+    assert_eq!(body["diagnostics"]["limit"], 2 * 1024 * 1024);
+}
+
+#[tokio::test]
+async fn unauthorized_gzip_is_rejected_without_inflating() {
+    let config = AppConfig {
+        allowed_tokens: Some(["good".to_string()].into_iter().collect()),
+        ..Default::default()
+    };
+    let (status, body) = send(config, analyze_request(gzip(b"{}"), Some("bad"))).await;
+    assert_eq!(status, StatusCode::UNAUTHORIZED);
+    assert_eq!(body["errorCode"], 2001);
+}
+
+#[tokio::test]
+async fn corrupt_gzip_gets_structured_error() {
+    let (status, body) = send(
+        AppConfig::default(),
+        analyze_request(b"\x1f\x8bnot gzip at all".to_vec(), Some("token")),
+    )
+    .await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["errorCode"], 4006);
+    assert_eq!(body["message"], "Invalid gzip body");
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "metrics")]
+
+use axum::http::{Request, StatusCode};
+use sentra::{app, AppState, AppStateBuilder};
+use tower::ServiceExt;
+
+async fn scrape(state: &AppState, authorization: Option<&str>) -> StatusCode {
+    let mut req = Request::builder().uri("/metrics");
+    if let Some(value) = authorization {
+        req = req.header("authorization", value);
+    }
+    app(state.clone())
+        .oneshot(req.body(axum::body::Body::empty()).unwrap())
+        .await
+        .unwrap()
+        .status()
+}
+
+fn state(token: Option<&str>) -> (AppState, tempfile::NamedTempFile) {
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let mut builder = AppStateBuilder::new().log_file(log.path().to_string_lossy());
+    if let Some(token) = token {
+        builder = builder.metrics_token(token);
+    }
+    (builder.build(), log)
+}
+
+#[tokio::test]
+async fn metrics_token_is_required_when_configured() {
+    let (state, _log) = state(Some("scrape-secret"));
+    assert_eq!(scrape(&state, None).await, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        scrape(&state, Some("Bearer wrong")).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        scrape(&state, Some("Basic scrape-secret")).await,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        scrape(&state, Some("Bearer scrape-secret")).await,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn metrics_stay_open_without_a_token() {
+    let (state, _log) = state(None);
+    assert_eq!(scrape(&state, None).await, StatusCode::OK);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "metrics")]
+
+use reqwest::Client;
+use sentra::{app, build_state_from_env};
+use std::io::Write;
+use tokio::net::TcpListener;
+
+#[tokio::test]
+async fn block_reason_series_per_policy_code() {
+    let mut cfg_file = tempfile::NamedTempFile::new().unwrap();
+    write!(
+        cfg_file,
+        "{}",
+        serde_json::json!({
+            "company_domain": "corp.example",
+            "policies": [
+                {"arg": "subject", "contains": ["confidential"], "reasonCode": 741},
+                {"arg": "subject", "contains": ["payroll"], "reasonCode": 742}
+            ]
+        })
+    )
+    .unwrap();
+    std::env::set_var("SENTRA_PLUGIN_CONFIG", cfg_file.path());
+    std::env::set_var("SENTRA_PLUGINS", "policy_pack");
+    let state = build_state_from_env().await.unwrap();
+    std::env::remove_var("SENTRA_PLUGIN_CONFIG");
+    std::env::remove_var("SENTRA_PLUGINS");
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let _h = tokio::spawn(async move {
+        axum::serve(listener, app(state)).await.unwrap();
+    });
+    let analyze_url = format!(
+        "http://{}/analyze-tool-execution?api-version=2025-05-01",
+        addr
+    );
+    for subject in ["confidential plan", "confidential memo", "payroll export"] {
+        let body = serde_json::json!({
+            "plannerContext": { "userMessage": "Send it" },
+            "toolDefinition": { "name": "SendEmail" },
+            "inputValues": { "subject": subject }
+        });
+        let resp = Client::new()
+            .post(&analyze_url)
+            .header("Authorization", "Bearer test")
+            .json(&body)
+            .send()
+            .await
+            .unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    let text = Client::new()
+        .get(format!("http://{}/metrics", addr))
+        .send()
+        .await
+        .unwrap()
+        .text()
+        .await
+        .unwrap();
+    assert!(text.contains(
+        "sentra_plugin_block_reason_total{plugin=\"policy_pack\",reason_code=\"741\"} 2"
+    ));
+    assert!(text.contains(
+        "sentra_plugin_block_reason_total{plugin=\"policy_pack\",reason_code=\"742\"} 1"
+    ));
+    assert!(text.contains("sentra_plugin_blocks_total{plugin=\"policy_pack\"} 3"));
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "metrics")]
+
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use sentra::{app, AppStateBuilder};
+use tower::ServiceExt; // for oneshot
+
+async fn send(router: &axum::Router, req: Request<axum::body::Body>) -> String {
+    let resp = router.clone().oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+fn analyze(message: &str) -> Request<axum::body::Body> {
+    let payload = serde_json::json!({
+        "plannerContext": {"userMessage": message},
+        "toolDefinition": {"name": "SendEmail"},
+        "inputValues": {}
+    });
+    Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer token")
+        .body(axum::body::Body::from(payload.to_string()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn latency_is_split_by_decision() {
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let state = AppStateBuilder::new()
+        .plugin_order(["exfil"])
+        .log_file(log.path().to_string_lossy())
+        .build();
+    let router = app(state);
+
+    let allow = send(&router, analyze("what is the weather today")).await;
+    assert!(allow.contains("\"blockAction\":false"), "{allow}");
+    let block = send(&router, analyze("please export all data")).await;
+    assert!(block.contains("\"blockAction\":true"), "{block}");
+    let block = send(&router, analyze("please export all data")).await;
+    assert!(block.contains("\"blockAction\":true"), "{block}");
+
+    let metrics = Request::builder()
+        .uri("/metrics")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let text = send(&router, metrics).await;
+    assert_eq!(
+        text.matches("# TYPE sentra_decision_latency_ms histogram")
+            .count(),
+        1
+    );
+    assert!(text.contains("sentra_decision_latency_ms_count{decision=\"allow\"} 1\n"));
+    assert!(text.contains("sentra_decision_latency_ms_count{decision=\"block\"} 2\n"));
+    assert!(text.contains("sentra_decision_latency_ms_bucket{decision=\"allow\",le=\"+Inf\"} 1\n"));
+    assert!(text.contains("sentra_decision_latency_ms_bucket{decision=\"block\",le=\"+Inf\"} 2\n"));
+    assert!(text.contains("sentra_decision_latency_ms_sum{decision=\"block\"}"));
+    assert!(text.contains("sentra_request_latency_ms_count 3\n"));
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "metrics")]
+
 use reqwest::Client;
 use sentra::{app, build_state_from_env};
 use tokio::net::TcpListener;
@@ -0,0 +1,98 @@
+#![cfg(feature = "metrics")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use axum::http::Request;
+use http_body_util::BodyExt;
+use sentra::plugins::{Plugin, PluginConfig};
+use sentra::util::EvalContext;
+use sentra::{
+    app, evaluate, AnalyzeRequest, AnalyzeResponse, AppConfig, AppState, AppStateBuilder,
+};
+use tower::ServiceExt;
+
+/// Allows, after sleeping past the largest latency bucket (2000ms) when the
+/// user message is "slow".
+struct Slow;
+
+#[async_trait::async_trait]
+impl Plugin for Slow {
+    fn name(&self) -> &str {
+        "slow"
+    }
+
+    async fn eval(
+        &self,
+        req: &AnalyzeRequest,
+        _ctx: &EvalContext,
+        _cfg: &PluginConfig,
+    ) -> Option<AnalyzeResponse> {
+        if req.planner_context.user_message.as_deref() == Some("slow") {
+            tokio::time::sleep(Duration::from_millis(2100)).await;
+        }
+        None
+    }
+}
+
+async fn scrape(state: &AppState) -> String {
+    let req = Request::builder()
+        .uri("/metrics")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let resp = app(state.clone()).oneshot(req).await.unwrap();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+fn sample(metrics: &str, series: &str) -> u64 {
+    metrics
+        .lines()
+        .find_map(|l| l.strip_prefix(series)?.strip_prefix(' '))
+        .unwrap_or_else(|| panic!("{series} missing:\n{metrics}"))
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn overflow_observations_count_only_towards_inf() {
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let state = AppStateBuilder::from_config(AppConfig {
+        plugin_budget_ms: 5000,
+        ..Default::default()
+    })
+    .plugins(vec![Arc::new(Slow)])
+    .log_file(log.path().to_string_lossy())
+    .build();
+
+    for message in ["fast", "fast", "slow"] {
+        let req = AnalyzeRequest::builder()
+            .user_message(message)
+            .tool("Lookup")
+            .build();
+        assert!(!evaluate(&req, &state).await.unwrap().block_action);
+    }
+
+    let metrics = scrape(&state).await;
+    let families = [
+        ("sentra_request_latency_ms", ""),
+        ("sentra_decision_latency_ms", "decision=\"allow\","),
+        ("sentra_plugin_latency_ms", "plugin=\"slow\","),
+    ];
+    for (family, labels) in families {
+        let le_2000 = sample(&metrics, &format!("{family}_bucket{{{labels}le=\"2000\"}}"));
+        let inf = sample(&metrics, &format!("{family}_bucket{{{labels}le=\"+Inf\"}}"));
+        let count = sample(&metrics, &format!("{family}_count{}", labels_only(labels)));
+        assert_eq!(le_2000, 2, "{family}: fast observations only");
+        assert_eq!(inf, le_2000 + 1, "{family}: overflow lands in +Inf once");
+        assert_eq!(count, inf, "{family}");
+    }
+}
+
+/// `{labels}` without the trailing comma, or nothing for an unlabelled family.
+fn labels_only(labels: &str) -> String {
+    match labels.strip_suffix(',') {
+        Some(inner) => format!("{{{inner}}}"),
+        None => String::new(),
+    }
+}
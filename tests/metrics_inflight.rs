@@ -0,0 +1,104 @@
+#![cfg(all(feature = "external-http", feature = "metrics"))]
+
+use axum::{routing::post, Json, Router};
+use reqwest::Client;
+use sentra::{app, build_state_from_env};
+use serde_json::json;
+use std::fs;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::Notify;
+
+fn inflight(metrics: &str) -> u64 {
+    metrics
+        .lines()
+        .find_map(|l| l.strip_prefix("sentra_inflight_requests "))
+        .expect("sentra_inflight_requests missing")
+        .trim()
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn inflight_gauge_tracks_requests_waiting_on_plugins() {
+    // The policy service parks each call until the test releases it, so the
+    // analyze request is guaranteed to be in flight during the first scrape.
+    let arrived = Arc::new(Notify::new());
+    let release = Arc::new(Notify::new());
+    let svc = Router::new().route(
+        "/slow",
+        post({
+            let arrived = arrived.clone();
+            let release = release.clone();
+            move |Json(_v): Json<serde_json::Value>| async move {
+                arrived.notify_one();
+                release.notified().await;
+                Json(json!({"block": false}))
+            }
+        }),
+    );
+    let svc_listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let svc_addr = svc_listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(svc_listener, svc).await.unwrap();
+    });
+
+    let cfg = json!({
+        "externalHttp": [
+            {"name": "external_slow", "url": format!("http://{}/slow", svc_addr), "timeoutMs": 5000, "failOpen": true}
+        ]
+    });
+    let cfg_path = tempfile::NamedTempFile::new().unwrap();
+    fs::write(cfg_path.path(), serde_json::to_string(&cfg).unwrap()).unwrap();
+    std::env::set_var(
+        "SENTRA_PLUGIN_CONFIG",
+        cfg_path.path().to_string_lossy().to_string(),
+    );
+    std::env::set_var("SENTRA_PLUGINS", "external_slow");
+    std::env::set_var("SENTRA_PLUGIN_BUDGET_MS", "5000");
+    let state = build_state_from_env().await.unwrap();
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, app(state)).await.unwrap();
+    });
+    let client = Client::new();
+    let metrics_url = format!("http://{}/metrics", addr);
+    let scrape = || async {
+        let text = client
+            .get(&metrics_url)
+            .send()
+            .await
+            .unwrap()
+            .text()
+            .await
+            .unwrap();
+        inflight(&text)
+    };
+    assert_eq!(scrape().await, 0);
+
+    let analyze = tokio::spawn({
+        let client = client.clone();
+        let url = format!(
+            "http://{}/analyze-tool-execution?api-version=2025-05-01",
+            addr
+        );
+        async move {
+            client
+                .post(&url)
+                .header("Authorization", "Bearer test")
+                .json(&json!({"plannerContext":{"userMessage":"ping"},"toolDefinition":{"name":"SendEmail"},"inputValues":{}}))
+                .send()
+                .await
+                .unwrap()
+                .status()
+        }
+    });
+    arrived.notified().await;
+    assert_eq!(scrape().await, 1);
+
+    release.notify_one();
+    assert!(analyze.await.unwrap().is_success());
+    assert_eq!(scrape().await, 0);
+}
@@ -0,0 +1,42 @@
+#![cfg(all(feature = "metrics", feature = "external-http"))]
+
+use axum::http::Request;
+use http_body_util::BodyExt;
+use regex::Regex;
+use sentra::plugins::PluginConfig;
+use sentra::{app, AppStateBuilder};
+use serde_json::json;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn plugin_names_are_escaped_in_labels() {
+    let name = "external_a\"b\\c\nd";
+    let cfg: PluginConfig = serde_json::from_value(json!({
+        "externalHttp": [{ "name": name, "url": "http://127.0.0.1:9/eval" }]
+    }))
+    .unwrap();
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let state = AppStateBuilder::new()
+        .plugin_order([name])
+        .plugin_config(cfg)
+        .log_file(log.path().to_string_lossy())
+        .build();
+
+    let req = Request::builder()
+        .uri("/metrics")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let resp = app(state).oneshot(req).await.unwrap();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let text = String::from_utf8(bytes.to_vec()).unwrap();
+
+    assert!(text.contains(r#"sentra_plugin_eval_ms_count{plugin="external_a\"b\\c\nd"} 0"#));
+    // Every sample is `name{label="value",...} number` on a single line.
+    let sample = Regex::new(
+        r#"^[a-zA-Z_:][a-zA-Z0-9_:]*(\{[a-zA-Z_]+="(?:[^"\\\n]|\\.)*"(,[a-zA-Z_]+="(?:[^"\\\n]|\\.)*")*\})? \S+$"#,
+    )
+    .unwrap();
+    for line in text.lines().filter(|l| !l.starts_with('#')) {
+        assert!(sample.is_match(line), "malformed sample: {line}");
+    }
+}
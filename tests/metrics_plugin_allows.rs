@@ -0,0 +1,79 @@
+#![cfg(all(feature = "external-http", feature = "metrics"))]
+
+use axum::http::Request;
+use axum::{routing::post, Json, Router};
+use http_body_util::BodyExt;
+use sentra::{app, build_state_from_env};
+use serde_json::json;
+use std::fs;
+use tokio::net::TcpListener;
+use tower::ServiceExt;
+
+fn counter(metrics: &str, series: &str) -> u64 {
+    metrics
+        .lines()
+        .find_map(|l| l.strip_prefix(series))
+        .unwrap_or_else(|| panic!("{} missing", series))
+        .trim()
+        .parse()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn explicit_allows_are_counted_but_abstentions_are_not() {
+    let svc = Router::new().route(
+        "/eval",
+        post(|Json(_v): Json<serde_json::Value>| async { Json(json!({"block": false})) }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move {
+        axum::serve(listener, svc).await.unwrap();
+    });
+    let cfg = tempfile::NamedTempFile::new().unwrap();
+    fs::write(
+        cfg.path(),
+        json!({"externalHttp": [{"name": "external_ok", "url": format!("http://{}/eval", addr), "timeoutMs": 1000}]})
+            .to_string(),
+    )
+    .unwrap();
+    std::env::set_var("SENTRA_PLUGIN_CONFIG", cfg.path());
+    std::env::set_var("SENTRA_PLUGINS", "secrets,external_ok");
+    let state = build_state_from_env().await.unwrap();
+
+    for _ in 0..2 {
+        let body = json!({
+            "plannerContext": {"userMessage": "hello"},
+            "toolDefinition": {"name": "SendEmail"},
+            "inputValues": {}
+        });
+        let req = Request::builder()
+            .method("POST")
+            .uri("/analyze-tool-execution?api-version=2025-05-01")
+            .header("content-type", "application/json")
+            .header("authorization", "Bearer token")
+            .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap();
+        let resp = app(state.clone()).oneshot(req).await.unwrap();
+        assert!(resp.status().is_success());
+    }
+
+    let req = Request::builder()
+        .uri("/metrics")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let resp = app(state).oneshot(req).await.unwrap();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let metrics = String::from_utf8(bytes.to_vec()).unwrap();
+    assert_eq!(
+        counter(
+            &metrics,
+            "sentra_plugin_allows_total{plugin=\"external_ok\"}"
+        ),
+        2
+    );
+    assert_eq!(
+        counter(&metrics, "sentra_plugin_allows_total{plugin=\"secrets\"}"),
+        0
+    );
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "metrics")]
+
+use axum::http::Request;
+use http_body_util::BodyExt;
+use sentra::{app, AppState, AppStateBuilder};
+use tower::ServiceExt;
+
+async fn scrape(state: &AppState) -> String {
+    let req = Request::builder()
+        .uri("/metrics")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let resp = app(state.clone()).oneshot(req).await.unwrap();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    String::from_utf8(bytes.to_vec()).unwrap()
+}
+
+/// Plugin label of every `sentra_plugin_*` sample line, in output order.
+fn plugin_lines(metrics: &str) -> Vec<String> {
+    metrics
+        .lines()
+        .filter(|l| l.starts_with("sentra_plugin_"))
+        .map(|l| l.split(' ').next().unwrap().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn plugin_series_follow_declared_order() {
+    let order = ["secrets", "pii", "exfil", "domain_block", "email_bcc"];
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let state = AppStateBuilder::new()
+        .plugin_order(order)
+        .log_file(log.path().to_string_lossy())
+        .build();
+
+    let first = scrape(&state).await;
+    let second = scrape(&state).await;
+    assert_eq!(plugin_lines(&first), plugin_lines(&second));
+
+    let sums: Vec<&str> = first
+        .lines()
+        .filter_map(|l| l.strip_prefix("sentra_plugin_eval_ms_sum{plugin=\""))
+        .map(|l| l.split('"').next().unwrap())
+        .collect();
+    assert_eq!(sums, order);
+}
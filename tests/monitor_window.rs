@@ -0,0 +1,95 @@
+#![cfg(not(feature = "wasm"))]
+
+use sentra::plugins::{MonitorConfig, PluginConfig, PluginOutcome, PluginPipeline};
+use sentra::util::EvalContext;
+use sentra::{AnalyzeRequest, AppStateBuilder};
+use std::fs;
+use std::sync::atomic::Ordering;
+
+#[tokio::test]
+async fn monitored_plugin_enforces_after_promote_after_evaluations() {
+    let cfg = PluginConfig {
+        monitor: MonitorConfig {
+            plugins: vec!["secrets".into()],
+            promote_after: Some(3),
+        },
+        ..Default::default()
+    };
+    let pipeline = PluginPipeline::new(&["secrets".to_string(), "email_bcc".to_string()], &cfg);
+    let req = AnalyzeRequest::builder()
+        .user_message("deploy with AKIAABCDEF1234567890")
+        .tool("Deploy")
+        .build();
+    let ctx = EvalContext::from_request(&req, &cfg, 900, 200);
+
+    for _ in 0..3 {
+        let eval = pipeline.evaluate(&req, &ctx, &cfg, true).await;
+        assert!(!eval.response.block_action, "still in the monitor window");
+        assert_eq!(eval.monitored.len(), 1);
+        assert_eq!(eval.monitored[0].blocked_by.as_deref(), Some("secrets"));
+        assert_eq!(eval.outcomes[0], PluginOutcome::Monitored);
+        // Monitoring does not short-circuit: later plugins still run.
+        assert_eq!(eval.timings.len(), 2);
+    }
+
+    // Clones share the window, as AppState clones do per request.
+    let eval = pipeline.clone().evaluate(&req, &ctx, &cfg, true).await;
+    assert!(eval.response.block_action);
+    assert_eq!(eval.response.blocked_by.as_deref(), Some("secrets"));
+    assert!(eval.monitored.is_empty());
+    assert_eq!(eval.outcomes[0], PluginOutcome::Block);
+}
+
+#[tokio::test]
+async fn monitor_without_threshold_never_enforces() {
+    let cfg: PluginConfig =
+        serde_json::from_value(serde_json::json!({"monitor": {"plugins": ["secrets"]}})).unwrap();
+    let pipeline = PluginPipeline::new(&["secrets".to_string()], &cfg);
+    let req = AnalyzeRequest::builder()
+        .user_message("AKIAABCDEF1234567890")
+        .tool("Deploy")
+        .build();
+    let ctx = EvalContext::from_request(&req, &cfg, 900, 200);
+    for _ in 0..5 {
+        let eval = pipeline.evaluate(&req, &ctx, &cfg, true).await;
+        assert!(!eval.response.block_action);
+        assert_eq!(eval.monitored.len(), 1);
+    }
+}
+
+#[tokio::test]
+async fn monitored_blocks_reach_telemetry_audit_and_metrics() {
+    let dir = tempfile::tempdir().unwrap();
+    let log = dir.path().join("telemetry.log");
+    let audit = dir.path().join("audit.log");
+    let cfg: PluginConfig =
+        serde_json::from_value(serde_json::json!({"monitor": {"plugins": ["secrets"]}})).unwrap();
+    let state = AppStateBuilder::new()
+        .plugin_order(["secrets"])
+        .plugin_config(cfg)
+        .log_file(log.to_str().unwrap())
+        .audit_log_file(audit.to_str().unwrap())
+        .build();
+    let req = AnalyzeRequest::builder()
+        .user_message("deploy with AKIAABCDEF1234567890")
+        .tool("Deploy")
+        .build();
+    let resp = sentra::evaluate(&req, &state).await.unwrap();
+    assert!(!resp.block_action);
+
+    let line: serde_json::Value =
+        serde_json::from_str(fs::read_to_string(&log).unwrap().trim()).unwrap();
+    assert_eq!(line["decision"], "allow");
+    assert_eq!(line["pluginTimings"][0]["outcome"], "monitored");
+    assert_eq!(line["monitored"][0]["blockedBy"], "secrets");
+
+    let record: serde_json::Value =
+        serde_json::from_str(fs::read_to_string(&audit).unwrap().trim()).unwrap();
+    assert_eq!(record["monitored"], true);
+    assert_eq!(record["auditOnly"], false);
+    assert_eq!(record["wouldResponse"]["blockedBy"], "secrets");
+
+    let pm = &state.plugin_metrics[state.plugin_metric_indices["secrets"]];
+    assert_eq!(pm.monitored_count.load(Ordering::Relaxed), 1);
+    assert_eq!(pm.block_count.load(Ordering::Relaxed), 0);
+}
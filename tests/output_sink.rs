@@ -0,0 +1,52 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use sentra::plugins::PluginConfig;
+use sentra::{AnalyzeRequest, AnalyzeResponse};
+use serde_json::{json, Value};
+
+fn request(outputs: Value) -> AnalyzeRequest {
+    serde_json::from_value(json!({
+        "plannerContext": { "userMessage": "summarize the quarterly report" },
+        "toolDefinition": { "name": "Summarize", "outputParameters": outputs },
+        "inputValues": {}
+    }))
+    .unwrap()
+}
+
+async fn evaluate(cfg: Value, req: &AnalyzeRequest) -> AnalyzeResponse {
+    let cfg: PluginConfig = serde_json::from_value(cfg).unwrap();
+    common::evaluate(&["output_sink"], req, &cfg).await
+}
+
+#[tokio::test]
+async fn webhook_output_parameter_blocks() {
+    let req = request(json!([
+        { "name": "summary", "type": "string" },
+        { "name": "webhookUrl", "type": "string" }
+    ]));
+    let resp = evaluate(json!({}), &req).await;
+    assert!(resp.block_action);
+    assert_eq!(resp.reason_code, Some(119));
+    assert_eq!(resp.blocked_by.as_deref(), Some("output_sink"));
+    let diag = resp.diagnostics.unwrap();
+    assert_eq!(diag["code"], "output_sink");
+    assert_eq!(diag["detail"], "webhook");
+    assert_eq!(diag["parameter"], "webhookUrl");
+    assert_eq!(diag["tool"], "Summarize");
+}
+
+#[tokio::test]
+async fn ordinary_outputs_and_custom_indicators() {
+    let req = request(json!([{ "name": "summary", "type": "string" }]));
+    assert!(!evaluate(json!({}), &req).await.block_action);
+
+    let req = request(json!([{ "name": "result", "type": "S3Bucket" }]));
+    assert!(!evaluate(json!({}), &req).await.block_action);
+    let resp = evaluate(json!({ "outputSinkIndicators": ["s3_bucket"] }), &req).await;
+    assert_eq!(resp.reason_code, Some(119));
+
+    let req = request(json!([{ "name": "webhook", "type": "string" }]));
+    let resp = evaluate(json!({ "outputSinkIndicators": [] }), &req).await;
+    assert!(!resp.block_action);
+}
@@ -0,0 +1,39 @@
+use sentra::plugins::{PluginConfig, PluginPipeline};
+use sentra::util::EvalContext;
+use sentra::{AnalyzeRequest, AnalyzeResponse};
+use serde_json::json;
+
+async fn run(cfg: &PluginConfig, message: &str) -> AnalyzeResponse {
+    let req: AnalyzeRequest = serde_json::from_value(json!({
+        "plannerContext": { "userMessage": message },
+        "toolDefinition": { "name": "UpdateRecord" },
+        "inputValues": {}
+    }))
+    .unwrap();
+    let pipeline = PluginPipeline::new(&["pii".to_string()], cfg);
+    let ctx = EvalContext::from_request(&req, cfg, 900, 200);
+    pipeline
+        .evaluate_with_timings(&req, &ctx, cfg)
+        .await
+        .response
+}
+
+#[tokio::test]
+async fn regex_keyword_blocks_employee_id() {
+    let cfg: PluginConfig = serde_json::from_value(json!({
+        // The invalid entry is skipped; the valid one still applies.
+        "piiKeywordRegex": ["(unclosed", r"\bEMP-\d{6}\b"]
+    }))
+    .unwrap();
+
+    let resp = run(&cfg, "Update record for EMP-482913 please").await;
+    assert!(resp.block_action);
+    assert_eq!(resp.reason_code, Some(202));
+    assert_eq!(resp.diagnostics.unwrap()["code"], "keyword");
+
+    assert!(
+        !run(&cfg, "Update record for EMP-48 please")
+            .await
+            .block_action
+    );
+}
@@ -0,0 +1,82 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use sentra::plugins::{Plugin, PluginConfig, PluginOutcome, PluginPipeline};
+use sentra::util::EvalContext;
+use sentra::{AnalyzeRequest, AnalyzeResponse};
+use serde_json::json;
+
+/// Takes 300 ms before blocking everything.
+struct SlowPlugin;
+
+#[async_trait::async_trait]
+impl Plugin for SlowPlugin {
+    fn name(&self) -> &str {
+        "slow"
+    }
+
+    async fn eval(
+        &self,
+        _req: &AnalyzeRequest,
+        _ctx: &EvalContext,
+        _cfg: &PluginConfig,
+    ) -> Option<AnalyzeResponse> {
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        Some(AnalyzeResponse {
+            block_action: true,
+            reason_code: Some(950),
+            reason: Some("too slow to matter".into()),
+            blocked_by: None,
+            diagnostics: None,
+        })
+    }
+}
+
+fn config(timeouts: serde_json::Value) -> PluginConfig {
+    serde_json::from_value(json!({ "pluginTimeouts": timeouts })).unwrap()
+}
+
+#[tokio::test]
+async fn timed_out_plugin_abstains_by_default() {
+    let cfg = config(json!({ "slow": { "ms": 20 } }));
+    let pipeline = PluginPipeline::from_plugins(vec![Arc::new(SlowPlugin)]);
+    let req = AnalyzeRequest::builder().tool("SendEmail").build();
+    let ctx = EvalContext::from_request(&req, &cfg, 900, 1000);
+
+    let start = Instant::now();
+    let eval = pipeline.evaluate(&req, &ctx, &cfg, true).await;
+    assert!(start.elapsed() < Duration::from_millis(250));
+    assert!(!eval.response.block_action);
+    assert_eq!(eval.timed_out, vec!["slow".to_string()]);
+    assert_eq!(eval.outcomes, vec![PluginOutcome::Timeout]);
+}
+
+#[tokio::test]
+async fn timed_out_plugin_blocks_when_fail_closed() {
+    let cfg = config(json!({ "slow": { "ms": 20, "failClosed": true } }));
+    let pipeline = PluginPipeline::from_plugins(vec![Arc::new(SlowPlugin)]);
+    let req = AnalyzeRequest::builder().tool("SendEmail").build();
+    let ctx = EvalContext::from_request(&req, &cfg, 900, 1000);
+
+    let eval = pipeline.evaluate(&req, &ctx, &cfg, true).await;
+    assert!(eval.response.block_action);
+    assert_eq!(eval.response.reason_code, Some(900));
+    assert_eq!(eval.response.blocked_by.as_deref(), Some("slow"));
+    let diag = serde_json::to_value(eval.response.diagnostics.unwrap()).unwrap();
+    assert_eq!(diag["code"], "timeout");
+    assert_eq!(diag["timeoutMs"], 20);
+    assert_eq!(eval.timed_out, vec!["slow".to_string()]);
+}
+
+#[tokio::test]
+async fn plugins_without_a_limit_run_to_completion() {
+    let cfg = config(json!({ "other": { "ms": 20 } }));
+    let pipeline = PluginPipeline::from_plugins(vec![Arc::new(SlowPlugin)]);
+    let req = AnalyzeRequest::builder().tool("SendEmail").build();
+    let ctx = EvalContext::from_request(&req, &cfg, 900, 1000);
+
+    let eval = pipeline.evaluate(&req, &ctx, &cfg, true).await;
+    assert!(eval.response.block_action);
+    assert_eq!(eval.response.reason_code, Some(950));
+    assert!(eval.timed_out.is_empty());
+}
@@ -23,10 +23,32 @@ async fn pipeline_records_timings_and_blocked_by() {
     };
 
     let ctx = EvalContext::from_request(&request, &cfg, 900, 120);
-    let (response, timings) = pipeline.evaluate_with_timings(&request, &ctx, &cfg).await;
+    let result = pipeline.evaluate_with_timings(&request, &ctx, &cfg).await;
 
-    assert!(response.block_action);
-    assert_eq!(response.blocked_by.as_deref(), Some("exfil"));
-    assert_eq!(timings.len(), 1);
-    assert_eq!(timings[0].0, "exfil");
+    assert!(result.response.block_action);
+    assert_eq!(result.response.blocked_by.as_deref(), Some("exfil"));
+    assert_eq!(result.timings.len(), 1);
+    assert_eq!(result.timings[0].name, "exfil");
+}
+
+#[tokio::test]
+async fn evaluation_result_serializes_named_fields() {
+    let order = vec!["secrets".to_string(), "exfil".to_string()];
+    let cfg = PluginConfig::default();
+    let pipeline = PluginPipeline::new(&order, &cfg);
+    let request = AnalyzeRequest::builder()
+        .user_message("what's the weather?")
+        .tool("GetWeather")
+        .build();
+    let ctx = EvalContext::from_request(&request, &cfg, 900, 120);
+
+    let result = pipeline.evaluate_with_timings(&request, &ctx, &cfg).await;
+    let v = serde_json::to_value(&result).unwrap();
+    assert_eq!(v["response"]["blockAction"], false);
+    let timings = v["timings"].as_array().unwrap();
+    assert_eq!(timings.len(), 2);
+    assert_eq!(timings[0]["name"], "secrets");
+    assert_eq!(timings[1]["name"], "exfil");
+    assert!(timings[0]["ms"].is_u64());
+    assert_eq!(timings[0]["outcome"], "allow");
 }
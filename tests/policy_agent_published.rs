@@ -0,0 +1,40 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use sentra::plugins::PluginConfig;
+use sentra::AnalyzeRequest;
+use serde_json::json;
+
+/// Evaluate a request to deploy to production, with `isPublished` set as
+/// given (or the agent metadata omitted).
+async fn evaluate(is_published: Option<bool>) -> sentra::AnalyzeResponse {
+    let cfg: PluginConfig = serde_json::from_value(json!({
+        "policies": [{
+            "agentPublished": false,
+            "contains": ["production"],
+            "reasonCode": 742,
+            "reason": "Draft agents may not touch production."
+        }]
+    }))
+    .unwrap();
+    let mut body = json!({
+        "plannerContext": { "userMessage": "deploy the build" },
+        "toolDefinition": { "name": "Deploy" },
+        "inputValues": { "environment": "production" }
+    });
+    if let Some(published) = is_published {
+        body["conversationMetadata"] = json!({ "agent": { "isPublished": published } });
+    }
+    let req: AnalyzeRequest = serde_json::from_value(body).unwrap();
+    common::evaluate(&["policy_pack"], &req, &cfg).await
+}
+
+#[tokio::test]
+async fn rule_fires_only_for_unpublished_agents() {
+    let resp = evaluate(Some(false)).await;
+    assert!(resp.block_action);
+    assert_eq!(resp.reason_code, Some(742));
+
+    assert!(!evaluate(Some(true)).await.block_action);
+    assert!(!evaluate(None).await.block_action);
+}
@@ -0,0 +1,55 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use sentra::plugins::PluginConfig;
+use sentra::AnalyzeRequest;
+use serde_json::json;
+
+async fn evaluate(cfg: &PluginConfig, input: serde_json::Value) -> sentra::AnalyzeResponse {
+    let req: AnalyzeRequest = serde_json::from_value(json!({
+        "plannerContext": { "userMessage": "send the report" },
+        "toolDefinition": { "name": "SendEmail" },
+        "inputValues": input
+    }))
+    .unwrap();
+    common::evaluate(&["policy_pack"], &req, cfg).await
+}
+
+#[tokio::test]
+async fn pointer_arg_reaches_nested_multi_value_field() {
+    let cfg: PluginConfig = serde_json::from_value(json!({
+        "policies": [{
+            "arg": "/recipients/bcc",
+            "contains": ["@competitor.com"],
+            "reasonCode": 731
+        }]
+    }))
+    .unwrap();
+
+    let nested_bcc = json!({
+        "recipients": {
+            "to": ["alice@yourcompany.com"],
+            "bcc": ["bob@yourcompany.com", "spy@competitor.com"]
+        }
+    });
+    let resp = evaluate(&cfg, nested_bcc).await;
+    assert!(resp.block_action);
+    assert_eq!(resp.reason_code, Some(731));
+    assert_eq!(resp.diagnostics.unwrap()["field"], "/recipients/bcc");
+
+    // The same address outside the pointed-to field is out of scope.
+    let in_to = json!({
+        "recipients": {
+            "to": ["spy@competitor.com"],
+            "bcc": ["bob@yourcompany.com"]
+        }
+    });
+    assert!(!evaluate(&cfg, in_to).await.block_action);
+
+    // Missing path: nothing to check.
+    assert!(
+        !evaluate(&cfg, json!({"recipients": "spy@competitor.com"}))
+            .await
+            .block_action
+    );
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "metrics")]
+
+use axum::http::Request;
+use http_body_util::BodyExt;
+use sentra::plugins::PluginConfig;
+use sentra::{app, AnalyzeRequest, AppStateBuilder, PolicyRule};
+use tower::ServiceExt; // for oneshot
+
+fn rule(word: &str) -> PolicyRule {
+    PolicyRule {
+        contains: vec![word.to_string()],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn rules_past_the_cap_are_dropped_and_reported() {
+    let cfg = PluginConfig {
+        policies: vec![rule("alpha"), rule("bravo"), rule("charlie")],
+        max_policy_rules: 2,
+        ..Default::default()
+    };
+    let state = AppStateBuilder::new()
+        .plugin_order(["policy_pack"])
+        .plugin_config(cfg)
+        .build();
+    assert_eq!(state.policy_rules_loaded, 2);
+
+    let request = |msg: &str| {
+        AnalyzeRequest::builder()
+            .user_message(msg)
+            .tool("Search")
+            .build()
+    };
+    let kept = sentra::evaluate(&request("say bravo"), &state)
+        .await
+        .unwrap();
+    assert!(kept.block_action);
+    let dropped = sentra::evaluate(&request("say charlie"), &state)
+        .await
+        .unwrap();
+    assert!(!dropped.block_action, "third rule should be truncated");
+
+    let resp = app(state)
+        .oneshot(
+            Request::get("/metrics")
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    let body = resp.into_body().collect().await.unwrap().to_bytes();
+    let text = String::from_utf8(body.to_vec()).unwrap();
+    assert!(text.contains("# TYPE sentra_policy_rules_loaded gauge"));
+    assert!(text.lines().any(|l| l == "sentra_policy_rules_loaded 2"));
+}
+
+#[test]
+fn new_applies_the_default_cap() {
+    use sentra::plugins::policy_pack::PolicyPackPlugin;
+    use sentra::plugins::DEFAULT_MAX_POLICY_RULES;
+
+    let rules = vec![rule("alpha"); DEFAULT_MAX_POLICY_RULES + 1];
+    assert_eq!(
+        PolicyPackPlugin::new(rules.clone()).rule_count(),
+        DEFAULT_MAX_POLICY_RULES
+    );
+    assert_eq!(PolicyPackPlugin::with_max_rules(rules, 2).rule_count(), 2);
+}
@@ -0,0 +1,35 @@
+#![cfg(not(feature = "wasm"))]
+
+use sentra::plugins::PluginConfig;
+use sentra::{evaluate, AnalyzeRequest, AppStateBuilder};
+use serde_json::json;
+
+fn config(reason_code: serde_json::Value) -> serde_json::Result<PluginConfig> {
+    serde_json::from_value(json!({
+        "policies": [{ "contains": ["forbidden"], "reasonCode": reason_code }]
+    }))
+}
+
+#[tokio::test]
+async fn max_i32_reason_code_round_trips_to_json() {
+    let cfg = config(json!(i32::MAX)).unwrap();
+    let state = AppStateBuilder::new()
+        .plugin_order(["policy_pack"])
+        .plugin_config(cfg)
+        .build();
+    let req: AnalyzeRequest = serde_json::from_value(json!({
+        "plannerContext": { "userMessage": "this is forbidden" },
+        "toolDefinition": { "name": "Search" },
+        "inputValues": {}
+    }))
+    .unwrap();
+    let resp = evaluate(&req, &state).await.unwrap();
+    let wire = serde_json::to_value(&resp).unwrap();
+    assert_eq!(wire["reasonCode"], json!(2147483647));
+}
+
+#[test]
+fn out_of_range_reason_code_is_rejected_not_truncated() {
+    let err = config(json!(i64::from(i32::MAX) + 1)).unwrap_err();
+    assert!(err.to_string().contains("2147483648"), "{err}");
+}
@@ -0,0 +1,46 @@
+#![cfg(not(feature = "wasm"))]
+
+#[path = "common/mod.rs"]
+mod common;
+
+use axum::http::{Request, StatusCode};
+use common::EnvGuard;
+use http_body_util::BodyExt;
+use sentra::{app, build_state_from_env};
+use std::fs;
+use tower::ServiceExt;
+
+#[tokio::test]
+async fn catalog_lists_builtin_and_configured_reasons() {
+    let mut env = EnvGuard::new();
+    let cfg = tempfile::NamedTempFile::new().unwrap();
+    fs::write(
+        cfg.path(),
+        serde_json::json!({
+            "policies": [
+                {"contains": ["project falcon"], "reasonCode": 742, "reason": "Codename disclosure"}
+            ],
+            "externalHttp": [
+                {"name": "external_dlp", "url": "http://127.0.0.1:1/eval", "reasonCode": 820}
+            ]
+        })
+        .to_string(),
+    )
+    .unwrap();
+    env.set("SENTRA_PLUGIN_CONFIG", cfg.path().to_str().unwrap());
+    let state = build_state_from_env().await.unwrap();
+
+    let req = Request::builder()
+        .uri("/reason-codes")
+        .body(axum::body::Body::empty())
+        .unwrap();
+    let resp = app(state).oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let catalog: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+
+    assert!(catalog["201"].as_str().unwrap().contains("AWS key"));
+    assert_eq!(catalog["742"], "Codename disclosure");
+    assert_eq!(catalog["820"], "External policy block");
+    assert!(catalog["112"].is_string());
+}
@@ -0,0 +1,76 @@
+#![cfg(not(feature = "wasm"))]
+
+use std::sync::Arc;
+
+use sentra::plugins::{Diagnostics, Plugin, PluginConfig};
+use sentra::util::EvalContext;
+use sentra::{evaluate, AnalyzeRequest, AnalyzeResponse, AppConfig, AppStateBuilder};
+
+/// Relays an upstream verdict whose text tries to forge a log line.
+struct Relay;
+
+#[async_trait::async_trait]
+impl Plugin for Relay {
+    fn name(&self) -> &str {
+        "relay"
+    }
+
+    async fn eval(
+        &self,
+        _req: &AnalyzeRequest,
+        _ctx: &EvalContext,
+        _cfg: &PluginConfig,
+    ) -> Option<AnalyzeResponse> {
+        Some(AnalyzeResponse {
+            block_action: true,
+            reason_code: Some(950),
+            reason: Some("blocked\n{\"fake\":true}".into()),
+            blocked_by: None,
+            diagnostics: Some(
+                Diagnostics::new("relay", "upstream")
+                    .detail("line\r\n{\"fake\":true}")
+                    .into(),
+            ),
+        })
+    }
+}
+
+fn single_json_line(path: &std::path::Path) -> serde_json::Value {
+    let text = std::fs::read_to_string(path).unwrap();
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 1, "{text}");
+    assert!(!text.contains("\n{\"fake\""));
+    serde_json::from_str(lines[0]).unwrap()
+}
+
+#[tokio::test]
+async fn forged_newlines_never_reach_responses_or_logs() {
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let audit = tempfile::NamedTempFile::new().unwrap();
+    let req = AnalyzeRequest::builder()
+        .user_message("hi")
+        .tool("Lookup")
+        .build();
+
+    let state = AppStateBuilder::new()
+        .plugins(vec![Arc::new(Relay)])
+        .log_file(log.path().to_string_lossy())
+        .build();
+    let resp = evaluate(&req, &state).await.unwrap();
+    assert_eq!(resp.reason.as_deref(), Some("blocked {\"fake\":true}"));
+    let diag = resp.diagnostics.unwrap();
+    assert_eq!(diag["detail"], "line  {\"fake\":true}");
+    let event = single_json_line(log.path());
+    assert_eq!(event["diagnostics"]["detail"], "line  {\"fake\":true}");
+
+    let state = AppStateBuilder::from_config(AppConfig {
+        audit_only: true,
+        audit_log_file: Some(audit.path().to_string_lossy().into_owned()),
+        ..Default::default()
+    })
+    .plugins(vec![Arc::new(Relay)])
+    .build();
+    evaluate(&req, &state).await.unwrap();
+    let record = single_json_line(audit.path());
+    assert_eq!(record["wouldResponse"]["reason"], "blocked {\"fake\":true}");
+}
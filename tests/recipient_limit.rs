@@ -0,0 +1,34 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use sentra::plugins::PluginConfig;
+use sentra::AnalyzeRequest;
+use serde_json::json;
+
+async fn evaluate(input: serde_json::Value) -> sentra::AnalyzeResponse {
+    let cfg = PluginConfig::default();
+    let req: AnalyzeRequest = serde_json::from_value(json!({
+        "plannerContext": { "userMessage": "send the newsletter" },
+        "toolDefinition": { "name": "SendEmail" },
+        "inputValues": input
+    }))
+    .unwrap();
+    common::evaluate(&["recipient_limit"], &req, &cfg).await
+}
+
+#[tokio::test]
+async fn fifty_bcc_recipients_block() {
+    let bcc: Vec<String> = (0..50).map(|i| format!("user{i}@example.org")).collect();
+    let resp = evaluate(json!({ "to": "me@corp.example", "bcc": bcc.join(",") })).await;
+    assert!(resp.block_action);
+    assert_eq!(resp.reason_code, Some(115));
+    let diag = resp.diagnostics.unwrap();
+    assert_eq!(diag["count"], json!(51));
+    assert_eq!(diag["limit"], json!(25));
+}
+
+#[tokio::test]
+async fn two_recipients_pass() {
+    let resp = evaluate(json!({ "to": ["a@corp.example"], "cc": "b@corp.example" })).await;
+    assert!(!resp.block_action);
+}
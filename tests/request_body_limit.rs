@@ -1,3 +1,5 @@
+#![cfg(not(feature = "wasm"))]
+
 #[path = "common/mod.rs"]
 mod common;
 
@@ -75,5 +77,9 @@ async fn chunked_payload_over_limit_returns_error_response() {
     assert_eq!(resp.status(), StatusCode::PAYLOAD_TOO_LARGE);
     let json: serde_json::Value = resp.json().await.unwrap();
     assert_eq!(json.get("errorCode").and_then(|v| v.as_i64()), Some(4001));
+    assert_eq!(
+        json.get("diagnostics"),
+        Some(&serde_json::json!({ "limit": 256 }))
+    );
     handle.abort();
 }
@@ -0,0 +1,116 @@
+#![cfg(not(feature = "wasm"))]
+
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use sentra::{app, AnalyzeRequest, AppStateBuilder};
+use tower::ServiceExt;
+
+fn analyze_request(auth: bool) -> Request<axum::body::Body> {
+    let body = AnalyzeRequest::builder()
+        .user_message("hi")
+        .tool("Lookup")
+        .build();
+    let mut req = Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("x-ms-correlation-id", "shared-corr");
+    if auth {
+        req = req.header("authorization", "Bearer token");
+    }
+    req.body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn request_id_matches_header_and_telemetry_line() {
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let state = AppStateBuilder::new()
+        .log_file(log.path().to_string_lossy())
+        .build();
+
+    let mut ids = Vec::new();
+    for _ in 0..2 {
+        let resp = app(state.clone())
+            .oneshot(analyze_request(true))
+            .await
+            .unwrap();
+        assert_eq!(resp.status(), 200);
+        let id = resp.headers()["x-sentra-request-id"].to_str().unwrap();
+        ids.push(id.to_string());
+    }
+    assert_ne!(
+        ids[0], ids[1],
+        "ids are per request, not per correlation id"
+    );
+
+    let text = std::fs::read_to_string(log.path()).unwrap();
+    let logged: Vec<String> = text
+        .lines()
+        .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+        .map(|event| {
+            assert_eq!(event["correlationId"], "shared-corr");
+            event["requestId"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(logged, ids);
+}
+
+/// Status, then asserts the body's `requestId` equals the response header.
+async fn request_id_round_trip(
+    state: sentra::AppState,
+    req: Request<axum::body::Body>,
+) -> StatusCode {
+    let resp = app(state).oneshot(req).await.unwrap();
+    let status = resp.status();
+    let id = resp.headers()["x-sentra-request-id"]
+        .to_str()
+        .unwrap()
+        .to_string();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    let body: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+    assert_eq!(body["requestId"], id.as_str(), "{body}");
+    status
+}
+
+#[tokio::test]
+async fn error_bodies_carry_the_header_request_id() {
+    let state = AppStateBuilder::new().build();
+    let status = request_id_round_trip(state, analyze_request(false)).await;
+    assert_eq!(status, 401);
+}
+
+#[cfg(feature = "strict-schema")]
+#[tokio::test]
+async fn schema_violations_carry_the_request_id() {
+    let state = AppStateBuilder::from_config(sentra::AppConfig {
+        strict_schema: true,
+        ..Default::default()
+    })
+    .build();
+    let req = Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer token")
+        .body(axum::body::Body::from(
+            r#"{"plannerContext":{"userMessage":"hi"},"toolDefinition":{"name":"T"},"inputValues":[]}"#,
+        ))
+        .unwrap();
+    assert_eq!(request_id_round_trip(state, req).await, 400);
+}
+
+#[cfg(feature = "compression")]
+#[tokio::test]
+async fn gzip_errors_carry_the_request_id() {
+    let req = Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("content-encoding", "gzip")
+        .header("authorization", "Bearer token")
+        .body(axum::body::Body::from("not gzip"))
+        .unwrap();
+    let status = request_id_round_trip(AppStateBuilder::new().build(), req).await;
+    assert_eq!(status, 400);
+}
@@ -0,0 +1,124 @@
+#![cfg(not(feature = "wasm"))]
+
+use std::sync::Arc;
+
+use axum::http::Request;
+use http_body_util::BodyExt;
+use sentra::plugins::{Diagnostics, Plugin, PluginConfig};
+use sentra::util::EvalContext;
+use sentra::{app, AnalyzeRequest, AnalyzeResponse, AppConfig, AppState, AppStateBuilder};
+use tower::ServiceExt;
+
+/// Blocks with a reason and diagnostics `size` bytes long, the way a
+/// plugin relaying an upstream service's verdict verbatim might.
+struct Verbose {
+    size: usize,
+}
+
+#[async_trait::async_trait]
+impl Plugin for Verbose {
+    fn name(&self) -> &str {
+        "verbose"
+    }
+
+    async fn eval(
+        &self,
+        _req: &AnalyzeRequest,
+        _ctx: &EvalContext,
+        _cfg: &PluginConfig,
+    ) -> Option<AnalyzeResponse> {
+        Some(AnalyzeResponse {
+            block_action: true,
+            reason_code: Some(950),
+            reason: Some("r".repeat(self.size)),
+            blocked_by: None,
+            diagnostics: Some(
+                Diagnostics::new("verbose", "upstream")
+                    .detail("d".repeat(self.size))
+                    .into(),
+            ),
+        })
+    }
+}
+
+fn state(size: usize, log: &tempfile::NamedTempFile) -> AppState {
+    state_with_cap(size, 4096, log)
+}
+
+fn state_with_cap(size: usize, cap: usize, log: &tempfile::NamedTempFile) -> AppState {
+    AppStateBuilder::from_config(AppConfig {
+        max_response_bytes: cap,
+        ..Default::default()
+    })
+    .plugins(vec![Arc::new(Verbose { size })])
+    .log_file(log.path().to_string_lossy())
+    .build()
+}
+
+async fn analyze(state: AppState) -> (usize, serde_json::Value) {
+    let body = AnalyzeRequest::builder()
+        .user_message("hi")
+        .tool("Lookup")
+        .build();
+    let req = Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer token")
+        .body(axum::body::Body::from(serde_json::to_vec(&body).unwrap()))
+        .unwrap();
+    let resp = app(state).oneshot(req).await.unwrap();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (bytes.len(), serde_json::from_slice(&bytes).unwrap())
+}
+
+#[tokio::test]
+async fn oversized_response_is_capped() {
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let (len, resp) = analyze(state(200_000, &log)).await;
+    assert!(len <= 4096, "{len} bytes");
+    assert_eq!(resp["blockAction"], true);
+    assert_eq!(resp["reasonCode"], 950);
+    assert_eq!(resp["blockedBy"], "verbose");
+    assert_eq!(resp["diagnostics"]["code"], "upstream");
+    assert_eq!(resp["diagnostics"]["truncated"], true);
+    // The pipeline already cut the reason; the detail alone overflows.
+    assert!(resp["diagnostics"]["originalBytes"].as_u64().unwrap() > 200_000);
+    assert!(resp["diagnostics"].get("detail").is_none());
+    assert!(resp["reason"].as_str().unwrap().ends_with('…'));
+
+    let line = std::fs::read_to_string(log.path()).unwrap();
+    assert!(line.len() < 8192, "telemetry carries the capped response");
+}
+
+#[tokio::test]
+async fn responses_within_the_cap_are_untouched() {
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let (_, resp) = analyze(state(100, &log)).await;
+    assert_eq!(resp["reason"], "r".repeat(100));
+    assert_eq!(resp["diagnostics"]["detail"], "d".repeat(100));
+    assert!(resp["diagnostics"].get("truncated").is_none());
+}
+
+#[tokio::test]
+async fn small_cap_is_enforced_by_shortening_the_reason() {
+    let log = tempfile::NamedTempFile::new().unwrap();
+    // The pipeline already limits the reason to 256 characters; that alone
+    // is over this cap.
+    let (len, resp) = analyze(state_with_cap(1000, 200, &log)).await;
+    assert!(len <= 200, "{len} bytes: {resp}");
+    assert_eq!(resp["blockAction"], true);
+    assert_eq!(resp["reasonCode"], 950);
+    assert_eq!(resp["blockedBy"], "verbose");
+    let reason = resp["reason"].as_str().unwrap();
+    assert!(
+        reason.starts_with("rrr") && reason.ends_with('…'),
+        "{reason}"
+    );
+
+    let (len, resp) = analyze(state_with_cap(1000, 70, &log)).await;
+    assert!(len <= 70, "{len} bytes: {resp}");
+    assert_eq!(resp["blockAction"], true);
+    assert!(resp.get("reason").is_none());
+    assert!(resp.get("diagnostics").is_none());
+}
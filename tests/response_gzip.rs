@@ -0,0 +1,62 @@
+#![cfg(feature = "compression")]
+
+use std::io::Read;
+
+use axum::http::{Request, StatusCode};
+use flate2::read::GzDecoder;
+use http_body_util::BodyExt;
+use sentra::{app, AppConfig, AppStateBuilder};
+use tower::ServiceExt; // for oneshot
+
+async fn get(uri: &str, accept_encoding: Option<&str>) -> (axum::http::HeaderMap, Vec<u8>) {
+    let log = tempfile::NamedTempFile::new().unwrap();
+    // The reason-code catalog (~360 bytes) sits above this threshold and
+    // /healthz below it.
+    let state = AppStateBuilder::from_config(AppConfig {
+        gzip_min_bytes: 256,
+        ..Default::default()
+    })
+    .log_file(log.path().to_string_lossy())
+    .build();
+    let mut req = Request::builder().uri(uri);
+    if let Some(value) = accept_encoding {
+        req = req.header("accept-encoding", value);
+    }
+    let resp = app(state)
+        .oneshot(req.body(axum::body::Body::empty()).unwrap())
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let headers = resp.headers().clone();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (headers, bytes.to_vec())
+}
+
+#[tokio::test]
+async fn large_response_is_gzipped_for_accepting_clients() {
+    let (plain_headers, plain) = get("/reason-codes", None).await;
+    assert!(plain_headers.get("content-encoding").is_none());
+    assert!(plain.len() >= 256, "catalog is {} bytes", plain.len());
+
+    let (headers, body) = get("/reason-codes", Some("br;q=1.0, gzip;q=0.8")).await;
+    assert_eq!(headers["content-encoding"], "gzip");
+    assert_eq!(headers["content-type"], "application/json");
+    assert_eq!(headers["vary"], "accept-encoding");
+    assert_eq!(headers["content-length"], body.len().to_string().as_str());
+    assert!(body.len() < plain.len());
+
+    let mut decoded = Vec::new();
+    GzDecoder::new(&body[..]).read_to_end(&mut decoded).unwrap();
+    assert_eq!(decoded, plain);
+    let _: serde_json::Value = serde_json::from_slice(&decoded).unwrap();
+}
+
+#[tokio::test]
+async fn small_or_refused_responses_are_not_compressed() {
+    let (headers, body) = get("/healthz", Some("gzip")).await;
+    assert!(headers.get("content-encoding").is_none());
+    let _: serde_json::Value = serde_json::from_slice(&body).unwrap();
+
+    let (headers, _) = get("/reason-codes", Some("gzip;q=0, identity")).await;
+    assert!(headers.get("content-encoding").is_none());
+}
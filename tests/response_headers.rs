@@ -0,0 +1,56 @@
+#![cfg(not(feature = "wasm"))]
+
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use sentra::{app, build_state_from_env};
+use tower::ServiceExt; // for oneshot
+
+async fn analyze(message: &str) -> (axum::http::HeaderMap, serde_json::Value) {
+    let state = build_state_from_env().await.unwrap();
+    let payload = serde_json::json!({
+        "plannerContext": {"userMessage": message},
+        "toolDefinition": {"name": "SendEmail"},
+        "inputValues": {}
+    });
+    let req = Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer token")
+        .body(axum::body::Body::from(
+            serde_json::to_vec(&payload).unwrap(),
+        ))
+        .unwrap();
+    let resp = app(state).oneshot(req).await.unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let headers = resp.headers().clone();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (headers, serde_json::from_slice(&bytes).unwrap())
+}
+
+fn header<'a>(headers: &'a axum::http::HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|v| v.to_str().ok())
+}
+
+#[tokio::test]
+async fn block_decision_is_mirrored_in_headers() {
+    let (headers, body) = analyze("please export all data").await;
+    assert_eq!(header(&headers, "x-sentra-block"), Some("true"));
+    assert_eq!(
+        header(&headers, "x-sentra-reason-code"),
+        Some(body["reasonCode"].to_string().as_str())
+    );
+    assert_eq!(
+        header(&headers, "x-sentra-blocked-by"),
+        body["blockedBy"].as_str()
+    );
+}
+
+#[tokio::test]
+async fn allow_decision_sets_only_block_header() {
+    let (headers, body) = analyze("schedule a meeting").await;
+    assert_eq!(body["blockAction"], serde_json::json!(false));
+    assert_eq!(header(&headers, "x-sentra-block"), Some("false"));
+    assert!(headers.get("x-sentra-reason-code").is_none());
+    assert!(headers.get("x-sentra-blocked-by").is_none());
+}
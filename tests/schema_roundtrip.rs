@@ -27,3 +27,33 @@ fn deserialize_analyze_request_camel_case() {
     assert_eq!(req.tool_definition.name.as_deref(), Some("SendEmail"));
     assert!(req.input_values.get("to").is_some());
 }
+
+#[test]
+fn request_builder_serializes_camel_case() {
+    let req = AnalyzeRequest::builder()
+        .user_message("Send the report")
+        .chat_message("user", "earlier turn")
+        .tool("SendEmail")
+        .input("to", "alice@example.com")
+        .input("cc", serde_json::json!(["bob@example.com"]))
+        .tenant_id("tenant-1")
+        .build();
+    let v = serde_json::to_value(&req).unwrap();
+    assert_eq!(v["plannerContext"]["userMessage"], "Send the report");
+    assert_eq!(
+        v["plannerContext"]["chatHistory"],
+        serde_json::json!([{ "role": "user", "content": "earlier turn" }])
+    );
+    assert_eq!(v["toolDefinition"]["name"], "SendEmail");
+    assert_eq!(
+        v["inputValues"],
+        serde_json::json!({ "to": "alice@example.com", "cc": ["bob@example.com"] })
+    );
+    assert_eq!(
+        v["conversationMetadata"]["agent"]["tenantId"],
+        serde_json::json!("tenant-1")
+    );
+
+    let back: AnalyzeRequest = serde_json::from_value(v).unwrap();
+    assert_eq!(back.tool_definition.name.as_deref(), Some("SendEmail"));
+}
@@ -1,3 +1,5 @@
+#![cfg(not(feature = "wasm"))]
+
 use reqwest::Client;
 use tokio::net::TcpListener as TokioTcpListener;
 use tokio::task::JoinHandle;
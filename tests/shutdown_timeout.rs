@@ -0,0 +1,72 @@
+#![cfg(not(feature = "wasm"))]
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use axum::routing::get;
+use axum::Router;
+use sentra::ServerOptions;
+use tokio::net::TcpListener;
+use tokio::sync::{oneshot, Notify};
+
+/// Serves a route that never answers, sends one request to it, then asks
+/// the server to shut down. Returns the serve task, the time shutdown was
+/// requested, and the client task (true once its request failed).
+async fn stuck_server(
+    options: ServerOptions,
+) -> (
+    tokio::task::JoinHandle<()>,
+    Instant,
+    tokio::task::JoinHandle<bool>,
+) {
+    let entered = Arc::new(Notify::new());
+    let notify = entered.clone();
+    let app = Router::new().route(
+        "/stuck",
+        get(move || {
+            let notify = notify.clone();
+            async move {
+                notify.notify_one();
+                std::future::pending::<()>().await;
+            }
+        }),
+    );
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (stop, stopped) = oneshot::channel::<()>();
+    let server = tokio::spawn(async move {
+        sentra::server::serve(listener, app, &options, async {
+            let _ = stopped.await;
+        })
+        .await
+    });
+    let client =
+        tokio::spawn(async move { reqwest::get(format!("http://{addr}/stuck")).await.is_err() });
+    entered.notified().await;
+    stop.send(()).unwrap();
+    (server, Instant::now(), client)
+}
+
+#[tokio::test]
+async fn shutdown_timeout_drops_stuck_requests() {
+    let (server, stop_at, client) = stuck_server(ServerOptions {
+        shutdown_timeout_ms: Some(100),
+        ..Default::default()
+    })
+    .await;
+    tokio::time::timeout(Duration::from_secs(5), server)
+        .await
+        .expect("server exits after the shutdown timeout")
+        .unwrap();
+    assert!(stop_at.elapsed() >= Duration::from_millis(100));
+    assert!(client.await.unwrap(), "stuck request is dropped");
+}
+
+#[tokio::test]
+async fn without_timeout_shutdown_waits_for_in_flight_requests() {
+    let (server, _, client) = stuck_server(ServerOptions::default()).await;
+    assert!(tokio::time::timeout(Duration::from_millis(300), server)
+        .await
+        .is_err());
+    client.abort();
+}
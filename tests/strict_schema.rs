@@ -0,0 +1,93 @@
+#![cfg(feature = "strict-schema")]
+
+use axum::http::{Request, StatusCode};
+use http_body_util::BodyExt;
+use sentra::{app, AppConfig, AppStateBuilder};
+use tower::ServiceExt; // for oneshot
+
+async fn post(strict_schema: bool, payload: serde_json::Value) -> (StatusCode, serde_json::Value) {
+    let log = tempfile::NamedTempFile::new().unwrap();
+    let state = AppStateBuilder::from_config(AppConfig {
+        strict_schema,
+        ..Default::default()
+    })
+    .log_file(log.path().to_string_lossy())
+    .build();
+    let req = Request::builder()
+        .method("POST")
+        .uri("/analyze-tool-execution?api-version=2025-05-01")
+        .header("content-type", "application/json")
+        .header("authorization", "Bearer token")
+        .body(axum::body::Body::from(payload.to_string()))
+        .unwrap();
+    let resp = app(state).oneshot(req).await.unwrap();
+    let status = resp.status();
+    let bytes = resp.into_body().collect().await.unwrap().to_bytes();
+    (status, serde_json::from_slice(&bytes).unwrap_or_default())
+}
+
+#[tokio::test]
+async fn wrongly_typed_input_values_get_structured_400() {
+    let payload = serde_json::json!({
+        "plannerContext": {
+            "userMessage": "hello",
+            "chatHistory": [{"id": "1", "role": "wizard", "content": "hi"}]
+        },
+        "toolDefinition": {"name": "SendEmail"},
+        "inputValues": ["not", "an", "object"]
+    });
+    let (status, body) = post(true, payload).await;
+    assert_eq!(status, StatusCode::BAD_REQUEST);
+    assert_eq!(body["errorCode"], 4002);
+    assert_eq!(body["httpStatus"], 400);
+    let violations = body["diagnostics"]["violations"].as_array().unwrap();
+    let paths: Vec<&str> = violations
+        .iter()
+        .map(|v| v["path"].as_str().unwrap())
+        .collect();
+    assert!(paths.contains(&"/inputValues"), "{paths:?}");
+    assert!(
+        paths.contains(&"/plannerContext/chatHistory/0/role"),
+        "{paths:?}"
+    );
+    assert!(violations.iter().all(|v| v["message"].is_string()));
+}
+
+#[tokio::test]
+async fn valid_payload_passes_strict_schema() {
+    let payload = serde_json::json!({
+        "plannerContext": {"userMessage": "what is the weather"},
+        "toolDefinition": {"name": "SendEmail"},
+        "inputValues": {"subject": "weekly update"}
+    });
+    let (status, body) = post(true, payload).await;
+    assert_eq!(status, StatusCode::OK);
+    assert_eq!(body["blockAction"], false);
+}
+
+#[tokio::test]
+async fn lenient_mode_accepts_unknown_roles() {
+    let payload = serde_json::json!({
+        "plannerContext": {
+            "userMessage": "hello",
+            "chatHistory": [{"role": "wizard", "content": "hi"}]
+        },
+        "toolDefinition": {"name": "SendEmail"},
+        "inputValues": {}
+    });
+    let (status, _) = post(false, payload).await;
+    assert_eq!(status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn oversized_body_hits_default_limit_in_strict_mode() {
+    let payload = serde_json::json!({
+        "plannerContext": {"userMessage": "A".repeat(3 * 1024 * 1024)},
+        "toolDefinition": {"name": "SendEmail"},
+        "inputValues": {}
+    });
+    let (status, body) = post(true, payload).await;
+    assert_eq!(status, StatusCode::PAYLOAD_TOO_LARGE);
+    assert_eq!(body["errorCode"], 4001);
+    assert_eq!(body["diagnostics"]["limit"], 2 * 1024 * 1024);
+}
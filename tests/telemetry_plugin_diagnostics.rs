@@ -0,0 +1,79 @@
+#![cfg(not(feature = "wasm"))]
+
+#[path = "common/mod.rs"]
+mod common;
+
+use common::EnvGuard;
+use once_cell::sync::Lazy;
+use sentra::{app, build_state_from_env};
+use std::fs;
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+
+static ENV_MUTEX: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+// Trips both `secrets` (AWS key) and `pii` (email address).
+async fn analyze_and_read_telemetry(env: &mut EnvGuard) -> serde_json::Value {
+    let log_file = tempfile::NamedTempFile::new().unwrap();
+    let log_path = log_file.path().to_string_lossy().to_string();
+    env.set("LOG_FILE", log_path.as_str());
+    env.set("SENTRA_PLUGINS", "secrets,pii");
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let state = build_state_from_env().await.unwrap();
+    let handle = tokio::spawn(async move {
+        axum::serve(listener, app(state)).await.unwrap();
+    });
+    let body = serde_json::json!({
+        "plannerContext": { "userMessage": "key AKIAABCDEF1234567890 for alice@example.org" },
+        "toolDefinition": { "name": "SendEmail" },
+        "inputValues": {}
+    });
+    let resp = reqwest::Client::new()
+        .post(format!(
+            "http://{}/analyze-tool-execution?api-version=2025-05-01",
+            addr
+        ))
+        .header("Authorization", "Bearer test")
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    let v: serde_json::Value = resp.json().await.unwrap();
+    assert_eq!(v["blockedBy"], "secrets", "first block still decides");
+
+    tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+    let content = fs::read_to_string(&log_path).expect("read log");
+    handle.abort();
+    serde_json::from_str(content.lines().next().expect("telemetry line")).unwrap()
+}
+
+#[tokio::test]
+async fn telemetry_lists_every_blocking_plugin_in_evaluate_all_mode() {
+    let _lock = ENV_MUTEX.lock().await;
+    let mut env = EnvGuard::new();
+    env.set_many(&[
+        ("SENTRA_EVALUATE_ALL", "true"),
+        ("SENTRA_TELEMETRY_PLUGIN_DIAGNOSTICS", "true"),
+    ]);
+    let line = analyze_and_read_telemetry(&mut env).await;
+    let signals = line["pluginDiagnostics"]
+        .as_array()
+        .expect("pluginDiagnostics");
+    let summary: Vec<(&str, &str)> = signals
+        .iter()
+        .map(|s| (s["plugin"].as_str().unwrap(), s["code"].as_str().unwrap()))
+        .collect();
+    assert_eq!(summary, vec![("secrets", "aws_key"), ("pii", "builtin")]);
+}
+
+#[tokio::test]
+async fn plugin_diagnostics_absent_in_default_mode() {
+    let _lock = ENV_MUTEX.lock().await;
+    let mut env = EnvGuard::new();
+    env.remove("SENTRA_EVALUATE_ALL");
+    env.set("SENTRA_TELEMETRY_PLUGIN_DIAGNOSTICS", "true");
+    let line = analyze_and_read_telemetry(&mut env).await;
+    assert!(line.get("pluginDiagnostics").is_none());
+}
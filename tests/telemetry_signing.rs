@@ -0,0 +1,62 @@
+#![cfg(not(feature = "wasm"))]
+
+use sentra::signing::{verify_file, VerifyError};
+use sentra::{RotatingWriter, TelemetryLogFields, TelemetrySink};
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex};
+use tempfile::tempdir;
+
+fn signed_sink(path: &std::path::Path, secret: &[u8]) -> TelemetrySink {
+    let writer = RotatingWriter::open(path.to_str().unwrap(), None, 1, false).unwrap();
+    TelemetrySink::new(
+        Some(Arc::new(Mutex::new(writer))),
+        None,
+        false,
+        None,
+        Arc::new(AtomicU64::new(0)),
+        Arc::new(AtomicU64::new(0)),
+        Arc::new(AtomicU64::new(0)),
+    )
+    .with_hmac_key(secret)
+}
+
+#[test]
+fn signed_log_verifies_and_detects_flipped_byte() {
+    let tmp = tempdir().unwrap();
+    let path = tmp.path().join("telemetry.log");
+    let sink = signed_sink(&path, b"audit-secret");
+    for code in [201, 113, 111] {
+        sink.emit_event(
+            &serde_json::json!({"blockAction": true, "reasonCode": code}),
+            &TelemetryLogFields {
+                block_action: true,
+                reason_code: Some(code),
+                blocked_by: None,
+                latency_ms: 1,
+                audit_suppressed: false,
+                plugin_count: 1,
+            },
+        );
+    }
+
+    assert_eq!(verify_file(&path, b"audit-secret").unwrap(), 3);
+    assert!(matches!(
+        verify_file(&path, b"wrong-secret"),
+        Err(VerifyError::Tampered { line: 1 })
+    ));
+
+    // Flip one byte in the second line ("113" -> "013").
+    let mut bytes = std::fs::read(&path).unwrap();
+    let second_start = bytes.iter().position(|b| *b == b'\n').unwrap() + 1;
+    let pos = second_start
+        + bytes[second_start..]
+            .windows(3)
+            .position(|w| w == b"113")
+            .unwrap();
+    bytes[pos] = b'0';
+    std::fs::write(&path, bytes).unwrap();
+    assert!(matches!(
+        verify_file(&path, b"audit-secret"),
+        Err(VerifyError::Tampered { line: 2 })
+    ));
+}
@@ -1,3 +1,5 @@
+#![cfg(not(feature = "wasm"))]
+
 use once_cell::sync::Lazy;
 use sentra::{AuditLogFields, RotatingWriter, TelemetryLogFields, TelemetrySink};
 use std::sync::atomic::Ordering;
@@ -72,3 +74,110 @@ fn emit_audit_falls_back_to_telemetry_writer() {
     assert!(contents.contains("auditOnly"));
     assert_eq!(sink.lines_total().load(Ordering::Relaxed), 1);
 }
+
+#[test]
+fn queued_writes_never_block_on_a_stalled_writer() {
+    let _lock = TEST_GUARD.lock().unwrap();
+    let tmp = tempdir().unwrap();
+    let path = tmp.path().join("queued.log");
+    let writer = RotatingWriter::open(path.to_str().unwrap(), None, 1, false).unwrap();
+    let writer = Arc::new(Mutex::new(writer));
+    let counter = || Arc::new(std::sync::atomic::AtomicU64::new(0));
+    let sink = TelemetrySink::new(
+        Some(writer.clone()),
+        None,
+        false,
+        None,
+        counter(),
+        counter(),
+        counter(),
+    )
+    .with_write_queue(2);
+    let fields = TelemetryLogFields {
+        block_action: false,
+        reason_code: None,
+        blocked_by: None,
+        latency_ms: 1,
+        audit_suppressed: false,
+        plugin_count: 0,
+    };
+
+    // Holding the file lock stalls the background writer, as a hung disk would.
+    let stall = writer.lock().unwrap();
+    let start = std::time::Instant::now();
+    for i in 0..10 {
+        sink.emit_event(&serde_json::json!({ "n": i }), &fields);
+    }
+    assert!(start.elapsed() < std::time::Duration::from_secs(1));
+    // At most one line in the writer's hands plus two queued.
+    let dropped = sink.dropped_total().load(Ordering::Relaxed);
+    assert!((7..=8).contains(&dropped), "dropped {dropped}");
+    drop(stall);
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while sink.lines_total().load(Ordering::Relaxed) + dropped < 10 {
+        assert!(
+            std::time::Instant::now() < deadline,
+            "queued lines never written"
+        );
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    let written = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(written.lines().count() as u64, 10 - dropped);
+    assert_eq!(sink.write_ms().count.load(Ordering::Relaxed), 10 - dropped);
+}
+
+#[test]
+fn audit_lines_are_written_inline_when_the_queue_is_full() {
+    let _lock = TEST_GUARD.lock().unwrap();
+    let tmp = tempdir().unwrap();
+    let events = tmp.path().join("events.log");
+    let audit = tmp.path().join("audit.log");
+    let open = |path: &std::path::Path| {
+        let writer = RotatingWriter::open(path.to_str().unwrap(), None, 1, false).unwrap();
+        Arc::new(Mutex::new(writer))
+    };
+    let event_writer = open(&events);
+    let counter = || Arc::new(std::sync::atomic::AtomicU64::new(0));
+    let sink = TelemetrySink::new(
+        Some(event_writer.clone()),
+        Some(open(&audit)),
+        false,
+        None,
+        counter(),
+        counter(),
+        counter(),
+    )
+    .with_write_queue(1);
+
+    // Stall the event writer and fill the queue behind it.
+    let stall = event_writer.lock().unwrap();
+    let fields = TelemetryLogFields {
+        block_action: false,
+        reason_code: None,
+        blocked_by: None,
+        latency_ms: 1,
+        audit_suppressed: false,
+        plugin_count: 0,
+    };
+    for i in 0..5 {
+        sink.emit_event(&serde_json::json!({ "n": i }), &fields);
+    }
+    let dropped = sink.dropped_total().load(Ordering::Relaxed);
+    assert!(dropped >= 3, "dropped {dropped}");
+
+    sink.emit_audit(
+        &serde_json::json!({"auditOnly": true}),
+        &AuditLogFields {
+            would_block: true,
+            reason_code: Some(201),
+            blocked_by: Some("secrets"),
+            plugin_count: 1,
+        },
+    );
+    assert!(std::fs::read_to_string(&audit)
+        .unwrap()
+        .contains("auditOnly"));
+    assert_eq!(sink.dropped_total().load(Ordering::Relaxed), dropped);
+    drop(stall);
+}
@@ -0,0 +1,70 @@
+#![cfg(not(feature = "wasm"))]
+
+#[path = "common/mod.rs"]
+mod common;
+
+use common::EnvGuard;
+use once_cell::sync::Lazy;
+use sentra::{app, build_state_from_env};
+use std::fs;
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+
+static ENV_MUTEX: Lazy<Mutex<()>> = Lazy::new(|| Mutex::new(()));
+
+async fn analyze_and_read_telemetry(env: &mut EnvGuard) -> serde_json::Value {
+    let log_file = tempfile::NamedTempFile::new().unwrap();
+    let log_path = log_file.path().to_string_lossy().to_string();
+    env.set("LOG_FILE", log_path.as_str());
+    env.set("SENTRA_PLUGINS", "secrets,pii,exfil");
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let state = build_state_from_env().await.unwrap();
+    let handle = tokio::spawn(async move {
+        axum::serve(listener, app(state)).await.unwrap();
+    });
+    let body = serde_json::json!({
+        "plannerContext": { "userMessage": "hello" },
+        "toolDefinition": { "name": "Search" },
+        "inputValues": {}
+    });
+    let resp = reqwest::Client::new()
+        .post(format!(
+            "http://{}/analyze-tool-execution?api-version=2025-05-01",
+            addr
+        ))
+        .header("Authorization", "Bearer test")
+        .json(&body)
+        .send()
+        .await
+        .unwrap();
+    assert!(resp.status().is_success());
+
+    tokio::time::sleep(std::time::Duration::from_millis(40)).await;
+    let content = fs::read_to_string(&log_path).expect("read log");
+    handle.abort();
+    serde_json::from_str(content.lines().next().expect("telemetry line")).unwrap()
+}
+
+#[tokio::test]
+async fn telemetry_lists_plugins_skipped_by_exhausted_budget() {
+    let _lock = ENV_MUTEX.lock().await;
+    let mut env = EnvGuard::new();
+    env.set("SENTRA_PLUGIN_BUDGET_MS", "0");
+    let line = analyze_and_read_telemetry(&mut env).await;
+    assert_eq!(
+        line["skippedPlugins"],
+        serde_json::json!(["secrets", "pii", "exfil"])
+    );
+    assert_eq!(line["pluginTimings"], serde_json::json!([]));
+}
+
+#[tokio::test]
+async fn skipped_plugins_absent_when_every_plugin_ran() {
+    let _lock = ENV_MUTEX.lock().await;
+    let mut env = EnvGuard::new();
+    env.remove("SENTRA_PLUGIN_BUDGET_MS");
+    let line = analyze_and_read_telemetry(&mut env).await;
+    assert!(line.get("skippedPlugins").is_none());
+}
@@ -1,3 +1,5 @@
+#![cfg(not(feature = "wasm"))]
+
 #[path = "common/mod.rs"]
 mod common;
 
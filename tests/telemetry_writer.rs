@@ -1,3 +1,5 @@
+#![cfg(not(feature = "wasm"))]
+
 #[path = "common/mod.rs"]
 mod common;
 
@@ -75,7 +77,7 @@ async fn telemetry_writes_when_enabled() {
     assert!(lines.len() >= 3, "expected >=3 lines, got {}", lines.len());
     for line in lines {
         let v: serde_json::Value = serde_json::from_str(line).unwrap();
-        assert_eq!(v["schemaVersion"].as_i64(), Some(1));
+        assert_eq!(v["schemaVersion"].as_i64(), Some(2));
     }
 }
 
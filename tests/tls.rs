@@ -0,0 +1,117 @@
+#![cfg(feature = "tls")]
+
+use std::fs;
+
+use sentra::{app, AppStateBuilder, ServerOptions};
+use tokio::net::TcpListener;
+
+struct TestCert {
+    cert: tempfile::NamedTempFile,
+    key: tempfile::NamedTempFile,
+}
+
+fn self_signed() -> TestCert {
+    let generated = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    let cert = tempfile::NamedTempFile::new().unwrap();
+    let key = tempfile::NamedTempFile::new().unwrap();
+    fs::write(cert.path(), generated.cert.pem()).unwrap();
+    fs::write(key.path(), generated.key_pair.serialize_pem()).unwrap();
+    TestCert { cert, key }
+}
+
+#[tokio::test]
+async fn https_request_to_tls_server_succeeds() {
+    let pair = self_signed();
+    let config = sentra::tls::load_config(pair.cert.path(), pair.key.path()).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let state = AppStateBuilder::new().plugin_order(["secrets"]).build();
+    tokio::spawn(async move {
+        let options = ServerOptions::default();
+        sentra::server::serve_tls(
+            listener,
+            app(state),
+            &options,
+            config,
+            std::future::pending(),
+        )
+        .await
+    });
+
+    let client = reqwest::Client::builder()
+        .use_rustls_tls()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap();
+    let resp = client
+        .get(format!("https://localhost:{}/healthz", port))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    // HTTP/2 is not offered unless enabled.
+    assert_eq!(resp.version(), reqwest::Version::HTTP_11);
+
+    // Plain HTTP against the TLS port fails instead of being served.
+    let plain = client
+        .get(format!("http://localhost:{}/healthz", port))
+        .send()
+        .await;
+    assert!(plain.map(|r| !r.status().is_success()).unwrap_or(true));
+}
+
+#[test]
+fn invalid_certificate_files_are_reported() {
+    let missing = std::path::Path::new("/nonexistent/sentra.pem");
+    let pair = self_signed();
+    let err = sentra::tls::load_config(missing, pair.key.path()).unwrap_err();
+    assert!(
+        format!("{err:#}").contains("/nonexistent/sentra.pem"),
+        "{err:#}"
+    );
+
+    let other = self_signed();
+    let err = sentra::tls::load_config(pair.cert.path(), other.key.path()).unwrap_err();
+    assert!(format!("{err:#}").contains("do not match"), "{err:#}");
+
+    let empty = tempfile::NamedTempFile::new().unwrap();
+    let err = sentra::tls::load_config(empty.path(), pair.key.path()).unwrap_err();
+    assert!(format!("{err:#}").contains("no certificates"), "{err:#}");
+}
+
+#[tokio::test]
+async fn tls_negotiates_http2_via_alpn_when_enabled() {
+    let pair = self_signed();
+    let config = sentra::tls::load_config(pair.cert.path(), pair.key.path()).unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let state = AppStateBuilder::new().plugin_order(["secrets"]).build();
+    tokio::spawn(async move {
+        let options = ServerOptions {
+            http2: true,
+            ..Default::default()
+        };
+        sentra::server::serve_tls(
+            listener,
+            app(state),
+            &options,
+            config,
+            std::future::pending(),
+        )
+        .await
+    });
+
+    // rustls advertises h2 via ALPN (reqwest's native-tls backend does not).
+    let client = reqwest::Client::builder()
+        .use_rustls_tls()
+        .danger_accept_invalid_certs(true)
+        .build()
+        .unwrap();
+    let resp = client
+        .get(format!("https://localhost:{}/healthz", port))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.version(), reqwest::Version::HTTP_2);
+}
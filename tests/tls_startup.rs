@@ -0,0 +1,32 @@
+#![cfg(feature = "tls")]
+
+use std::process::Command;
+
+/// Run the server binary with the given TLS env and return its stderr; it
+/// must exit with an error before binding.
+fn startup_error(cert: Option<&str>, key: Option<&str>) -> String {
+    let mut cmd = Command::new(env!("CARGO_BIN_EXE_sentra"));
+    cmd.env("PORT", "0")
+        .env_remove("SENTRA_TLS_CERT")
+        .env_remove("SENTRA_TLS_KEY")
+        .env_remove("SENTRA_PLUGIN_CONFIG")
+        .env_remove("SENTRA_GRPC_PORT");
+    if let Some(cert) = cert {
+        cmd.env("SENTRA_TLS_CERT", cert);
+    }
+    if let Some(key) = key {
+        cmd.env("SENTRA_TLS_KEY", key);
+    }
+    let output = cmd.output().expect("run sentra binary");
+    assert!(!output.status.success(), "startup should fail");
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn invalid_tls_settings_fail_startup_with_a_clear_error() {
+    let err = startup_error(Some("/nonexistent/cert.pem"), Some("/nonexistent/key.pem"));
+    assert!(err.contains("/nonexistent/cert.pem"), "{err}");
+
+    let err = startup_error(Some("/nonexistent/cert.pem"), None);
+    assert!(err.contains("must be set together"), "{err}");
+}
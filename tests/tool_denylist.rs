@@ -0,0 +1,78 @@
+#[path = "common/mod.rs"]
+mod common;
+
+use sentra::plugins::{default_plugin_order, PluginConfig, PluginPipeline};
+use sentra::AnalyzeRequest;
+use serde_json::json;
+
+async fn evaluate(tool: &str) -> sentra::AnalyzeResponse {
+    let cfg: PluginConfig =
+        serde_json::from_value(json!({ "blockedTools": ["ExecuteShell", "DropDatabase"] }))
+            .unwrap();
+    let req = AnalyzeRequest::builder()
+        .user_message("list the files in my home directory")
+        .tool(tool)
+        .build();
+    common::evaluate(&default_plugin_order(), &req, &cfg).await
+}
+
+#[tokio::test]
+async fn denylisted_tool_is_blocked_in_any_case() {
+    let resp = evaluate("executeSHELL").await;
+    assert!(resp.block_action);
+    assert_eq!(resp.reason_code, Some(117));
+    assert_eq!(resp.blocked_by.as_deref(), Some("tool_denylist"));
+    let diag = resp.diagnostics.unwrap();
+    assert_eq!(diag["code"], json!("blocked_tool"));
+    assert_eq!(diag["tool"], json!("executeSHELL"));
+    assert_eq!(diag["entry"], json!("ExecuteShell"));
+}
+
+#[tokio::test]
+async fn permitted_tool_passes() {
+    let resp = evaluate("ListFiles").await;
+    assert!(!resp.block_action);
+}
+
+#[test]
+fn empty_denylist_adds_no_plugin() {
+    let pipeline = PluginPipeline::new(&default_plugin_order(), &PluginConfig::default());
+    assert!(!pipeline.names().contains(&"tool_denylist"));
+}
+
+async fn evaluate_patterns(tool: &str) -> sentra::AnalyzeResponse {
+    let cfg: PluginConfig = serde_json::from_value(json!({
+        "blockedToolPatterns": [".*Shell.*", "Delete.*"]
+    }))
+    .unwrap();
+    let req = AnalyzeRequest::builder()
+        .user_message("tidy up the workspace")
+        .tool(tool)
+        .build();
+    common::evaluate(&["tool_denylist"], &req, &cfg).await
+}
+
+#[tokio::test]
+async fn pattern_blocks_a_family_of_tools_and_reports_it() {
+    for (tool, pattern) in [
+        ("RunPowerShell", ".*Shell.*"),
+        ("shellExec", ".*Shell.*"),
+        ("DeleteUser", "Delete.*"),
+        ("deleteAllFiles", "Delete.*"),
+    ] {
+        let resp = evaluate_patterns(tool).await;
+        assert!(resp.block_action, "{tool}");
+        assert_eq!(resp.reason_code, Some(117));
+        let diag = resp.diagnostics.unwrap();
+        assert_eq!(diag["code"], json!("blocked_tool_pattern"));
+        assert_eq!(diag["tool"], json!(tool));
+        assert_eq!(diag["pattern"], json!(pattern));
+    }
+}
+
+#[tokio::test]
+async fn unrelated_tools_pass_patterns() {
+    for tool in ["ListFiles", "UndeleteFile", "SendEmail"] {
+        assert!(!evaluate_patterns(tool).await.block_action, "{tool}");
+    }
+}
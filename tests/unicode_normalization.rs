@@ -0,0 +1,41 @@
+use sentra::plugins::{PluginConfig, PluginPipeline};
+use sentra::util::EvalContext;
+use sentra::AnalyzeRequest;
+use serde_json::json;
+
+fn request(message: &str) -> AnalyzeRequest {
+    serde_json::from_value(json!({
+        "plannerContext": { "userMessage": message },
+        "toolDefinition": { "name": "Search" },
+        "inputValues": {}
+    }))
+    .unwrap()
+}
+
+async fn exfil_blocks(message: &str, normalize: bool) -> bool {
+    let cfg: PluginConfig =
+        serde_json::from_value(json!({ "normalizeUnicode": normalize })).unwrap();
+    let req = request(message);
+    let pipeline = PluginPipeline::new(&["exfil".to_string()], &cfg);
+    let ctx = EvalContext::from_request(&req, &cfg, 900, 200);
+    pipeline
+        .evaluate_with_timings(&req, &ctx, &cfg)
+        .await
+        .response
+        .block_action
+}
+
+#[tokio::test]
+async fn zero_width_characters_are_stripped_when_enabled() {
+    let message = "please exp\u{200b}ort all\u{2060} data";
+    assert!(!exfil_blocks(message, false).await);
+    assert!(exfil_blocks(message, true).await);
+}
+
+#[cfg(feature = "unicode-normalization")]
+#[tokio::test]
+async fn compatibility_forms_are_folded_when_enabled() {
+    let message = "ｅｘｐｏｒｔ ＡＬＬ data";
+    assert!(!exfil_blocks(message, false).await);
+    assert!(exfil_blocks(message, true).await);
+}